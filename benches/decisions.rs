@@ -0,0 +1,51 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use figgie_auto::{Book, Card, Direction, Event, EventDrivenPlayer, Level, LogLevel, MarketContext, Order, PlayerName, Strategy, StrategyConfig};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+
+
+const SUITS: [Card; 4] = [Card::Spade, Card::Club, Card::Diamond, Card::Heart];
+
+// a random but sane book: a few levels a side, bids below asks, a last trade somewhere in between most of the time
+fn random_book(rng: &mut StdRng) -> Book {
+    let mut book = Book::new();
+    let mid = rng.gen_range(3..12);
+    for tick in 0..rng.gen_range(0..4) {
+        book.insert(&Direction::Buy, Level { price: mid - 1 - tick, player_name: PlayerName::new(&format!("Bidder#{}", tick)), size: 1 });
+    }
+    for tick in 0..rng.gen_range(0..4) {
+        book.insert(&Direction::Sell, Level { price: mid + 1 + tick, player_name: PlayerName::new(&format!("Seller#{}", tick)), size: 1 });
+    }
+    if rng.gen_bool(0.7) {
+        book.record_trade(mid, Direction::Buy, 1);
+    }
+    book
+}
+
+// PickOff's decision function over one update's worth of books, all four suits, nothing is sent anywhere
+fn decisions(c: &mut Criterion) {
+    figgie_auto::utils::logging::set_level(LogLevel::Error); // printing every decision would be all we measured
+    let mut rng = StdRng::seed_from_u64(0);
+    let (event_sender, _) = tokio::sync::broadcast::channel::<Event>(1);
+    let (order_sender, _order_receiver) = kanal::unbounded_async::<Order>();
+    let player = EventDrivenPlayer::new(PlayerName::new("Bench"), Strategy::PickOff, StrategyConfig::default(), event_sender, Arc::new(order_sender));
+
+    let mut group = c.benchmark_group("pick_off");
+    group.throughput(Throughput::Elements(SUITS.len() as u64));
+    group.bench_function("decide_update", |b| b.iter_batched(
+        || [random_book(&mut rng), random_book(&mut rng), random_book(&mut rng), random_book(&mut rng)],
+        |books| {
+            let mut orders = 0;
+            for (card, book) in SUITS.iter().zip(books) {
+                orders += player.decide_pick_off(&MarketContext::new(120, card.clone(), book, 2)).len();
+            }
+            orders
+        },
+        BatchSize::SmallInput,
+    ));
+    group.finish();
+}
+
+criterion_group!(benches, decisions);
+criterion_main!(benches);
@@ -0,0 +1,38 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use figgie_auto::{Card, Direction, Event, Exchange, Inventory, LogLevel, Order, PlayerName};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+
+
+const SUITS: [Card; 4] = [Card::Spade, Card::Club, Card::Diamond, Card::Heart];
+
+// random buys and sells straight into the matching engine, one order to its trades per iteration
+fn matching(c: &mut Criterion) {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    let mut rng = StdRng::seed_from_u64(0);
+    let player_names: Vec<PlayerName> = (0..5).map(|seat| PlayerName::new(&format!("Bench#{}", seat))).collect();
+    let (event_sender, _event_receiver) = tokio::sync::broadcast::channel::<Event>(1); // held so rejections have somewhere to go
+    let (_, order_receiver) = kanal::unbounded_async::<Order>();
+    let (_, snapshot_receiver) = kanal::unbounded_async::<PlayerName>();
+    let mut exchange = Exchange::new(usize::MAX / 2, player_names.clone(), event_sender, Arc::new(order_receiver), Arc::new(snapshot_receiver));
+    for inventory in exchange.player_inventories.values_mut() {
+        *inventory = Inventory { spades: usize::MAX / 2, clubs: usize::MAX / 2, diamonds: usize::MAX / 2, hearts: usize::MAX / 2 }; // nobody runs out
+    }
+
+    let mut group = c.benchmark_group("exchange");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("process_order", |b| b.iter_batched(
+        || {
+            let player_name = player_names[rng.gen_range(0..player_names.len())].clone();
+            let direction = if rng.gen_bool(0.5) { Direction::Buy } else { Direction::Sell };
+            Order::new(player_name, rng.gen_range(1..15), direction, SUITS[rng.gen_range(0..4)].clone(), rng.gen_range(1..3))
+        },
+        |order| exchange.process_order(order),
+        BatchSize::SmallInput,
+    ));
+    group.finish();
+}
+
+criterion_group!(benches, matching);
+criterion_main!(benches);
@@ -0,0 +1,64 @@
+use super::{Card, Inventory, PlayerName, Pot, hand_size};
+use rand::prelude::SliceRandom;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::collections::HashMap;
+
+
+// everything a round's deal decides, drawn up front when several runs need to face the same cards (see sweep)
+// the deck is Figgie's 40 cards: 12 of the common suit, 10 of two others and 8 of the last, the goal suit is the
+// common suit's same-color partner, so it's an 8 or a 10
+#[derive(Debug, Clone)]
+pub struct Deal {
+    pub common_suit: Card,
+    pub goal_suit: Card,
+    pub suit_counts: HashMap<Card, usize>, // 12 of the common suit, 8 of one other and 10 of the rest
+    pub inventories: HashMap<PlayerName, Inventory>,
+}
+
+impl Deal {
+    // consumes `rng` exactly as MatchMaker always has, so a seeded game deals the same cards whether or not they were drawn ahead
+    pub fn draw(player_names: &[PlayerName], rng: &mut StdRng) -> Self {
+        let suits = [Card::Spade, Card::Club, Card::Diamond, Card::Heart];
+        let common_suit = suits[rng.gen_range(0..=3)].clone();
+        let (goal_suit, suit_1, suit_2) = common_suit.get_other_cards();
+
+        let mut cards: Vec<Card> = Vec::new();
+        for _ in 0..12 { cards.push(common_suit.clone()) }
+        let mut suit_counts = HashMap::new();
+        suit_counts.insert(common_suit.clone(), 12);
+
+        // randomly pick one of the other 3 suits to be the one with 8 cards
+        let mut already_lucky = false;
+        for (idx, suit) in [suit_1, suit_2, goal_suit.clone()].iter().enumerate() {
+            let lucky_eight = rng.gen::<bool>();
+            let count = if !already_lucky && (idx == 2 || lucky_eight) {
+                already_lucky = true;
+                8
+            } else {
+                10
+            };
+            for _ in 0..count { cards.push(suit.clone()) }
+            suit_counts.insert(suit.clone(), count);
+        }
+
+        cards.shuffle(rng); // randomly shuffle the cards
+
+        let chunk_size = hand_size(player_names.len());
+        let inventories = player_names.iter().zip(cards.chunks(chunk_size)).map(|(player_name, chunk)| {
+            let mut inventory = Inventory::new();
+            inventory.count(chunk.to_vec());
+            (player_name.clone(), inventory)
+        }).collect();
+
+        Self { common_suit, goal_suit, suit_counts, inventories }
+    }
+}
+
+
+// one round's hands, its goal suit and the pot the antes make, for anything that wants to deal without a MatchMaker
+pub fn deal(player_names: &[PlayerName], rng: &mut StdRng) -> (HashMap<PlayerName, Inventory>, Card, usize) {
+    let deal = Deal::draw(player_names, rng);
+    let pot = Pot::new(player_names.len()).ante * player_names.len();
+    (deal.inventories, deal.goal_suit, pot)
+}
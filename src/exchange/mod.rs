@@ -0,0 +1,549 @@
+use super::{Card, Book, BookDiff, Level, SuitLadders, Inventory, Order, Event, Update, Trade, Direction, TimeInForce, RejectReason, CL, LogLevel, log, PlayerName};
+use tokio::sync::broadcast::Sender;
+use kanal::AsyncReceiver;
+use std::sync::Arc;
+use std::collections::HashMap;
+use std::time::Instant;
+
+
+pub const HEARTBEAT_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(1); // players call the feed stale after a few of these go missing
+
+pub struct Exchange {
+    pub player_names: Vec<PlayerName>,
+    pub books: HashMap<Card, Book>,
+    pub player_inventories: HashMap<PlayerName, Inventory>,
+    pub player_points: HashMap<PlayerName, usize>,
+    pub event_sender: Sender<Event>,
+    pub order_receiver: Arc<AsyncReceiver<Order>>,
+    pub snapshot_receiver: Arc<AsyncReceiver<PlayerName>>, // players asking to be resynced
+    pub round_start: Instant, // trade timestamps are measured from here
+    pub ladders: SuitLadders, // prices outside a suit's ladder are rejected, the rest snap to its nearest rung
+    pub heartbeat_tick: u64, // heartbeats sent so far, across rounds
+}
+
+impl Exchange {
+    pub fn new(
+        starting_balance: usize,
+        player_names: Vec<PlayerName>,
+        event_sender: Sender<Event>,
+        order_receiver: Arc<AsyncReceiver<Order>>,
+        snapshot_receiver: Arc<AsyncReceiver<PlayerName>>,
+    ) -> Self {
+
+        let mut player_inventories = HashMap::new();
+        let mut player_points = HashMap::new();
+        for player_name in &player_names {
+            player_points.insert(player_name.clone(), starting_balance);
+            player_inventories.insert(player_name.clone(), Inventory::new());
+        }
+
+        let mut books = HashMap::new();
+        books.insert(Card::Spade, Book::new());
+        books.insert(Card::Club, Book::new());
+        books.insert(Card::Diamond, Book::new());
+        books.insert(Card::Heart, Book::new());
+
+        Self {
+            player_names,
+            books,
+            player_inventories,
+            player_points,
+            event_sender,
+            order_receiver,
+            snapshot_receiver,
+            round_start: Instant::now(),
+            ladders: SuitLadders::default(),
+            heartbeat_tick: 0,
+        }
+    }
+
+    pub fn get_update(&self, trades: Vec<Trade>) -> Update {
+        debug_assert!(self.books.values().all(|book| !book.is_crossed()), "about to broadcast a crossed book");
+        Update {
+            spades: self.books.get(&Card::Spade).unwrap().clone(),
+            clubs: self.books.get(&Card::Club).unwrap().clone(),
+            diamonds: self.books.get(&Card::Diamond).unwrap().clone(),
+            hearts: self.books.get(&Card::Heart).unwrap().clone(),
+            trades,
+        }
+    }
+
+    pub fn broadcast_update(&self, trades: Vec<Trade>) {
+        let update_event = Event::Update(self.get_update(trades));
+        if let Err(e) = self.event_sender.send(update_event) {
+            println!("[!] Error sending update event: {:?}", e);
+        }
+    }
+
+    pub fn broadcast_accounts(&self) {
+        // payouts are 10 a goal card plus the majority's share of what's left, a tied majority splits it evenly
+        // and the odd points that don't divide stay with nobody, so a tied winner's delta can come up a point or two short
+        for (player_name, cash) in &self.player_points {
+            let account_update = Event::AccountUpdate {
+                player_name: player_name.clone(),
+                cash: *cash,
+            };
+            if let Err(e) = self.event_sender.send(account_update) {
+                println!("[!] Error sending account update event: {:?}", e);
+            }
+        }
+    }
+
+    pub fn broadcast_diff(&self, card: &Card) { // only `card`'s book moved
+        debug_assert!(!self.books.get(card).unwrap().is_crossed(), "about to broadcast a crossed {:?} book", card);
+        let diff = BookDiff {
+            changed: vec![(card.clone(), self.books.get(card).unwrap().clone())],
+            trades: Vec::new(),
+        };
+        if let Err(e) = self.event_sender.send(Event::Diff(diff)) {
+            println!("[!] Error sending diff event: {:?}", e);
+        }
+    }
+
+    pub fn broadcast_heartbeat(&mut self) {
+        self.heartbeat_tick += 1;
+        if let Err(e) = self.event_sender.send(Event::Heartbeat { tick: self.heartbeat_tick }) {
+            println!("[!] Error sending heartbeat event: {:?}", e);
+        }
+    }
+
+    pub fn acknowledge(&self, player_name: &PlayerName, seq: u64, accepted: bool) { // unsequenced orders (seq 0) go unanswered
+        if seq == 0 {
+            return;
+        }
+        if let Err(e) = self.event_sender.send(Event::OrderAck { player_name: player_name.clone(), seq, accepted }) {
+            println!("[!] Error sending order ack event: {:?}", e);
+        }
+    }
+
+    pub fn send_snapshot(&self, player_name: &PlayerName) {
+        let inventory = match self.player_inventories.get(player_name) {
+            Some(inventory) => *inventory,
+            None => return, // not at the table
+        };
+        let snapshot = Event::Snapshot {
+            player_name: player_name.clone(),
+            spades: self.books.get(&Card::Spade).unwrap().clone(),
+            clubs: self.books.get(&Card::Club).unwrap().clone(),
+            diamonds: self.books.get(&Card::Diamond).unwrap().clone(),
+            hearts: self.books.get(&Card::Heart).unwrap().clone(),
+            inventory,
+        };
+        if let Err(e) = self.event_sender.send(snapshot) {
+            println!("[!] Error sending snapshot event: {:?}", e);
+        }
+    }
+
+    pub fn reject(&self, order: &Order, reason: RejectReason) {
+        let rejected = Event::OrderRejected {
+            player_name: order.player_name.clone(),
+            card: order.card.clone(),
+            reason,
+        };
+        if let Err(e) = self.event_sender.send(rejected) {
+            println!("[!] Error sending order rejected event: {:?}", e);
+        }
+    }
+
+    pub fn reset_books(&mut self) {
+        self.books.get_mut(&Card::Spade).unwrap().reset_quotes();
+        self.books.get_mut(&Card::Club).unwrap().reset_quotes();
+        self.books.get_mut(&Card::Diamond).unwrap().reset_quotes();
+        self.books.get_mut(&Card::Heart).unwrap().reset_quotes();
+    }
+
+
+
+    pub async fn run(&mut self, round_duration: tokio::time::Duration, goal_suit: &Card) {
+        let start = tokio::time::Instant::now();
+        self.round_start = Instant::now();
+        let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+        while start.elapsed() < round_duration {
+
+            let snapshot_receiver = self.snapshot_receiver.clone();
+            let order = tokio::select! {
+                order = self.order_receiver.recv() => order,
+                _ = heartbeat.tick() => {
+                    self.broadcast_heartbeat();
+                    continue;
+                },
+                request = snapshot_receiver.recv(), if !snapshot_receiver.is_disconnected() => {
+                    if let Ok(player_name) = request {
+                        self.send_snapshot(&player_name);
+                    }
+                    continue;
+                }
+            };
+
+            if let Ok(order) = order {
+                let is_cancel = matches!(order.direction, Direction::Cancel);
+                let free_leg = order.paired.as_ref().is_some_and(|leg| leg.price == 0 || leg.size == 0);
+                if !is_cancel && (order.price == 0 || order.size == 0 || free_leg) { // No free lunches allowed
+                    self.acknowledge(&order.player_name, order.seq, false);
+                    continue;
+                }
+
+                log!(LogLevel::Debug, "[~] Processing order: {:?} | Queue: {}x", order, self.order_receiver.len());
+
+                let (card, player_name, seq) = (order.card.clone(), order.player_name.clone(), order.seq);
+                let trades = match self.process_order(order) {
+                    Some(trades) => trades,
+                    None => {
+                        self.acknowledge(&player_name, seq, false);
+                        continue;
+                    },
+                };
+
+                if !trades.is_empty() {
+                    // =-= Reset all the Books =-= //
+                    self.reset_books();
+
+                    // =-= Drain the Order Receiver =-= //
+                    let drain_amount = self.order_receiver.len();
+                    for _ in 0..drain_amount {
+                        if let Ok(Some(dropped)) = self.order_receiver.try_recv() {
+                            self.acknowledge(&dropped.player_name, dropped.seq, false);
+                        }
+                    }
+                }
+
+                self.print_books(goal_suit);
+
+                if trades.is_empty() {
+                    self.broadcast_diff(&card);
+                } else {
+                    self.broadcast_update(trades);
+                }
+                self.acknowledge(&player_name, seq, true);
+            }
+        }
+    }
+
+
+
+    // matches the order against the opposite side of its book, returns None if the order was thrown out entirely
+    // the book is never left crossed: matching below should see to that on its own, and if it ever doesn't (debug builds
+    // panic here) the best bid is pulled and sent back through as a fresh arrival until the book uncrosses
+    pub fn process_order(&mut self, mut order: Order) -> Option<Vec<Trade>> {
+        if let Some(leg) = order.paired.take() {
+            return self.process_quote(order, *leg);
+        }
+        let card = order.card.clone();
+        let mut trades = self.match_order(order)?;
+        debug_assert!(!self.books.get(&card).unwrap().is_crossed(), "matching left the {:?} book crossed", card);
+        while self.books.get(&card).unwrap().is_crossed() {
+            let bid = self.books.get_mut(&card).unwrap().bids.remove(0);
+            log!(LogLevel::Error, "[!] {:?} |:| Book crossed, rematching {:?}'s bid at {}", card, bid.player_name, bid.price);
+            let order = Order { layer: true, ..Order::new(bid.player_name, bid.price, Direction::Buy, card.clone(), bid.size) }; // keep its other bids
+            if let Some(rematched) = self.match_order(order) {
+                trades.extend(rematched);
+            }
+        }
+        Some(trades)
+    }
+
+    fn match_order(&mut self, mut order: Order) -> Option<Vec<Trade>> {
+        if let Direction::Cancel = order.direction {
+            // pull both sides, cancelling something that already traded or never rested is a no-op
+            let book = self.books.get_mut(&order.card).unwrap();
+            if !book.cancel(&order.player_name) {
+                return None;
+            }
+            return Some(Vec::new());
+        }
+
+        let ladder = self.ladders.get(&order.card);
+        if !ladder.contains(order.price) {
+            self.reject(&order, RejectReason::OffLadder);
+            return None;
+        }
+        order.price = ladder.round(order.price);
+
+        let is_buy = matches!(order.direction, Direction::Buy);
+        if !is_buy {
+            // check if the user has the inventory to sell this Card
+            let available = self.player_inventories.get(&order.player_name).unwrap().get(&order.card);
+            if available == 0 {
+                log!(LogLevel::Warn, "[!] {:?} | {:?} |:| Player does not have the inventory to sell this Card", order.player_name, order.card);
+                self.reject(&order, RejectReason::NoInventory);
+                return None;
+            }
+            order.size = order.size.min(available); // clamp to what the player actually holds
+        } else {
+            // ... and the cash to pay for it, every fill is at or under the order's price
+            let cash = self.player_points.get(&order.player_name).copied().unwrap_or(0);
+            if cash < order.price {
+                log!(LogLevel::Warn, "[!] {:?} | {:?} |:| Player does not have the points to buy this Card", order.player_name, order.card);
+                self.reject(&order, RejectReason::NoCash);
+                return None;
+            }
+            order.size = order.size.min(cash / order.price.max(1));
+        }
+
+        if order.tif == TimeInForce::FOK && self.fillable(&order) < order.size {
+            self.reject(&order, RejectReason::Killed);
+            return None;
+        }
+
+        let book = self.books.get_mut(&order.card).unwrap();
+        let mut trades: Vec<Trade> = Vec::new();
+
+        // walk the opposite side from the best level inward until the order is filled or stops crossing
+        // price-time priority comes from Book::insert keeping each side sorted by price with arrival order within a price,
+        // so the first level is always the best price that's been there longest, and every fill is at the resting price
+        loop {
+            let resting = if is_buy { book.asks.first() } else { book.bids.first() };
+            let resting = match resting {
+                Some(resting) => resting.clone(),
+                None => break,
+            };
+            let crosses = if is_buy { order.price >= resting.price } else { order.price <= resting.price };
+            if order.size == 0 || !crosses {
+                break;
+            }
+            if resting.player_name == order.player_name {
+                if trades.is_empty() {
+                    self.reject(&order, RejectReason::SelfCross);
+                    return None;
+                }
+                break; // already traded through the better levels, stop before our own
+            }
+
+            if trades.is_empty() {
+                if is_buy {
+                    log!(LogLevel::Info, CL::Green, "[-] Aggressing Player: {:?} | {:?} |:| Matched buy order!", order.player_name, order.card);
+                } else {
+                    log!(LogLevel::Info, CL::Red, "[-] Aggressing Player: {:?} | {:?} |:| Matched sell order!", order.player_name, order.card);
+                }
+            }
+
+            let (buyer, seller) = if is_buy {
+                (order.player_name.clone(), resting.player_name.clone())
+            } else {
+                (resting.player_name.clone(), order.player_name.clone())
+            };
+
+            // a resting ask can only deliver what its owner still holds, and a resting bid only buy what its owner can still pay for
+            let deliverable = self.player_inventories.get(&seller).map(|inventory| inventory.get(&order.card)).unwrap_or(0);
+            let affordable = self.player_points.get(&buyer).copied().unwrap_or(0) / resting.price.max(1);
+            let fill_size = order.size.min(resting.size).min(deliverable).min(affordable);
+
+            let timestamp = self.round_start.elapsed().as_millis() as u64;
+            for _ in 0..fill_size {
+                trades.push(Self::fill(&mut self.player_inventories, &mut self.player_points, &order, resting.price, &buyer, &seller, timestamp));
+            }
+            if fill_size > 0 {
+                book.record_trade(resting.price, order.direction.clone(), fill_size);
+            }
+            order.size -= fill_size;
+
+            // an ask whose owner ran out of cards comes off whole, as does a bid whose owner ran out of points
+            let (side, backed) = if is_buy { (&mut book.asks, deliverable) } else { (&mut book.bids, affordable) };
+            if order.size == 0 && fill_size < resting.size && fill_size < backed {
+                side[0].size -= fill_size; // partially consumed, the rest stays on the book
+            } else {
+                side.remove(0);
+            }
+        }
+
+        if let (Some(context), Some(first)) = (&order.context, trades.first()) {
+            // compare against what the player thought it was hitting, the gap is what latency cost (or saved) it
+            let seen = if is_buy { context.ask } else { context.bid };
+            log!(LogLevel::Debug, CL::Dull, "[~] {:?} | {:?} |:| Landed {}ms after deciding, saw {:?}, traded at {}", order.player_name, order.card, context.age_ms(), seen, first.price);
+        }
+
+        // whatever is left rests on the book, unless it already traded (the books get reset after a trade anyway) or was only good for now
+        if trades.is_empty() && order.size > 0 && order.tif == TimeInForce::GTC {
+            // Figgie quotes have to improve on the best one someone else is showing
+            let same_side = if is_buy { &book.bids } else { &book.asks };
+            let improves = match same_side.iter().find(|level| level.player_name != order.player_name) {
+                Some(best) => if is_buy { order.price > best.price } else { order.price < best.price },
+                None => true,
+            };
+            if !improves {
+                self.reject(&order, RejectReason::PriceNotImproving);
+                return None;
+            }
+
+            let level = Level {
+                price: order.price,
+                player_name: order.player_name.clone(),
+                size: order.size,
+            };
+            if order.layer {
+                book.layer(&order.direction, level);
+            } else {
+                book.insert(&order.direction, level);
+            }
+        }
+
+        Some(trades)
+    }
+
+    // a two-sided quote: both legs rest together or neither does, and neither may trade on the way in,
+    // so the book never shows one side of it alone
+    fn process_quote(&mut self, mut bid: Order, mut ask: Order) -> Option<Vec<Trade>> {
+        if bid.direction == Direction::Sell {
+            std::mem::swap(&mut bid, &mut ask);
+        }
+        let shape = if bid.direction != Direction::Buy || ask.direction != Direction::Sell || bid.card != ask.card || bid.player_name != ask.player_name {
+            Err(RejectReason::LegRejected) // not a quote at all
+        } else if bid.price >= ask.price {
+            Err(RejectReason::SelfCross)
+        } else {
+            Ok(())
+        };
+        let checks = [shape.clone().and_then(|_| self.check_quote(&mut bid)), shape.and_then(|_| self.check_quote(&mut ask))];
+        if checks.iter().any(Result::is_err) {
+            for (leg, check) in [&bid, &ask].into_iter().zip(checks) {
+                self.reject(leg, check.err().unwrap_or(RejectReason::LegRejected));
+            }
+            return None;
+        }
+
+        let book = self.books.get_mut(&bid.card).unwrap();
+        for leg in [bid, ask] {
+            let level = Level { price: leg.price, player_name: leg.player_name.clone(), size: leg.size };
+            if leg.layer {
+                book.layer(&leg.direction, level);
+            } else {
+                book.insert(&leg.direction, level);
+            }
+        }
+        Some(Vec::new())
+    }
+
+    // everything match_order would hold against a resting order, plus that it mustn't cross anyone, snaps the price to the ladder
+    fn check_quote(&self, leg: &mut Order) -> Result<(), RejectReason> {
+        let ladder = self.ladders.get(&leg.card);
+        if !ladder.contains(leg.price) {
+            return Err(RejectReason::OffLadder);
+        }
+        leg.price = ladder.round(leg.price);
+
+        let is_buy = leg.direction == Direction::Buy;
+        if !is_buy {
+            let available = self.player_inventories.get(&leg.player_name).map(|inventory| inventory.get(&leg.card)).unwrap_or(0);
+            if available == 0 {
+                return Err(RejectReason::NoInventory);
+            }
+            leg.size = leg.size.min(available);
+        } else {
+            let cash = self.player_points.get(&leg.player_name).copied().unwrap_or(0);
+            if cash < leg.price {
+                return Err(RejectReason::NoCash);
+            }
+            leg.size = leg.size.min(cash / leg.price.max(1));
+        }
+
+        let book = self.books.get(&leg.card).unwrap();
+        let opposite = if is_buy { book.asks.first() } else { book.bids.first() };
+        if let Some(resting) = opposite.filter(|resting| if is_buy { leg.price >= resting.price } else { leg.price <= resting.price }) {
+            return Err(if resting.player_name == leg.player_name { RejectReason::SelfCross } else { RejectReason::WouldCross });
+        }
+
+        let same_side = if is_buy { &book.bids } else { &book.asks };
+        let improves = match same_side.iter().find(|level| level.player_name != leg.player_name) {
+            Some(best) => if is_buy { leg.price > best.price } else { leg.price < best.price },
+            None => true,
+        };
+        if !improves {
+            return Err(RejectReason::PriceNotImproving);
+        }
+        Ok(())
+    }
+
+    // how much of the order would trade right now, walking the book the same way process_order does
+    fn fillable(&self, order: &Order) -> usize {
+        let is_buy = matches!(order.direction, Direction::Buy);
+        let book = self.books.get(&order.card).unwrap();
+        let side = if is_buy { &book.asks } else { &book.bids };
+        let mut delivered: HashMap<PlayerName, usize> = HashMap::new(); // a seller with several levels can only deliver its inventory once
+        let mut spent: HashMap<PlayerName, usize> = HashMap::new(); // ... and a buyer spend its points once
+        let mut fillable = 0;
+        for resting in side {
+            let crosses = if is_buy { order.price >= resting.price } else { order.price <= resting.price };
+            if !crosses || resting.player_name == order.player_name || fillable >= order.size {
+                break;
+            }
+            let (buyer, seller) = if is_buy { (&order.player_name, &resting.player_name) } else { (&resting.player_name, &order.player_name) };
+            let held = self.player_inventories.get(seller).map(|inventory| inventory.get(&order.card)).unwrap_or(0);
+            let cash = self.player_points.get(buyer).copied().unwrap_or(0);
+            let used = delivered.get(seller).copied().unwrap_or(0);
+            let paid = spent.get(buyer).copied().unwrap_or(0);
+            let fill_size = resting.size.min(held.saturating_sub(used)).min(cash.saturating_sub(paid) / resting.price.max(1)).min(order.size - fillable);
+            *delivered.entry(seller.clone()).or_insert(0) += fill_size;
+            *spent.entry(buyer.clone()).or_insert(0) += fill_size * resting.price;
+            fillable += fill_size;
+        }
+        fillable
+    }
+
+    fn fill(
+        player_inventories: &mut HashMap<PlayerName, Inventory>,
+        player_points: &mut HashMap<PlayerName, usize>,
+        order: &Order, // the aggressing order
+        price: usize,
+        buyer: &PlayerName,
+        seller: &PlayerName,
+        timestamp: u64,
+    ) -> Trade {
+        let card = &order.card;
+        // =-= Update the Inventories =-= //
+        let buyer_inventory = player_inventories.get_mut(buyer).unwrap();
+        buyer_inventory.change(card.clone(), true);
+
+        let seller_inventory = player_inventories.get_mut(seller).unwrap();
+        seller_inventory.change(card.clone(), false);
+
+
+        // =-= Update the Points =-= //
+        let buyer_points = player_points.get_mut(buyer).unwrap();
+        *buyer_points -= price;
+
+        let seller_points = player_points.get_mut(seller).unwrap();
+        *seller_points += price;
+
+
+        // =-= Package Trade =-= //
+        Trade {
+            card: card.clone(),
+            price,
+            buyer: buyer.clone(),
+            seller: seller.clone(),
+            aggressor: order.direction.clone(),
+            timestamp,
+        }
+    }
+    pub fn print_books(&self, goal_suit: &Card) { // only at debug, it runs after every order
+        let (spades_color, clubs_color, diamonds_color, hearts_color) = goal_suit.get_book_colors();
+
+        log!(LogLevel::Debug, CL::Dull, "\n=---------------------------------------------------------------------------------=");
+
+        let quote = |level: Option<&Level>, color: CL| match level { // an empty side prints as '-' rather than a fake price
+            Some(level) => format!("{}{}{}, {:?}", color.get(), level.price, CL::Dull.get(), level.player_name),
+            None => String::from("-"),
+        };
+        let rows = [
+            ("Spades  ", spades_color, Card::Spade),
+            ("Clubs   ", clubs_color, Card::Club),
+            ("Diamonds", diamonds_color, Card::Diamond),
+            ("Hearts  ", hearts_color, Card::Heart),
+        ];
+        for (label, color, card) in rows {
+            let book = self.books.get(&card).unwrap();
+            log!(LogLevel::Debug, CL::Dull, "{}{}  {}|:| Bid: ({}) | Ask: ({}) |:|{} Last trade: {}{:?}{} | Volume: {}x{}", color.get(), label, CL::Dull.get(), quote(book.bid(), CL::Green), quote(book.ask(), CL::PeachRed), CL::Dull.get(), CL::DimLightBlue.get(), book.last_price().unwrap_or_default(), CL::Dull.get(), book.volume_since_deal, CL::End.get());
+        }
+
+        let mut inventory_string = format!("{}Points    {}|:|{} ", CL::DullGreen.get(), CL::Dull.get(), CL::DullGreen.get());
+        for player_name in &self.player_names {
+            let player_points = self.player_points.get(player_name).unwrap();
+            inventory_string += &format!("{:?}: {} | ", player_name, player_points);
+        }
+        inventory_string.truncate(inventory_string.len() - 3);
+
+        log!(LogLevel::Debug, CL::Dull, "{}{}", inventory_string, CL::End.get());
+        log!(LogLevel::Debug, CL::Dull, "=---------------------------------------------------------------------------------=\n");
+    }
+
+}
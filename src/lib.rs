@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+pub mod utils;
+pub use utils::*;
+
+pub mod models;
+pub use models::*;
+
+pub mod exchange;
+pub use exchange::Exchange;
+
+pub mod replay;
+
+pub mod net;
+pub use net::{WsGateway, Latency};
+
+pub mod match_maker;
+pub use match_maker::MatchMaker;
+
+pub mod deal;
+pub use deal::Deal;
+
+pub mod sweep;
+
+pub mod player;
+pub use player::{PlayerName, Strategy, StrategyConfig, MarketContext};
+pub use player::generic::GenericPlayer;
+pub use player::event_driven::EventDrivenPlayer;
+
+use crate::player::TiltInventory;
+use kanal::AsyncSender;
+use tokio::sync::broadcast::Sender;
+
+
+pub const STARTING_BALANCE: usize = 500;
+
+
+// spawns whichever player implementation runs `strategy` in the seat `player_name`, the channels are the player's own ends
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_player(
+    player_name: PlayerName,
+    strategy: Strategy,
+    round_seconds: u64,
+    seed: Option<u64>,
+    config: StrategyConfig,
+    event_sender: Sender<Event>,
+    order_sender: Arc<AsyncSender<Order>>,
+    snapshot_sender: Arc<AsyncSender<PlayerName>>,
+    report_sender: Option<Arc<AsyncSender<RoundReport>>>,
+    starting_balance: usize,
+) -> tokio::task::JoinHandle<()> {
+    match strategy {
+        Strategy::TiltInventory => tokio::task::spawn(async move {
+            let mut player: TiltInventory = TiltInventory::new(player_name, 2000, 4000, event_sender, order_sender);
+            player.round_seconds = round_seconds;
+            player.start().await;
+        }),
+        Strategy::PickOff | Strategy::Fade => tokio::task::spawn(async move {
+            // the event-driven players reach the exchange through a latency layer, raise it to watch them lose races to the others
+            let latency = Latency::new(tokio::time::Duration::from_millis(0), tokio::time::Duration::from_millis(0));
+            let mut player: EventDrivenPlayer = EventDrivenPlayer::new(player_name, strategy, config, latency.delay_events(&event_sender), latency.delay_orders(order_sender));
+            drop(event_sender);
+            player.round_seconds = round_seconds;
+            player.starting_cash = starting_balance as i64;
+            player.snapshot_sender = Some(snapshot_sender);
+            player.report_sender = report_sender;
+            player.start().await;
+        }),
+        _ => {
+            let (lower_frequency, higher_frequency) = match strategy {
+                Strategy::Spread | Strategy::Pro => (1000, 2000),
+                Strategy::Noisy => (4000, 8000),
+                _ => (2000, 4000),
+            };
+            tokio::task::spawn(async move {
+                let mut player: GenericPlayer = GenericPlayer::new(player_name, strategy, lower_frequency, higher_frequency, seed, config, event_sender, order_sender);
+                player.round_seconds = round_seconds;
+                player.starting_cash = starting_balance as i64;
+                player.snapshot_sender = Some(snapshot_sender);
+                player.report_sender = report_sender;
+                player.start().await;
+            })
+        }
+    }
+}
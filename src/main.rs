@@ -1,25 +1,83 @@
+use figgie_auto::*;
+use figgie_auto::player::DEFAULT_ROUND_SECONDS;
 use std::sync::Arc;
+use clap::Parser;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
-pub mod utils;
-pub use utils::*;
 
-pub mod models;
-pub use models::*;
+#[derive(Parser)]
+#[command(about = "Sit a table of strategies down for a few rounds of Figgie")]
+struct Cli {
+    /// who's playing, 4 or 5 of: Spread, Seller, Noisy, PickOff, TiltInventory, TheHoarder, PrayingMantis, Momentum, MeanRevert, Arb, Hedge, Fade, Pro (repeats are fine)
+    #[arg(long, value_delimiter = ',', default_value = "TiltInventory,Spread,Seller,Noisy,PickOff")]
+    players: Vec<Strategy>,
 
-pub mod match_maker;
-pub use match_maker::MatchMaker;
+    /// rounds to play before printing standings, plays forever if left out
+    #[arg(long)]
+    rounds: Option<u32>,
 
-pub mod player;
-pub use player::PlayerName;
-pub use player::generic::GenericPlayer;
-pub use player::event_driven::EventDrivenPlayer;
+    /// how long each round trades for
+    #[arg(long, default_value_t = DEFAULT_ROUND_SECONDS)]
+    round_seconds: u64,
 
-use crate::player::TiltInventory;
+    /// pause between the results and the next deal
+    #[arg(long, default_value_t = 30)]
+    between_rounds: u64,
 
+    /// fixes the deal and every player's random draws
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// trace, debug, info, warn or error; debug has every player print its orders, rejections and settlements
+    #[arg(long, default_value = "info")]
+    log_level: LogLevel,
+
+    /// write the final standings here as csv
+    #[arg(long)]
+    leaderboard: Option<String>,
+
+    /// write every player's end-of-round report here as csv, one line per player per round
+    #[arg(long)]
+    reports: Option<String>,
+
+    /// play this many fixed deals per point of a grid over the Seller's reservation curve instead of a match, and rank the grid by the Seller's mean P&L
+    #[arg(long)]
+    sweep: Option<usize>,
+
+    /// strategy parameters, see strategy.toml
+    #[arg(long, default_value = "strategy.toml")]
+    config: String,
+}
 
 fn main() {
+    let cli = Cli::parse();
+    utils::logging::set_level(cli.log_level);
 
-    const STARTING_BALANCE: usize = 500;
+    for strategy in &cli.players {
+        if matches!(strategy, Strategy::Taker | Strategy::WildestDreams) {
+            println!("{}[!] {:?} has no implementation behind it yet{}", CL::Red.get(), strategy, CL::End.get());
+            return;
+        }
+    }
+    if !(4..=5).contains(&cli.players.len()) {
+        println!("{}[!] Figgie is played with 4 or 5 players, got {}{}", CL::Red.get(), cli.players.len(), CL::End.get());
+        return;
+    }
+
+    // a strategy sitting down once goes by its own name, repeats are numbered so every seat stays distinct
+    let mut seats: Vec<(PlayerName, Strategy)> = Vec::new();
+    for strategy in &cli.players {
+        let repeats = cli.players.iter().filter(|other| *other == strategy).count();
+        let name = if repeats > 1 {
+            let nth = seats.iter().filter(|(_, other)| other == strategy).count() + 1;
+            format!("{:?}#{}", strategy, nth)
+        } else {
+            format!("{:?}", strategy)
+        };
+        seats.push((PlayerName::new(&name), *strategy));
+    }
+    let players: Vec<PlayerName> = seats.iter().map(|(player_name, _)| player_name.clone()).collect();
 
     let runtime = tokio::runtime::Builder::new_multi_thread()
         .enable_all()
@@ -27,7 +85,7 @@ fn main() {
         .expect("Failed to build runtime");
     runtime.block_on(async {
 
-        println!("");
+        println!();
         println!("{}|==============================================|{}", CL::DimLightBlue.get(), CL::End.get());
         println!("{}|{}{}           Welcome to Figgie Auto!            {}{}|{}", CL::DimLightBlue.get(), CL::End.get(), CL::Teal.get(), CL::End.get(), CL::DimLightBlue.get(), CL::End.get());
         println!("{}|{}         {}---------------------------{}          {}|{}", CL::DimLightBlue.get(), CL::End.get(), CL::Dull.get(), CL::End.get(), CL::DimLightBlue.get(), CL::End.get());
@@ -43,93 +101,84 @@ fn main() {
         println!("Let the games begin!\n");
 
 
-        let mut handles = Vec::new();
-
-        let (tx, rx) = kanal::unbounded_async::<Order>();
-        let match_maker_order_receiver = Arc::new(rx);
-        let player_1_order_sender = Arc::new(tx);
-        let player_2_order_sender = Arc::clone(&player_1_order_sender);
-        let player_3_order_sender = Arc::clone(&player_1_order_sender);
-        let player_4_order_sender = Arc::clone(&player_1_order_sender);
-        let player_5_order_sender = Arc::clone(&player_1_order_sender);
-
-
-        let (match_maker_event_sender, _) = tokio::sync::broadcast::channel::<Event>(100);
-        let player_1_event_receiver = match_maker_event_sender.clone();
-        let player_2_event_receiver = match_maker_event_sender.clone();
-        let player_3_event_receiver = match_maker_event_sender.clone();
-        let player_4_event_receiver = match_maker_event_sender.clone();
-        let player_5_event_receiver = match_maker_event_sender.clone();
-
-
-        let mut players = Vec::new();
-
-
-        // Player 1
-        let player_name: PlayerName = PlayerName::TiltInventory;
-        players.push(player_name.clone());
-        let player_handle: tokio::task::JoinHandle<()> = tokio::task::spawn(async move {
-            let mut player: TiltInventory = TiltInventory::new(player_name, false, 2000, 4000, player_1_event_receiver, player_1_order_sender);
-            player.start().await;
-        });
-        handles.push(player_handle);
-
-
-
-        // Player 2
-        let player_name: PlayerName = PlayerName::Spread;
-        players.push(player_name.clone());
-        let player_handle: tokio::task::JoinHandle<()> = tokio::task::spawn(async move {
-            let mut player: GenericPlayer = GenericPlayer::new(player_name, false, 1000, 2000, player_2_event_receiver, player_2_order_sender);
-            player.start().await;
-        });
-        handles.push(player_handle);
-
-
-        // Player 3
-        let player_name: PlayerName = PlayerName::Seller;
-        players.push(player_name.clone());
-        let player_handle: tokio::task::JoinHandle<()> = tokio::task::spawn(async move {
-            let mut player: GenericPlayer = GenericPlayer::new(player_name, false, 2000, 4000, player_3_event_receiver, player_3_order_sender);
-            player.start().await;
-        });
-        handles.push(player_handle);
-
-
-        // Player 4
-        let player_name: PlayerName = PlayerName::Noisy;
-        players.push(player_name.clone());
-        let player_handle: tokio::task::JoinHandle<()> = tokio::task::spawn(async move {
-            let mut player: GenericPlayer = GenericPlayer::new(player_name, false, 4000, 8000, player_4_event_receiver, player_4_order_sender);
-            player.start().await;
-        });
-        handles.push(player_handle);
-
-
-        // Player 5
-        let player_name: PlayerName = PlayerName::PickOff;
-        players.push(player_name.clone());
-        let player_handle: tokio::task::JoinHandle<()> = tokio::task::spawn(async move {
-            let mut player: EventDrivenPlayer = EventDrivenPlayer::new(player_name, false, player_5_event_receiver, player_5_order_sender);
-            player.start().await;
-        });
-        handles.push(player_handle);
-
+        let config = match StrategyConfig::load(&cli.config) {
+            Ok(config) => config,
+            Err(e) => {
+                println!("{}[!] Using default strategy config, {}{}", CL::Orange.get(), e, CL::End.get());
+                StrategyConfig::default()
+            }
+        };
+
+        if let Some(deal_count) = cli.sweep {
+            let Some((subject, _)) = seats.iter().find(|(_, strategy)| *strategy == Strategy::Seller) else {
+                println!("{}[!] Sweeping the reservation curve needs a Seller at the table{}", CL::Red.get(), CL::End.get());
+                return;
+            };
+            let seed = cli.seed.unwrap_or(0);
+            let deals = sweep::draw_deals(deal_count, &players, seed);
+            let results = sweep::sweep(sweep::seller_grid(&config), deals, &seats, subject, cli.round_seconds, seed).await;
+            println!("=---------------------------- Sweep ----------------------------=");
+            for (place, result) in results.iter().enumerate() {
+                println!("{} {}. reservation {} -> {} | Mean: {:+.1} | Std dev: {:.1} | Per deal: {:?}{}", CL::Dull.get(), place + 1, result.config.seller.reservation_open, result.config.seller.reservation_close, result.mean_pnl, result.std_dev(), result.pnls, CL::End.get());
+            }
+            return;
+        }
 
+        let (order_sender, order_receiver) = kanal::unbounded_async::<Order>();
+        let order_sender = Arc::new(order_sender);
+        let (snapshot_sender, snapshot_receiver) = kanal::unbounded_async::<PlayerName>();
+        let snapshot_sender = Arc::new(snapshot_sender);
+        let (event_sender, _) = tokio::sync::broadcast::channel::<Event>(100);
+        let (report_sender, report_receiver) = kanal::unbounded_async::<RoundReport>();
+        let report_sender = cli.reports.as_ref().map(|_| Arc::new(report_sender)); // nobody reports unless someone's collecting
+
+        for (seat, (player_name, strategy)) in seats.iter().enumerate() {
+            let seed = cli.seed.map(|seed| seed.wrapping_add(seat as u64 + 1)); // each seat draws differently, but repeatably
+            spawn_player(player_name.clone(), *strategy, cli.round_seconds, seed, config.clone(), event_sender.clone(), Arc::clone(&order_sender), Arc::clone(&snapshot_sender), report_sender.clone(), STARTING_BALANCE);
+        }
+        drop(order_sender); // only the players hold these now, so the exchange sees them all go
+        drop(snapshot_sender);
+        drop(report_sender);
 
         // Matchmaker
-        let match_maker_handle: tokio::task::JoinHandle<()> = tokio::task::spawn(async move {
-            let mut match_maker: MatchMaker = MatchMaker::new(STARTING_BALANCE, players, match_maker_event_sender, match_maker_order_receiver);
+        let mut match_maker: MatchMaker = MatchMaker::new(STARTING_BALANCE, players, event_sender, Arc::new(order_receiver), Arc::new(snapshot_receiver));
+        match_maker.max_rounds = cli.rounds;
+        match_maker.round_seconds = cli.round_seconds;
+        match_maker.between_rounds = tokio::time::Duration::from_secs(cli.between_rounds);
+        match_maker.exchange.ladders = config.ladder;
+        if let Some(seed) = cli.seed {
+            match_maker.rng = StdRng::seed_from_u64(seed);
+        }
+        let match_maker_handle: tokio::task::JoinHandle<MatchMaker> = tokio::task::spawn(async move {
             match_maker.start().await;
+            match_maker
         });
-        handles.push(match_maker_handle);
-
 
-
-        for handle in handles {
-            handle.await.unwrap();
+        // the game is over when the dealer says so, the players are torn down with the runtime
+        let match_maker = match_maker_handle.await.unwrap();
+        if let Some(file_path) = &cli.leaderboard {
+            if let Err(e) = match_maker.leaderboard.write_csv(file_path) {
+                println!("{}[!] Error writing leaderboard to {}: {:?}{}", CL::Red.get(), file_path, e, CL::End.get());
+            }
+        }
+        if let Some(file_path) = &cli.reports {
+            // the last round's reports can still be on their way, TiltInventory never sends any so don't wait forever on them
+            let mut collector = ReportCollector::new();
+            let expected = seats.len() * match_maker.round as usize;
+            while collector.len() < expected {
+                match tokio::time::timeout(tokio::time::Duration::from_secs(1), report_receiver.recv()).await {
+                    Ok(Ok(report)) => collector.record(report),
+                    _ => break,
+                }
+            }
+            for (player_name, share) in collector.average_goal_share() {
+                println!("{}{:?} |:| Goal suit share of final holdings: {:.0}%{}", CL::Dull.get(), player_name, share * 100.0, CL::End.get());
+            }
+            if let Err(e) = collector.write_csv(file_path) {
+                println!("{}[!] Error writing round reports to {}: {:?}{}", CL::Red.get(), file_path, e, CL::End.get());
+            }
         }
 
     });
 
-}
\ No newline at end of file
+}
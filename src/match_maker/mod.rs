@@ -1,25 +1,27 @@
-use super::{Card, Book, Inventory, Order, Event, Update, Trade, Direction, CL, PlayerName};
+use super::{Card, Order, Event, RoundResult, Pot, Leaderboard, CL, PlayerName, Exchange, Deal, validate_deal};
 use tokio::sync::broadcast::Sender;
-use rand::prelude::SliceRandom;
 use kanal::AsyncReceiver;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 use std::sync::Arc;
-use rand::Rng;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use super::player::DEFAULT_ROUND_SECONDS;
+
 
 pub struct MatchMaker {
     pub round: u32,
+    pub max_rounds: Option<u32>, // play forever when None
+    pub round_seconds: u64,
+    pub between_rounds: tokio::time::Duration, // pause after the results before the next deal
+    pub leaderboard: Leaderboard,
     pub player_names: Vec<PlayerName>,
     pub suits: [Card; 4],
     pub goal_suit: Card,
     pub common_suit: Card,
-    pub player_points: HashMap<PlayerName, usize>,
-    pub books: HashMap<Card, Book>,
-    pub player_inventories: HashMap<PlayerName, Inventory>,
+    pub exchange: Exchange,
     pub event_sender: Sender<Event>,
-    pub order_receiver: Arc<AsyncReceiver<Order>>,
     pub rng: StdRng,
+    pub deals: VecDeque<Deal>, // played in order before any are drawn from rng
 }
 
 impl MatchMaker {
@@ -28,347 +30,150 @@ impl MatchMaker {
         player_names: Vec<PlayerName>,
         event_sender: Sender<Event>,
         order_receiver: Arc<AsyncReceiver<Order>>,
+        snapshot_receiver: Arc<AsyncReceiver<PlayerName>>,
     ) -> Self {
 
-        let mut player_inventories = HashMap::new();
-        let mut player_points = HashMap::new();
-        for player_name in &player_names {
-            player_points.insert(player_name.clone(), starting_balance);
-            player_inventories.insert(player_name.clone(), Inventory::new());
-        }
-
-        let mut books = HashMap::new();
-        books.insert(Card::Spade, Book::new());
-        books.insert(Card::Club, Book::new());
-        books.insert(Card::Diamond, Book::new());
-        books.insert(Card::Heart, Book::new());
-
+        let exchange = Exchange::new(starting_balance, player_names.clone(), event_sender.clone(), order_receiver, snapshot_receiver);
 
         Self {
             round: 0,
+            max_rounds: None,
+            round_seconds: DEFAULT_ROUND_SECONDS,
+            between_rounds: tokio::time::Duration::from_secs(30),
+            leaderboard: Leaderboard::new(),
             player_names,
             suits: [Card::Spade, Card::Club, Card::Diamond, Card::Heart],
             goal_suit: Card::Spade,
             common_suit: Card::Club,
-            player_points,
-            books,
-            player_inventories,
+            exchange,
             event_sender,
-            order_receiver,
             rng: StdRng::from_entropy(),
+            deals: VecDeque::new(),
         }
     }
 
-    pub fn pick_new_common_suit(&mut self) {
-        self.common_suit = self.suits[self.rng.gen_range(0..=3)].clone();
-    }
-
-    pub fn get_new_inventories(&mut self) -> HashMap<Card, usize> {
-        let mut cards: Vec<Card> = Vec::new();
-        let (goal_suit, suit_1, suit_2) = self.common_suit.get_other_cards();
-        self.goal_suit = goal_suit.clone();
-
-        for _ in 0..12 { cards.push(self.common_suit.clone()) }
-        
-        let mut starting_inventory = HashMap::new();
+    // sits the deal down at the exchange and prints the card count, returns how many of each suit are out
+    pub fn apply_deal(&mut self, deal: Deal) -> HashMap<Card, usize> {
+        self.common_suit = deal.common_suit;
+        self.goal_suit = deal.goal_suit;
+        self.exchange.player_inventories = deal.inventories;
+        for book in self.exchange.books.values_mut() {
+            book.reset_volume();
+        }
 
         println!("=---= Card Count =---=");
         println!("{} - {:?} | 12x{}", CL::Dull.get(), self.common_suit, CL::End.get());
-        starting_inventory.insert(self.common_suit.clone(), 12);
-
-        // randomly pick one of the other 3 suits to be the one with 8 cards
-        let mut already_lucky = false;
-        for (idx, suit) in [suit_1, suit_2, goal_suit].iter().enumerate() {
-            let lucky_eight = rand::random::<bool>();
-            if idx == 2 && !already_lucky {
-                for _ in 0..8 { cards.push(suit.clone()) }
-                println!("{} - {:?} | 8x{}", CL::Dull.get(), suit, CL::End.get());
-                starting_inventory.insert(suit.clone(), 8);
-            } else {
-                if !already_lucky && lucky_eight {
-                    for _ in 0..8 { cards.push(suit.clone()) }
-                    println!("{} - {:?} | 8x{}", CL::Dull.get(), suit, CL::End.get());
-                    starting_inventory.insert(suit.clone(), 8);
-                    already_lucky = true;
-                } else {
-                    for _ in 0..10 { cards.push(suit.clone()) }
-                    println!("{} - {:?} | 10x{}", CL::Dull.get(), suit, CL::End.get());
-                    starting_inventory.insert(suit.clone(), 10);
-                }
-            }
-        }
-
-        cards.shuffle(&mut self.rng); // randomly shuffle the cards
-
-        let chunk_size = 40 / self.player_names.len();
-        let chunks: Vec<&[Card]> = cards.chunks(chunk_size).collect();
-
-        for (i, player_name) in self.player_names.iter().enumerate() {
-            let mut player_inventory = Inventory::new();
-            player_inventory.count(chunks[i].to_vec());
-            self.player_inventories.insert(player_name.clone(), player_inventory.clone());
+        for suit in self.suits.iter().filter(|suit| **suit != self.common_suit) {
+            println!("{} - {:?} | {}x{}", CL::Dull.get(), suit, deal.suit_counts.get(suit).unwrap_or(&0), CL::End.get());
         }
 
-        starting_inventory
+        deal.suit_counts
     }
 
 
 
     pub async fn start(&mut self) {
-        let round_duration = tokio::time::Duration::from_secs(60 * 4); // 4 minutes per round
+        let round_duration = tokio::time::Duration::from_secs(self.round_seconds);
 
-        loop {
-            let mut pot = 0;
-            let ante = 200 / self.player_names.len();
+        while self.max_rounds.is_none_or(|max_rounds| self.round < max_rounds) {
+            let mut pot = Pot::new(self.player_names.len());
+            let ante = pot.ante;
 
             println!("{}==================== ROUND {} ===================={}", CL::Purple.get(), self.round, CL::End.get());
-            println!("");
+            println!();
             println!("=---= Game Details =---=");
             println!("{} - Players: {}x{}", CL::Dull.get(), self.player_names.len(), CL::End.get());
             println!("{} - Ante: {}{}", CL::Dull.get(), ante, CL::End.get());
-            println!("{} - Pot: 200{}", CL::Dull.get(), CL::End.get());
-            println!("");
+            println!("{} - Pot: {}{}", CL::Dull.get(), ante * self.player_names.len(), CL::End.get());
+            println!();
             
-            let initial_points = self.player_points.clone();
-            for (player, points) in self.player_points.iter_mut() {
-                if *points < ante {
+            let initial_points = self.exchange.player_points.clone();
+            for (player, points) in self.exchange.player_points.iter_mut() {
+                if !pot.collect(points) {
                     println!("[!] Player {:?} does not have enough points to play", player);
                     break;
                 }
-                *points -= ante;
-                pot += ante;
             }
 
-            self.pick_new_common_suit();
-            let starting_inventory = self.get_new_inventories();
+            let deal = match self.deals.pop_front() {
+                Some(deal) => deal,
+                None => Deal::draw(&self.player_names, &mut self.rng),
+            };
+            let starting_inventory = self.apply_deal(deal);
 
             println!("{} - Common suit: {:?}{}", CL::Dull.get(), self.common_suit, CL::End.get());
             println!("{} - Goal suit: {}{:?}{}{}", CL::Dull.get(), CL::LimeGreen.get(), self.goal_suit, CL::End.get(), CL::End.get());
-            println!("");
+            println!();
+
+            if let Err(e) = validate_deal(&self.exchange.player_inventories) {
+                println!("{}[!] Refusing to start round, illegal deal: {:?}{}", CL::Red.get(), e, CL::End.get());
+                self.exchange.player_points = initial_points; // hand the antes back
+                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                continue;
+            }
 
             println!("{}[+] Dealing cards...{}\n", CL::DimLightBlue.get(), CL::End.get());
             
             tokio::time::sleep(tokio::time::Duration::from_secs(5)).await; // give the players a little bit to get ready
             
-            if let Err(e) = self.event_sender.send(Event::DealCards(self.player_inventories.clone())) {
+            let deal_cards = Event::DealCards {
+                inventories: self.exchange.player_inventories.clone(),
+                pot,
+            };
+            if let Err(e) = self.event_sender.send(deal_cards) {
                 println!("{}[!] Error sending deal cards event: {:?}{}", CL::Red.get(), e, CL::End.get());
             }
+            self.exchange.broadcast_accounts(); // after the deal, players take the ante off themselves on DealCards
 
             tokio::time::sleep(tokio::time::Duration::from_secs(2)).await; // give the players some time to order their cards
 
             // send out the book
-            let book_event = Event::Update(Update {
-                spades: self.books.get(&Card::Spade).unwrap().clone(),
-                clubs: self.books.get(&Card::Club).unwrap().clone(),
-                diamonds: self.books.get(&Card::Diamond).unwrap().clone(),
-                hearts: self.books.get(&Card::Heart).unwrap().clone(),
-                trade: None,
-            });
-            if let Err(e) = self.event_sender.send(book_event) {
-                println!("[!] Error sending book event: {:?}", e);
-            }
+            self.exchange.reset_books();
+            self.exchange.broadcast_update(Vec::new());
 
-            let (spades_color, clubs_color, diamonds_color, hearts_color) = self.goal_suit.get_book_colors();
-
-            let start = tokio::time::Instant::now();
-            while start.elapsed() < round_duration {
-
-                if let Ok(order) = self.order_receiver.recv().await {
-                    if order.price == 0 { // No free lunches allowed
-                        continue;
-                    }
-
-                    println!("Processing order: {:?} | Queue: {}x", order, self.order_receiver.len());
-
-                    let book = self.books.get_mut(&order.card).unwrap();
-                    let trade: Option<Trade> = match order.direction {
-                        Direction::Buy => {
-                            if order.price >= book.ask.price {
-                                println!("{}[-] Aggressing Player: {:?} | {:?} |:| Matched buy order!{}", CL::Green.get(), order.player_name, order.card, CL::End.get());
-
-
-                                // =-= Update the Inventories =-= //
-                                let buyer_inventory = self.player_inventories.get_mut(&order.player_name).unwrap();
-                                buyer_inventory.change(order.card.clone(), true);
-
-                                let seller_inventory = self.player_inventories.get_mut(&book.ask.player_name).unwrap();
-                                seller_inventory.change(order.card.clone(), false);
-
-
-                                // =-= Update the Points =-= //
-                                let buyer_points = self.player_points.get_mut(&order.player_name).unwrap();
-                                *buyer_points -= book.ask.price;
-
-                                let seller_points = self.player_points.get_mut(&book.ask.player_name).unwrap();
-                                *seller_points += book.ask.price;
-
-
-                                // =-= Package Trade =-= //
-                                book.last_trade = Some(book.ask.price);
-                                let trade = Trade {
-                                    card: order.card.clone(),
-                                    price: book.ask.price,
-                                    buyer: order.player_name,
-                                    seller: book.ask.player_name.clone(),
-                                };
-                                Some(trade)
-
-                            } else {
-                                // check if this price beats the current best bid
-                                if order.price > book.bid.price {
-                                    // update the bid price and user_id
-                                    book.bid.price = order.price;
-                                    book.bid.player_name = order.player_name;
-                                }
-                                None
-                            }
-                        },
-                        Direction::Sell => {
-                            // check if the user has the inventory to sell this Card
-                            let seller_inventory = self.player_inventories.get(&order.player_name).unwrap();
-                            if seller_inventory.get(&order.card) == 0 {
-                                println!("[!] {:?} | {:?} |:| Player does not have the inventory to sell this Card", order.player_name, order.card);
-                                continue;
-                            }
-
-                            if order.price <= book.bid.price {
-                                println!("{}[-] Aggressing Player: {:?} | {:?} |:| Matched sell order!{}", CL::Red.get(), order.player_name, order.card, CL::End.get());
-
-                                // =-= Update the Inventories =-= //
-                                let buyer_inventory = self.player_inventories.get_mut(&book.bid.player_name).unwrap();
-                                buyer_inventory.change(order.card.clone(), true);
-
-                                let seller_inventory = self.player_inventories.get_mut(&order.player_name).unwrap();
-                                seller_inventory.change(order.card.clone(), false);
-
-
-                                // =-= Update the Points =-= //
-                                let buyer_points = self.player_points.get_mut(&book.bid.player_name).unwrap();
-                                *buyer_points -= book.bid.price;
-
-                                let seller_points = self.player_points.get_mut(&order.player_name).unwrap();
-                                *seller_points += book.bid.price;
-
-
-                                // =-= Package Trade =-= //
-                                book.last_trade = Some(book.bid.price);
-                                let trade = Trade {
-                                    card: order.card.clone(),
-                                    price: book.bid.price,
-                                    buyer: book.bid.player_name.clone(),
-                                    seller: order.player_name,
-                                };
-                                Some(trade)
-
-                            } else {
-                                // check if this price beats the current best bid
-                                if order.price < book.ask.price {
-                                    // update the bid price and user_id
-                                    book.ask.price = order.price;
-                                    book.ask.player_name = order.player_name;
-                                }
-                                None
-                            }
-                        },
-                    };
-
-                    if let Some(_) = trade.clone() {
-                        // =-= Reset all the Books =-= //
-                        self.books.get_mut(&Card::Spade).unwrap().reset_quotes();
-                        self.books.get_mut(&Card::Club).unwrap().reset_quotes();
-                        self.books.get_mut(&Card::Diamond).unwrap().reset_quotes();
-                        self.books.get_mut(&Card::Heart).unwrap().reset_quotes();
-
-                        // =-= Drain the Order Receiver =-= //
-                        let drain_amount = self.order_receiver.len();
-                        for _ in 0..drain_amount {
-                            let _ = self.order_receiver.try_recv();
-                        }
-                    }
-
-                    // =-= Print the Game =-= //
-                    println!("\n=---------------------------------------------------------------------------------=");
-
-                    let spades = self.books.get(&Card::Spade).unwrap();
-                    let clubs = self.books.get(&Card::Club).unwrap();
-                    let diamonds = self.books.get(&Card::Diamond).unwrap();
-                    let hearts = self.books.get(&Card::Heart).unwrap();
-                    println!("{}Spades    {}|:| Bid: ({}{:?}{}, {:?}) | Ask: ({}{:?}{}, {:?}) |:|{} Last trade: {}{:?}{}", spades_color.get(), CL::Dull.get(), CL::Green.get(), spades.bid.price,    CL::Dull.get(), spades.bid.player_name,    CL::PeachRed.get(),  spades.ask.price,    CL::Dull.get(),  spades.ask.player_name,    CL::Dull.get(),  CL::DimLightBlue.get(),  spades.last_trade.unwrap_or_default(),    CL::End.get());
-                    println!("{}Clubs     {}|:| Bid: ({}{:?}{}, {:?}) | Ask: ({}{:?}{}, {:?}) |:|{} Last trade: {}{:?}{}", clubs_color.get(), CL::Dull.get(), CL::Green.get(), clubs.bid.price,     CL::Dull.get(), clubs.bid.player_name,     CL::PeachRed.get(),  clubs.ask.price,     CL::Dull.get(),  clubs.ask.player_name,     CL::Dull.get(),  CL::DimLightBlue.get(),  clubs.last_trade.unwrap_or_default(),     CL::End.get());
-                    println!("{}Diamonds  {}|:| Bid: ({}{:?}{}, {:?}) | Ask: ({}{:?}{}, {:?}) |:|{} Last trade: {}{:?}{}", diamonds_color.get(), CL::Dull.get(), CL::Green.get(), diamonds.bid.price,  CL::Dull.get(), diamonds.bid.player_name,  CL::PeachRed.get(),  diamonds.ask.price,  CL::Dull.get(),  diamonds.ask.player_name,  CL::Dull.get(),  CL::DimLightBlue.get(),  diamonds.last_trade.unwrap_or_default(),  CL::End.get());
-                    println!("{}Hearts    {}|:| Bid: ({}{:?}{}, {:?}) | Ask: ({}{:?}{}, {:?}) |:|{} Last trade: {}{:?}{}", hearts_color.get(), CL::Dull.get(), CL::Green.get(), hearts.bid.price,    CL::Dull.get(), hearts.bid.player_name,    CL::PeachRed.get(),  hearts.ask.price,    CL::Dull.get(),  hearts.ask.player_name,    CL::Dull.get(),  CL::DimLightBlue.get(),  hearts.last_trade.unwrap_or_default(),    CL::End.get());
-                    
-                    let mut inventory_string = format!("{}Points    {}|:|{} ", CL::DullGreen.get(), CL::Dull.get(), CL::DullGreen.get());
-                    for player_name in &self.player_names {
-                        let player_points = self.player_points.get(player_name).unwrap();
-                        inventory_string += &format!("{:?}: {} | ", player_name, player_points);
-                    }
-                    inventory_string.truncate(inventory_string.len() - 3);
-
-                    println!("{}{}", inventory_string, CL::End.get());
-                    println!("=---------------------------------------------------------------------------------=\n");
-
-                    let update = Update {
-                        spades: self.books.get(&Card::Spade).unwrap().clone(),
-                        clubs: self.books.get(&Card::Club).unwrap().clone(),
-                        diamonds: self.books.get(&Card::Diamond).unwrap().clone(),
-                        hearts: self.books.get(&Card::Heart).unwrap().clone(),
-                        trade,
-                    };
-                    let update_event = Event::Update(update);
-
-                    //println!("{}[+] Done processing request{}", CL::Green.get(), CL::End.get());
-
-                    if let Err(e) = self.event_sender.send(update_event) {
-                        println!("[!] Error sending update event: {:?}", e);
-                    }
-                }
-            } 
+            self.exchange.run(round_duration, &self.goal_suit).await;
 
             // =-= End the Round =-= //
-            let end_round = Event::EndRound;
+            let round_result = RoundResult {
+                goal_suit: self.goal_suit.clone(),
+                pot: pot.pot_total,
+                inventories: self.exchange.player_inventories.clone(),
+            };
+            let end_round = Event::EndRound(round_result.clone());
             if let Err(e) = self.event_sender.send(end_round) {
                 println!("[!] Error sending end round event: {:?}", e);
             }
 
-            println!("");
+            println!();
             println!("{}=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-={}", CL::Pink.get(), CL::End.get());
             println!("{}=-=-=-=-=-=-=-=-=-=-=-=-=-=-= Round over! =-=-=-=-=-=-=-=-=-=-=-=-=-=-={}", CL::Pink.get(), CL::End.get());
             println!("{}=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-={}", CL::Pink.get(), CL::End.get());
-            println!("");
+            println!();
             
             println!("=---= Game Details =---=");
             println!("{} - Players: {}x{}", CL::Dull.get(), self.player_names.len(), CL::End.get());
             println!("{} - Ante: {}{}", CL::Dull.get(), ante, CL::End.get());
-            println!("{} - Pot: {}{}", CL::Dull.get(), pot, CL::End.get());
-            println!("");
+            println!("{} - Pot: {}{}", CL::Dull.get(), pot.pot_total, CL::End.get());
+            println!();
             println!("=---= Card Count =---=");
             for (suit, amount) in starting_inventory {
                 println!("{} - {:?} | {}x{}", CL::Dull.get(), suit, amount, CL::End.get());
             }
             println!("{} - Common suit: {:?}{}", CL::Dull.get(), self.common_suit, CL::End.get());
             println!("{} - Goal suit: {}{:?}{}{}", CL::Dull.get(), CL::LimeGreen.get(), self.goal_suit, CL::End.get(), CL::End.get());
-            println!("");
+            println!();
 
             self.round += 1;
 
-            // calculate the scores, each player is awared goal_suit * 10
-            // and the player with the most of the goal_suit is awarded 50
-
-            // get each players inventory and if add their points, simulatentously subtracting from pot
-            let mut winner: (PlayerName, usize) = (PlayerName::None, 0); // player_id, goal_cards
-            let mut tied_winnders: Vec<PlayerName> = Vec::new(); // player_ids
+            // calculate the scores, each player is awarded goal_suit * 10
+            // and the player(s) with the most of the goal_suit split whatever is left in the pot
+            let (most_goal_cards, majority_holders) = round_result.get_majority();
+            let remaining_pot = round_result.get_remaining_pot();
 
             println!("=---------------------------- Inventory ----------------------------=");
             for player_name in &self.player_names {
-                let inventory = self.player_inventories.get(player_name).unwrap();
-                let player_points = self.player_points.get_mut(player_name).unwrap();
-                let goal_cards = match self.goal_suit {
-                    Card::Spade => inventory.spades,
-                    Card::Club => inventory.clubs,
-                    Card::Diamond => inventory.diamonds,
-                    Card::Heart => inventory.hearts,
-                };
+                let inventory = self.exchange.player_inventories.get(player_name).unwrap();
+                let player_points = self.exchange.player_points.get_mut(player_name).unwrap();
 
                 let (spade_color, club_color, diamond_color, heart_color) = match self.goal_suit {
                     Card::Spade => (CL::LimeGreen.get(), CL::Dull.get(), CL::Dull.get(), CL::Dull.get()),
@@ -379,47 +184,35 @@ impl MatchMaker {
 
                 println!("{}{}{:?}{} |:| Spades: {}{}x{} | Clubs: {}{}x{} | Diamonds: {}{}x{} | Hearts: {}{}x{}{}", CL::Dull.get(), CL::DimLightBlue.get(), player_name, CL::Dull.get(), spade_color, inventory.spades, CL::Dull.get(), club_color, inventory.clubs, CL::Dull.get(), diamond_color, inventory.diamonds, CL::Dull.get(), heart_color, inventory.hearts, CL::End.get(), CL::End.get());
 
-                if goal_cards >= winner.1 {
-                    if goal_cards == winner.1 {
-                        tied_winnders.push(player_name.clone());
-                    } else {
-                        winner = (player_name.clone(), goal_cards);
-                        tied_winnders.clear();
-                    }
-                }
-
-                *player_points += goal_cards * 10;
-                pot -= goal_cards * 10;
+                *player_points += round_result.get_payout(inventory);
             }
-            println!("");
+            println!();
+            self.exchange.broadcast_accounts();
 
             // if there's one winner, award them the pot
             // if there's a tie, split the pot evenly between the winners
 
             println!("=----------------------------- Results -----------------------------=");
-            if tied_winnders.is_empty() {
-                println!("{}[+] Player '{:?}' wins the whole pot of {} points{}", CL::Green.get(), winner.0, pot, CL::End.get());
-                let winner_points = self.player_points.get_mut(&winner.0).unwrap();
-                *winner_points += pot;
+            let winners: Vec<&PlayerName> = self.player_names.iter().filter(|player_name| self.exchange.player_inventories.get(*player_name).unwrap().get(&self.goal_suit) == most_goal_cards).collect();
+            if majority_holders == 1 {
+                println!("{}[+] Player '{:?}' wins the whole pot of {} points{}", CL::Green.get(), winners[0], remaining_pot, CL::End.get());
             } else {
-                let split = pot / (tied_winnders.len() + 1);
-                println!("{}[+] Players tie for the pot of {} points{}\n", CL::Teal.get(), pot, CL::End.get());
+                let split = remaining_pot / majority_holders;
+                println!("{}[+] Players tie for the pot of {} points{}\n", CL::Teal.get(), remaining_pot, CL::End.get());
                 println!("{}------ Tied Players ------{}", CL::Dull.get(), CL::End.get());
-                println!("{}{}{:?}{} | Goal Cards: {}x | Points: {}+{}x{}{}", CL::Dull.get(), CL::DimLightBlue.get(), winner.0, CL::Dull.get(), winner.1, CL::LimeGreen.get(), split, CL::End.get(), CL::End.get());
-                for player_name in tied_winnders {
-                    println!("{}{}{:?}{} | Goal Cards: {}x | Points: {}+{}x{}{}", CL::Dull.get(), CL::DimLightBlue.get(), player_name, CL::Dull.get(), winner.1, CL::LimeGreen.get(), split, CL::End.get(), CL::End.get());
-                    let player_points = self.player_points.get_mut(&player_name).unwrap();
-                    *player_points += split;
+                for player_name in winners {
+                    println!("{}{}{:?}{} | Goal Cards: {}x | Points: {}+{}x{}{}", CL::Dull.get(), CL::DimLightBlue.get(), player_name, CL::Dull.get(), most_goal_cards, CL::LimeGreen.get(), split, CL::End.get(), CL::End.get());
                 }
             }
-            println!("");
+            println!();
 
             println!("=-------------------------- Updated Points -------------------------=");
             let mut inventory_string = String::from("");
             for player_name in &self.player_names {
                 let initial_points = initial_points.get(player_name).unwrap();
-                let player_points = self.player_points.get(player_name).unwrap();
+                let player_points = self.exchange.player_points.get(player_name).unwrap();
                 let point_change: i32 = *player_points as i32 - *initial_points as i32;
+                self.leaderboard.record(player_name, point_change as i64, &round_result);
 
                 let change_color = match point_change {
                     x if x > 0 => CL::Green.get(),
@@ -431,12 +224,15 @@ impl MatchMaker {
             }
             inventory_string.truncate(inventory_string.len() - 3);
             println!("{}{}{}", CL::Dull.get(), inventory_string, CL::End.get());
-            println!("");
+            println!();
 
-            tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
+            if self.max_rounds.is_none_or(|max_rounds| self.round < max_rounds) {
+                tokio::time::sleep(self.between_rounds).await;
+            }
 
         }
 
+        self.leaderboard.print();
     }
 
 }
\ No newline at end of file
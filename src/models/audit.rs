@@ -0,0 +1,74 @@
+use super::{Card, Inventory, PlayerName, RoundResult, RoundReport, infer_goal_from_distribution, settle_round};
+use std::collections::HashMap;
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditError {
+    CardsNotConserved { card: Card, dealt: usize, settled: usize }, // trading created or destroyed cards, e.g. a sell from an empty hand
+    PointsNotConserved { before: usize, after: usize, dust: usize }, // points at the table before the ante vs after the payout, plus what an uneven split leaves behind
+    WrongGoalSuit { dealt: Card, settled: Card }, // the round paid out on a suit other than the one the deal implies
+    PnlMismatch { player: PlayerName, reported: i64, settled: i64 }, // a player's own realized P&L plus its payout less the ante vs what the exchange moved its points by
+    TradingNotZeroSum(i64), // the players' realized P&L should cancel out, every point one paid another received
+}
+
+
+fn totals(inventories: &HashMap<PlayerName, Inventory>) -> Inventory {
+    let mut totals = Inventory::new();
+    for inventory in inventories.values() {
+        totals.spades += inventory.spades;
+        totals.clubs += inventory.clubs;
+        totals.diamonds += inventory.diamonds;
+        totals.hearts += inventory.hearts;
+    }
+    totals
+}
+
+// what has to hold once a round is settled: trading only moves cards and points between players,
+// so every suit still adds up to what was dealt, the antes all came back out of the pot, and the payout went to the deal's goal suit
+pub fn audit_round(dealt: &HashMap<PlayerName, Inventory>, result: &RoundResult, points_before: &HashMap<PlayerName, usize>, points_after: &HashMap<PlayerName, usize>) -> Vec<AuditError> {
+    let mut errors = Vec::new();
+
+    if let Ok(goal_suit) = infer_goal_from_distribution(dealt) { // an illegal deal never gets this far, validate_deal stops the round first
+        if goal_suit != result.goal_suit {
+            errors.push(AuditError::WrongGoalSuit { dealt: goal_suit, settled: result.goal_suit.clone() });
+        }
+    }
+
+    let (dealt, settled) = (totals(dealt), totals(&result.inventories));
+    for card in [Card::Spade, Card::Club, Card::Diamond, Card::Heart] {
+        if dealt.get(&card) != settled.get(&card) {
+            errors.push(AuditError::CardsNotConserved { card: card.clone(), dealt: dealt.get(&card), settled: settled.get(&card) });
+        }
+    }
+
+    let before: usize = points_before.values().sum();
+    let after: usize = points_after.values().sum();
+    let dust = result.get_dust();
+    if after + dust != before {
+        errors.push(AuditError::PointsNotConserved { before, after, dust });
+    }
+
+    errors
+}
+
+// the players' side of the same round: each one's realized P&L from trading, plus its payout less the ante, is what the exchange
+// moved its points by, and between them the trading nets to zero, only holds once every seat has sent its report
+pub fn audit_reports(reports: &[RoundReport], result: &RoundResult, points_before: &HashMap<PlayerName, usize>, points_after: &HashMap<PlayerName, usize>) -> Vec<AuditError> {
+    let mut errors = Vec::new();
+
+    for report in reports {
+        let reported = report.realized_pnl + settle_round(&report.final_inventory, result);
+        let (before, after) = (points_before.get(&report.player).copied().unwrap_or(0), points_after.get(&report.player).copied().unwrap_or(0));
+        let settled = after as i64 - before as i64;
+        if reported != settled {
+            errors.push(AuditError::PnlMismatch { player: report.player.clone(), reported, settled });
+        }
+    }
+
+    let total: i64 = reports.iter().map(|report| report.realized_pnl).sum();
+    if total != 0 {
+        errors.push(AuditError::TradingNotZeroSum(total));
+    }
+
+    errors
+}
@@ -1,47 +1,174 @@
-use super::PlayerName;
+use super::{Direction, PlayerName};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
-pub struct Quote {
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Level {
     pub price: usize,
     pub player_name: PlayerName,
+    pub size: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradePrint {
+    pub price: usize,
+    pub aggressor: Direction, // Buy when someone lifted the ask, Sell when they hit the bid
+    pub size: usize, // cards that went through at that price
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Book {
-    pub bid: Quote,
-    pub ask: Quote,
-    pub last_trade: Option<usize>,
+    pub bids: Vec<Level>, // best (highest) first
+    pub asks: Vec<Level>, // best (lowest) first
+    pub last_trade: Option<TradePrint>,
+    #[serde(default)]
+    pub volume_since_deal: usize, // cards traded in this suit since the cards went out
 }
 
 impl Book {
     pub fn reset_quotes(&mut self) {
-        self.bid = Quote {
-            price: 0,
-            player_name: PlayerName::None,
-        };
-        self.ask = Quote {
-            price: 99,
-            player_name: PlayerName::None,
+        self.bids.clear();
+        self.asks.clear();
+    }
+
+    pub fn record_trade(&mut self, price: usize, aggressor: Direction, size: usize) {
+        self.last_trade = Some(TradePrint { price, aggressor, size });
+        self.volume_since_deal += size;
+    }
+
+    pub fn reset_volume(&mut self) { // on a new deal, the last print stays as a reference price
+        self.volume_since_deal = 0;
+    }
+
+    pub fn last_price(&self) -> Option<usize> {
+        self.last_trade.as_ref().map(|print| print.price)
+    }
+
+    pub fn bid(&self) -> Option<&Level> { // best bid, None when nobody is bidding (a 0 bid is never a real quote)
+        self.bids.first()
+    }
+
+    pub fn ask(&self) -> Option<&Level> { // best ask, None when nobody is offering
+        self.asks.first()
+    }
+
+    pub fn mid(&self) -> Option<f32> { // None unless both sides are actually quoted
+        match (self.bid(), self.ask()) {
+            (Some(bid), Some(ask)) => Some((bid.price + ask.price) as f32 / 2.0),
+            _ => None,
+        }
+    }
+
+    pub fn spread(&self) -> Option<usize> { // ask minus bid, zero if the book is crossed or locked
+        match (self.bid(), self.ask()) {
+            (Some(bid), Some(ask)) => Some(ask.price.saturating_sub(bid.price)),
+            _ => None,
+        }
+    }
+
+    pub fn imbalance(&self) -> f32 { // -1 (all asks) to 1 (all bids), resting size on each side, 0 with nothing resting
+        let bid_size: usize = self.bids.iter().map(|level| level.size).sum();
+        let ask_size: usize = self.asks.iter().map(|level| level.size).sum();
+        let total = bid_size + ask_size;
+        if total == 0 {
+            return 0.0;
+        }
+        (bid_size as f32 - ask_size as f32) / total as f32
+    }
+
+    pub fn is_crossed(&self) -> bool {
+        match (self.bid(), self.ask()) {
+            (Some(bid), Some(ask)) => bid.price >= ask.price,
+            _ => false,
+        }
+    }
+
+    pub fn insert(&mut self, direction: &Direction, level: Level) {
+        // one resting level per player per side, a new quote replaces the old one
+        // levels at the same price keep arrival order
+        match direction {
+            Direction::Buy => {
+                self.bids.retain(|resting| resting.player_name != level.player_name);
+                let idx = self.bids.iter().position(|resting| resting.price < level.price).unwrap_or(self.bids.len());
+                self.bids.insert(idx, level);
+            },
+            Direction::Sell => {
+                self.asks.retain(|resting| resting.player_name != level.player_name);
+                let idx = self.asks.iter().position(|resting| resting.price > level.price).unwrap_or(self.asks.len());
+                self.asks.insert(idx, level);
+            },
+            Direction::Cancel => {}
+        }
+    }
+
+    pub fn layer(&mut self, direction: &Direction, level: Level) {
+        // like insert, but the player's other levels on this side stay put, only one at the same price is replaced
+        let side = match direction {
+            Direction::Buy => &mut self.bids,
+            Direction::Sell => &mut self.asks,
+            Direction::Cancel => return,
         };
+        side.retain(|resting| resting.player_name != level.player_name || resting.price != level.price);
+        let behind = |resting: &Level| if *direction == Direction::Buy { resting.price < level.price } else { resting.price > level.price };
+        let idx = side.iter().position(behind).unwrap_or(side.len());
+        side.insert(idx, level);
+    }
+
+    pub fn cancel(&mut self, player_name: &PlayerName) -> bool { // returns whether anything was actually pulled
+        let resting = self.bids.len() + self.asks.len();
+        self.bids.retain(|level| level.player_name != *player_name);
+        self.asks.retain(|level| level.player_name != *player_name);
+        resting != self.bids.len() + self.asks.len()
+    }
+
+    pub fn is_actionable(&self, player_name: &PlayerName, price: usize, direction: &Direction) -> bool {
+        // an order is worth sending if it crosses someone else's level (walking from the best inward)
+        // or if it improves a best quote that isn't already ours
+        match direction {
+            Direction::Buy => {
+                let crosses = self.asks.iter().take_while(|level| level.price <= price).any(|level| level.player_name != *player_name);
+                crosses || self.bid().is_none_or(|bid| bid.price < price && bid.player_name != *player_name)
+            },
+            Direction::Sell => {
+                let crosses = self.bids.iter().take_while(|level| level.price >= price).any(|level| level.player_name != *player_name);
+                crosses || self.ask().is_none_or(|ask| ask.price > price && ask.player_name != *player_name)
+            },
+            Direction::Cancel => self.bids.iter().chain(self.asks.iter()).any(|level| level.player_name == *player_name)
+        }
+    }
+
+    pub fn is_layerable(&self, player_name: &PlayerName, price: usize, direction: &Direction) -> bool {
+        // is_actionable for a player that layers: our own levels don't stand in the way, the quote only has to beat
+        // everyone else's best and not be one we already have resting at that price
+        let ours = |level: &&Level| level.player_name == *player_name;
+        match direction {
+            Direction::Buy => {
+                let crosses = self.asks.iter().take_while(|level| level.price <= price).any(|level| !ours(&level));
+                let resting = self.bids.iter().filter(ours).any(|level| level.price == price);
+                crosses || (!resting && self.bids.iter().find(|level| !ours(level)).is_none_or(|bid| bid.price < price))
+            },
+            Direction::Sell => {
+                let crosses = self.bids.iter().take_while(|level| level.price >= price).any(|level| !ours(&level));
+                let resting = self.asks.iter().filter(ours).any(|level| level.price == price);
+                crosses || (!resting && self.asks.iter().find(|level| !ours(level)).is_none_or(|ask| ask.price > price))
+            },
+            Direction::Cancel => self.is_actionable(player_name, price, direction),
+        }
     }
+}
 
-    pub fn update_last_trade(&mut self, price: usize) {
-        self.last_trade = Some(price);
+impl Default for Book {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl Book {
     pub fn new() -> Self {
         Self {
-            bid: Quote {
-                price: 0,
-                player_name: PlayerName::None,
-            },
-            ask: Quote {
-                price: 99,
-                player_name: PlayerName::None,
-            },
+            bids: Vec::new(),
+            asks: Vec::new(),
             last_trade: None,
+            volume_since_deal: 0,
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,57 @@
+use super::{Card, Inventory, PlayerName, Trade, DECK_SIZE, COMMON_SUIT_SIZE};
+use std::collections::HashMap;
+
+
+// what we think everyone else holds, in cards per suit ordered spades, clubs, diamonds, hearts
+// - seeded at the deal: the deck averages 10 a suit, whatever we don't hold is spread evenly over the others
+// - every print moves a card from seller to buyer, so a player who keeps buying one suit shows up here quickly
+// - kept fractional so the even split doesn't round away, and never outside what a suit can actually hold
+#[derive(Debug, Clone)]
+pub struct CompetitorModel {
+    pub me: PlayerName,
+    pub holdings: HashMap<PlayerName, [f32; 4]>,
+}
+
+impl CompetitorModel {
+    pub fn new(me: PlayerName) -> Self {
+        Self {
+            me,
+            holdings: HashMap::new(),
+        }
+    }
+
+    pub fn deal(&mut self, hand: &Inventory, players: &[PlayerName]) {
+        self.holdings.clear();
+        let opponents: Vec<&PlayerName> = players.iter().filter(|player| **player != self.me).collect();
+        if opponents.is_empty() {
+            return;
+        }
+        let average_suit = (DECK_SIZE / 4) as f32;
+        let mut seed = [0.0; 4];
+        for card in [Card::Spade, Card::Club, Card::Diamond, Card::Heart] {
+            seed[card.get_index()] = (average_suit - hand.get(&card) as f32).max(0.0) / opponents.len() as f32;
+        }
+        for opponent in opponents {
+            self.holdings.insert(opponent.clone(), seed);
+        }
+    }
+
+    pub fn observe_trade(&mut self, trade: &Trade) {
+        let index = trade.card.get_index();
+        for (player, delta) in [(&trade.buyer, 1.0), (&trade.seller, -1.0)] {
+            if *player == self.me || *player == PlayerName::None {
+                continue; // our own hand is tracked exactly elsewhere
+            }
+            let holding = self.holdings.entry(player.clone()).or_insert([0.0; 4]);
+            holding[index] = (holding[index] + delta).clamp(0.0, COMMON_SUIT_SIZE as f32);
+        }
+    }
+
+    pub fn estimate(&self, player: &PlayerName, card: &Card) -> usize {
+        self.holdings.get(player).map_or(0, |holding| holding[card.get_index()].round() as usize)
+    }
+
+    pub fn clear(&mut self) {
+        self.holdings.clear();
+    }
+}
@@ -0,0 +1,115 @@
+use super::{Card, Inventory, PlayerName};
+use std::collections::HashMap;
+
+
+pub const DECK_SIZE: usize = 40;
+pub const COMMON_SUIT_SIZE: usize = 12;
+
+
+pub fn hand_size(num_players: usize) -> usize { // everyone gets an equal share of the deck: 10 cards at a 4-player table, 8 at 5
+    DECK_SIZE / num_players.max(1)
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DealError {
+    WrongTotal { expected: usize, found: usize }, // the deck wasn't fully (or was over) dealt
+    NoCommonSuit, // no single suit has 12 cards
+    BadSuitCount { card: Card, count: usize }, // a non-common suit that isn't 8 or 10 cards
+    BadEightCount(usize), // exactly one of the other three suits should be short
+}
+
+
+// checks the hands add up to a legal Figgie deck: 40 cards, one 12-card common suit, and 10/10/8 among the rest
+// the goal suit is always the common suit's partner so the color rule holds as long as the split does
+pub fn validate_deal(inventories: &HashMap<PlayerName, Inventory>) -> Result<(), DealError> {
+    let mut totals = Inventory::new();
+    for inventory in inventories.values() {
+        totals.spades += inventory.spades;
+        totals.clubs += inventory.clubs;
+        totals.diamonds += inventory.diamonds;
+        totals.hearts += inventory.hearts;
+    }
+
+    let found = totals.spades + totals.clubs + totals.diamonds + totals.hearts;
+    if found != DECK_SIZE {
+        return Err(DealError::WrongTotal { expected: DECK_SIZE, found });
+    }
+
+    let suits = [Card::Spade, Card::Club, Card::Diamond, Card::Heart];
+    let common_suits: Vec<&Card> = suits.iter().filter(|card| totals.get(card) == COMMON_SUIT_SIZE).collect();
+    if common_suits.len() != 1 {
+        return Err(DealError::NoCommonSuit);
+    }
+
+    let mut eights = 0;
+    for card in suits.iter().filter(|card| *card != common_suits[0]) {
+        match totals.get(card) {
+            8 => eights += 1,
+            10 => {},
+            count => return Err(DealError::BadSuitCount { card: card.clone(), count }),
+        }
+    }
+    if eights != 1 {
+        return Err(DealError::BadEightCount(eights));
+    }
+
+    Ok(())
+}
+
+// the goal suit as the cards alone say it is, the common suit's partner (there are two 10-card suits, so the count can't say it directly)
+// a deal validate_deal wouldn't pass has no answer, its error comes back instead
+pub fn infer_goal_from_distribution(inventories: &HashMap<PlayerName, Inventory>) -> Result<Card, DealError> {
+    validate_deal(inventories)?;
+    let mut totals = Inventory::new();
+    for inventory in inventories.values() {
+        totals.spades += inventory.spades;
+        totals.clubs += inventory.clubs;
+        totals.diamonds += inventory.diamonds;
+        totals.hearts += inventory.hearts;
+    }
+    let common_suit = [Card::Spade, Card::Club, Card::Diamond, Card::Heart].into_iter().find(|card| totals.get(card) == COMMON_SUIT_SIZE).ok_or(DealError::NoCommonSuit)?;
+    Ok(common_suit.get_goal_suit())
+}
+
+
+// what a player learned about the deck at the deal, kept for the round
+// the split is dealt face down, so normally all we know is our own hand and the 12/10/10/8 rule
+#[derive(Debug, Clone, Copy)]
+pub struct DealContext {
+    pub hand: Inventory, // as dealt, before any trading
+    pub totals: Option<Inventory>, // the full split, only when it's been revealed (e.g. replaying a finished round)
+}
+
+impl Default for DealContext {
+    fn default() -> Self {
+        Self::from_hand(Inventory::new())
+    }
+}
+
+impl DealContext {
+    pub fn from_hand(hand: Inventory) -> Self {
+        Self { hand, totals: None }
+    }
+
+    pub fn from_split(hand: Inventory, totals: Inventory) -> Self {
+        Self { hand, totals: Some(totals) }
+    }
+
+    // how many of `card` are in the deck, exact with the split in hand, otherwise the most it could be:
+    // any suit might be the 12-card common suit, unless we were dealt or have since bought more than 10 of another one,
+    // which can only be the common suit itself
+    pub fn suit_total(&self, card: &Card, holding: &Inventory) -> usize {
+        if let Some(totals) = &self.totals {
+            return totals.get(card);
+        }
+        let suits = [Card::Spade, Card::Club, Card::Diamond, Card::Heart];
+        let common_elsewhere = suits.iter().any(|other| other != card && self.hand.get(other).max(holding.get(other)) > 10);
+        if common_elsewhere { 10 } else { COMMON_SUIT_SIZE }
+    }
+
+    // in other hands given what we hold now (trading moves it away from the deal), an upper bound unless the split is known
+    pub fn cards_outstanding(&self, card: &Card, holding: &Inventory) -> usize {
+        self.suit_total(card, holding).saturating_sub(holding.get(card))
+    }
+}
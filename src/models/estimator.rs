@@ -0,0 +1,139 @@
+use super::{Book, Card, Direction, Inventory, Trade};
+
+const SUITS: [Card; 4] = [Card::Spade, Card::Club, Card::Diamond, Card::Heart];
+const NEUTRAL_PRICE: f32 = 5.0; // roughly what a card is worth before anyone knows anything
+const TRADE_WEIGHT: f32 = 0.15; // log-odds moved per tick a print is above/below neutral
+const BOOK_WEIGHT: f32 = 0.05; // same for the live quotes, much weaker since quotes are cheap to post
+const SELL_AGGRESSOR_DISCOUNT: f32 = 0.5; // a bid getting hit mostly says what the bidder thought, and observe_books already has that
+
+
+// probability each suit is the goal suit, ordered spades, clubs, diamonds, hearts
+#[derive(Debug, Clone, Copy)]
+pub struct SuitPriors {
+    pub probabilities: [f32; 4],
+}
+
+impl SuitPriors {
+    pub fn uniform() -> Self {
+        Self { probabilities: [0.25; 4] }
+    }
+
+    pub fn from_hand(hand: &Inventory) -> Self { // what the hand alone says, before any trading
+        let mut estimator = GoalSuitEstimator::new();
+        estimator.reset(hand);
+        estimator.priors()
+    }
+
+    pub fn get(&self, card: &Card) -> f32 {
+        self.probabilities[card.get_index()]
+    }
+}
+
+
+// posterior over which suit is the goal suit, kept in log space
+// - prior: our own hand, the goal suit's partner is the 12-card common suit so holding a lot of a suit points at its partner
+// - flow: every print moves the odds, trading a suit above neutral price is evidence someone knows it's the goal
+// - book: the current quotes, replaced on every update rather than accumulated so a resting quote isn't counted twice
+#[derive(Debug, Clone)]
+pub struct GoalSuitEstimator {
+    pub log_prior: [f32; 4],
+    pub log_flow: [f32; 4],
+    pub log_book: [f32; 4],
+}
+
+impl Default for GoalSuitEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GoalSuitEstimator {
+    pub fn new() -> Self {
+        Self {
+            log_prior: [0.0; 4],
+            log_flow: [0.0; 4],
+            log_book: [0.0; 4],
+        }
+    }
+
+    pub fn reset(&mut self, hand: &Inventory) {
+        // treat each card in hand as a draw from the deck implied by each hypothesis:
+        // common suit 12, goal suit 8 or 10 (call it 9), the other two share what's left
+        for (idx, goal) in SUITS.iter().enumerate() {
+            let common = goal.get_goal_suit(); // the pairing is symmetric
+            let mut log_likelihood = 0.0;
+            for suit in SUITS.iter() {
+                let expected = if *suit == common {
+                    12.0
+                } else if suit == goal {
+                    9.0
+                } else {
+                    9.5
+                };
+                log_likelihood += hand.get(suit) as f32 * (expected / 40.0_f32).ln();
+            }
+            self.log_prior[idx] = log_likelihood;
+        }
+        self.log_flow = [0.0; 4];
+        self.log_book = [0.0; 4];
+    }
+
+    pub fn observe_trade(&mut self, trade: &Trade) {
+        // someone lifting an offer is paying up for the suit, that's the print worth listening to
+        let weight = match trade.aggressor {
+            Direction::Sell => TRADE_WEIGHT * SELL_AGGRESSOR_DISCOUNT,
+            _ => TRADE_WEIGHT,
+        };
+        self.log_flow[trade.card.get_index()] += weight * (trade.price as f32 - NEUTRAL_PRICE);
+    }
+
+    // books are ordered spades, clubs, diamonds, hearts
+    pub fn observe_books(&mut self, books: &[Book; 4]) {
+        for (idx, book) in books.iter().enumerate() {
+            // only the bid says anything, anyone can offer a card they think is worthless at a high price
+            self.log_book[idx] = match book.bids.first() {
+                Some(level) => BOOK_WEIGHT * (level.price as f32 - NEUTRAL_PRICE),
+                None => 0.0,
+            };
+        }
+    }
+
+    pub fn posterior(&self) -> [f32; 4] {
+        let mut log_odds = [0.0; 4];
+        for (idx, value) in log_odds.iter_mut().enumerate() {
+            *value = self.log_prior[idx] + self.log_flow[idx] + self.log_book[idx];
+        }
+
+        // normalise with the max pulled out so exp doesn't overflow
+        let max = log_odds.iter().cloned().fold(f32::MIN, f32::max);
+        let mut posterior = [0.0; 4];
+        let mut total = 0.0;
+        for (idx, value) in log_odds.iter().enumerate() {
+            posterior[idx] = (value - max).exp();
+            total += posterior[idx];
+        }
+        for value in posterior.iter_mut() {
+            *value /= total;
+        }
+        posterior
+    }
+
+    pub fn priors(&self) -> SuitPriors {
+        SuitPriors { probabilities: self.posterior() }
+    }
+
+    pub fn probability(&self, card: &Card) -> f32 {
+        self.posterior()[card.get_index()]
+    }
+
+    pub fn most_likely(&self) -> (Card, f32) {
+        let posterior = self.posterior();
+        let mut best = (Card::Spade, posterior[0]);
+        for (idx, suit) in SUITS.iter().enumerate().skip(1) {
+            if posterior[idx] > best.1 {
+                best = (suit.clone(), posterior[idx]);
+            }
+        }
+        best
+    }
+}
@@ -1,28 +1,56 @@
-use super::{Card, Book, Inventory, PlayerName};
+use super::{Card, Book, Direction, Inventory, PlayerName, RoundResult, RejectReason, Pot};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Trade {
     pub card: Card,
     pub price: usize,
     pub buyer: PlayerName,
     pub seller: PlayerName,
+    pub aggressor: Direction, // the side of the incoming order, the other one was resting
+    pub timestamp: u64, // ms since the round opened, monotonic within a round
 }
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Update {
     pub spades: Book,
     pub clubs: Book,
     pub diamonds: Book,
     pub hearts: Book,
-    pub trade: Option<Trade>,
+    pub trades: Vec<Trade>, // one trade per unit filled
 }
 
-#[derive(Debug, Clone)]
+// an Update without the books that didn't change, only sent when no trade happened (a trade resets every book)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookDiff {
+    pub changed: Vec<(Card, Book)>,
+    pub trades: Vec<Trade>,
+}
+
+impl BookDiff {
+    pub fn apply(self, books: &[Book; 4]) -> Update { // books ordered spades, clubs, diamonds, hearts, as last seen
+        let mut books = books.clone();
+        for (card, book) in self.changed {
+            books[card.get_index()] = book;
+        }
+        let [spades, clubs, diamonds, hearts] = books;
+        Update { spades, clubs, diamonds, hearts, trades: self.trades }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)] // Update dominates the traffic anyway, boxing it buys nothing
 pub enum Event {
-    Update(Update),
-    DealCards(HashMap<PlayerName, Inventory>),
-    EndRound,
+    Update(Update), // every book, the resync path
+    Diff(BookDiff), // just the books that moved, merged onto the last Update
+    DealCards { inventories: HashMap<PlayerName, Inventory>, pot: Pot }, // the stakes go out with the hands
+    EndRound(RoundResult),
+    OrderRejected { player_name: PlayerName, card: Card, reason: RejectReason }, // only the named player needs to care
+    Snapshot { player_name: PlayerName, spades: Book, clubs: Book, diamonds: Book, hearts: Book, inventory: Inventory }, // answers one player's resync request
+    AccountUpdate { player_name: PlayerName, cash: usize }, // the exchange's figure, sent once the antes are in and again once the round is paid out
+    Heartbeat { tick: u64 }, // the exchange is still there, sent every HEARTBEAT_INTERVAL while a round runs
+    OrderAck { player_name: PlayerName, seq: u64, accepted: bool }, // the fate of the named player's order `seq`, after any Update it caused
 }
\ No newline at end of file
@@ -0,0 +1,59 @@
+use super::{Card, Inventory, Trade, COMMON_SUIT_SIZE, DECK_SIZE, POT_SIZE};
+
+
+const GOAL_CARD_PAYOUT: f32 = 10.0;
+const EXPECTED_GOAL_CARDS: f32 = 9.0; // the goal suit is 8 or 10 cards
+const BONUS_SHARE: f32 = 0.5; // the majority bonus only goes to one player, a single card gets half its share
+const LONG_SUIT_SIZE: usize = 10; // the biggest a suit can be without being the common suit
+const PRIOR_WEIGHT: f32 = 3.0; // how many prints the count-based prior is worth
+
+
+// what one card of a suit is worth, from two sources:
+// - prior: how many of the suit are out there, the 12-card common suit can never be the goal, any other suit is one of three candidates
+// - trades: the running average of the suit's prints, which takes over as more of them come in
+pub struct FairValue;
+
+impl FairValue {
+    pub fn estimate(card: &Card, observed_trades: &[Trade], suit_count_hint: usize) -> f32 {
+        let prior = Self::goal_odds(suit_count_hint) * Self::goal_card_value();
+
+        let prices: Vec<f32> = observed_trades.iter().filter(|trade| trade.card == *card).map(|trade| trade.price as f32).collect();
+        if prices.is_empty() {
+            return prior;
+        }
+        let average = prices.iter().sum::<f32>() / prices.len() as f32;
+        let trade_weight = prices.len() as f32 / (prices.len() as f32 + PRIOR_WEIGHT);
+        trade_weight * average + (1.0 - trade_weight) * prior
+    }
+
+    // estimate, pulled towards what the card pays if `card` is the goal suit by the estimator's odds
+    // - a suit the posterior is sure of is worth nearly the whole goal card value (~$16.1) whatever it has been trading at,
+    // one it has ruled out is worth next to nothing
+    // - how hard we pull depends on how decided the posterior is overall: uniform odds leave the plain estimate alone
+    pub fn with_goal(card: &Card, observed_trades: &[Trade], suit_count_hint: usize, goal_posterior: &[f32; 4]) -> f32 {
+        let estimate = Self::estimate(card, observed_trades, suit_count_hint);
+        let expected = goal_posterior[card.get_index()] * Self::goal_card_value();
+        let most_likely = goal_posterior.iter().copied().fold(0.0, f32::max);
+        let decisiveness = ((most_likely - 0.25) / 0.75).clamp(0.0, 1.0);
+        decisiveness * expected + (1.0 - decisiveness) * estimate
+    }
+
+    // how many of `card` the whole deck holds, scaled up from what we were dealt
+    pub fn suit_count_hint(hand: &Inventory, card: &Card) -> usize {
+        let hand_size = hand.total();
+        if hand_size == 0 {
+            return DECK_SIZE / 4;
+        }
+        hand.get(card) * DECK_SIZE / hand_size
+    }
+
+    fn goal_odds(suit_count: usize) -> f32 {
+        // one in three for anything up to 10 cards, sliding to nothing as it looks more like the common suit
+        let common_likeness = suit_count.saturating_sub(LONG_SUIT_SIZE) as f32 / (COMMON_SUIT_SIZE - LONG_SUIT_SIZE) as f32;
+        (1.0 - common_likeness.min(1.0)) / 3.0
+    }
+
+    pub fn goal_card_value() -> f32 { // a sure payout plus a marginal card's share of the majority bonus
+        GOAL_CARD_PAYOUT + BONUS_SHARE * (POT_SIZE as f32 - GOAL_CARD_PAYOUT * EXPECTED_GOAL_CARDS) / EXPECTED_GOAL_CARDS
+    }
+}
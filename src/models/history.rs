@@ -0,0 +1,48 @@
+use super::Trade;
+
+
+pub const DEFAULT_TRADE_HISTORY: usize = 256; // a busy round prints well under this
+
+
+// the most recent `capacity` trades, oldest first
+// backed by a Vec that's trimmed once it reaches twice the capacity, so pushes stay cheap and `as_slice` stays one contiguous slice
+#[derive(Debug, Clone)]
+pub struct TradeHistory {
+    trades: Vec<Trade>,
+    pub capacity: usize,
+}
+
+impl Default for TradeHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRADE_HISTORY)
+    }
+}
+
+impl TradeHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self { trades: Vec::with_capacity(capacity), capacity }
+    }
+
+    pub fn push(&mut self, trade: Trade) {
+        if self.trades.len() >= self.capacity.max(1) * 2 {
+            self.trades.drain(..self.trades.len() - self.capacity);
+        }
+        self.trades.push(trade);
+    }
+
+    pub fn as_slice(&self) -> &[Trade] {
+        &self.trades[self.trades.len().saturating_sub(self.capacity)..]
+    }
+
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.trades.clear();
+    }
+}
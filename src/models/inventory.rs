@@ -1,7 +1,16 @@
-use super::{Card};
+use super::{Card, SuitPriors, POT_SIZE, DECK_SIZE, Pot};
+use serde::{Deserialize, Serialize};
 
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InventoryDrift {
+    pub card: Card,
+    pub local: usize, // what we had counted from trades
+    pub expected: usize, // what the exchange says we hold
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Inventory {
     pub spades: usize,
     pub clubs: usize,
@@ -9,6 +18,12 @@ pub struct Inventory {
     pub hearts: usize,
 }
 
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Inventory {
     pub fn new() -> Self {
         Self {
@@ -33,44 +48,79 @@ impl Inventory {
     pub fn change(&mut self, card: Card, add: bool) {
         match card {
             Card::Spade => {
-                let new_amount: usize;
-                if add {
-                    new_amount = self.spades + 1;
+                let new_amount = if add {
+                    self.spades + 1
                 } else {
-                    new_amount = self.spades - 1;
-                }
+                    self.spades.saturating_sub(1)
+                };
                 self.spades = new_amount;
             },
             Card::Club => {
-                let new_amount: usize;
-                if add {
-                    new_amount = self.clubs + 1;
+                let new_amount = if add {
+                    self.clubs + 1
                 } else {
-                    new_amount = self.clubs - 1;
-                }
+                    self.clubs.saturating_sub(1)
+                };
                 self.clubs = new_amount;
             },
             Card::Diamond => {
-                let new_amount: usize;
-                if add {
-                    new_amount = self.diamonds + 1;
+                let new_amount = if add {
+                    self.diamonds + 1
                 } else {
-                    new_amount = self.diamonds - 1;
-                }
+                    self.diamonds.saturating_sub(1)
+                };
                 self.diamonds = new_amount;
             },
             Card::Heart => {
-                let new_amount: usize;
-                if add {
-                    new_amount = self.hearts + 1;
+                let new_amount = if add {
+                    self.hearts + 1
                 } else {
-                    new_amount = self.hearts - 1;
-                }
+                    self.hearts.saturating_sub(1)
+                };
                 self.hearts = new_amount;
             },
         }
     }
 
+    pub fn total(&self) -> usize {
+        self.spades + self.clubs + self.diamonds + self.hearts
+    }
+
+    pub fn expected_value(&self, priors: &SuitPriors) -> f32 {
+        // round EV of holding this hand to the end, net of the ante
+        // - each goal card pays 10, the suit is 8 or 10 cards so call it 9, leaving ~110 of the pot for the majority
+        // - holding 5 of ~9 goal cards all but guarantees the majority, below that we credit the bonus linearly (crude, but monotone)
+        // - the table size is read off the hand, everyone is dealt an equal share of the 40 cards
+        let hand_size = self.total();
+        if hand_size == 0 {
+            return 0.0;
+        }
+        let players = (DECK_SIZE / hand_size).max(1);
+        let ante = Pot::new(players).ante as f32;
+        let remaining_pot = POT_SIZE as f32 - 10.0 * 9.0;
+
+        let mut expected = 0.0;
+        for card in [Card::Spade, Card::Club, Card::Diamond, Card::Heart].iter() {
+            let held = self.get(card) as f32;
+            let majority_odds = (held / 5.0).min(1.0);
+            expected += priors.get(card) * (10.0 * held + majority_odds * remaining_pot);
+        }
+        expected - ante
+    }
+
+    // overwrite our count with the exchange's, returning every suit that had drifted (a dropped or doubled trade)
+    pub fn reconcile(&mut self, expected: &Inventory) -> Vec<InventoryDrift> {
+        let mut drift = Vec::new();
+        for card in [Card::Spade, Card::Club, Card::Diamond, Card::Heart] {
+            let (local, expected) = (self.get(&card), expected.get(&card));
+            if local != expected {
+                drift.push(InventoryDrift { card, local, expected });
+            }
+        }
+        *self = *expected;
+        drift
+    }
+
     pub fn get(&self, card: &Card) -> usize {
         match card {
             Card::Spade => self.spades,
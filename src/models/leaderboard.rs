@@ -0,0 +1,110 @@
+use super::{PlayerName, RoundResult, CL, settle_round};
+use std::collections::HashMap;
+use std::io;
+
+
+// one player's running totals across every round it's been recorded for
+#[derive(Debug, Clone, Default)]
+pub struct Standing {
+    pub rounds: u32,
+    pub net: i64,
+    pub best_round: i64,
+    pub worst_round: i64,
+    pub majorities: u32, // rounds they held (or tied for) the most goal cards
+    pub goal_cards: usize,
+    pub history: Vec<i64>, // every round's net, in the order they were played
+}
+
+impl Standing {
+    pub fn average(&self) -> f32 {
+        if self.rounds == 0 {
+            return 0.0;
+        }
+        self.net as f32 / self.rounds as f32
+    }
+
+    pub fn variance(&self) -> f32 { // of the per-round net, how much the average can be trusted
+        if self.history.is_empty() {
+            return 0.0;
+        }
+        let mean = self.average();
+        self.history.iter().map(|net| (*net as f32 - mean).powi(2)).sum::<f32>() / self.history.len() as f32
+    }
+}
+
+
+// =-= Leaderboard =-= //
+// net P&L per player across rounds, fed one EndRound at a time, for telling which strategy actually wins over many deals
+#[derive(Debug, Clone, Default)]
+pub struct Leaderboard {
+    pub standings: HashMap<PlayerName, Standing>,
+}
+
+impl Leaderboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // `net` is whatever the round did to the player's points, trading included
+    pub fn record(&mut self, player_name: &PlayerName, net: i64, result: &RoundResult) {
+        let goal_cards = result.inventories.get(player_name).map(|inventory| inventory.get(&result.goal_suit)).unwrap_or(0);
+        let (most, _) = result.get_majority();
+
+        let standing = self.standings.entry(player_name.clone()).or_default();
+        if standing.rounds == 0 {
+            standing.best_round = net;
+            standing.worst_round = net;
+        }
+        standing.rounds += 1;
+        standing.net += net;
+        standing.history.push(net);
+        standing.best_round = standing.best_round.max(net);
+        standing.worst_round = standing.worst_round.min(net);
+        standing.goal_cards += goal_cards;
+        if goal_cards == most && most > 0 {
+            standing.majorities += 1;
+        }
+    }
+
+    // for when all we've got is the EndRound itself, each player is credited its payout less the ante (no trading P&L)
+    pub fn record_settlement(&mut self, result: &RoundResult) {
+        for (player_name, inventory) in &result.inventories {
+            self.record(player_name, settle_round(inventory, result), result);
+        }
+    }
+
+    pub fn ranked(&self) -> Vec<(&PlayerName, &Standing)> { // best net first
+        let mut ranked: Vec<(&PlayerName, &Standing)> = self.standings.iter().collect();
+        ranked.sort_by_key(|(_, standing)| std::cmp::Reverse(standing.net));
+        ranked
+    }
+
+    pub fn print(&self) {
+        println!("=---------------------------- Standings ----------------------------=");
+        for (place, (player_name, standing)) in self.ranked().iter().enumerate() {
+            let net_color = match standing.net {
+                x if x > 0 => CL::Green.get(),
+                x if x < 0 => CL::Red.get(),
+                _ => CL::Dull.get(),
+            };
+            println!("{} {}. {}{:?}{} | Net: {}{:+}{} ({:+.1}/round, best {:+}, worst {:+}) | Majorities: {}/{} | Goal Cards: {}x{}", CL::Dull.get(), place + 1, CL::DimLightBlue.get(), player_name, CL::Dull.get(), net_color, standing.net, CL::Dull.get(), standing.average(), standing.best_round, standing.worst_round, standing.majorities, standing.rounds, standing.goal_cards, CL::End.get());
+        }
+        println!();
+    }
+
+    pub fn reset(&mut self) { // start a fresh session
+        self.standings.clear();
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("rank,player,rounds,net,average,best_round,worst_round,majorities,goal_cards\n");
+        for (place, (player_name, standing)) in self.ranked().iter().enumerate() {
+            csv += &format!("{},{:?},{},{},{:.2},{},{},{},{}\n", place + 1, player_name, standing.rounds, standing.net, standing.average(), standing.best_round, standing.worst_round, standing.majorities, standing.goal_cards);
+        }
+        csv
+    }
+
+    pub fn write_csv(&self, file_path: &str) -> io::Result<()> {
+        std::fs::write(file_path, self.to_csv())
+    }
+}
@@ -1,4 +1,5 @@
 use super::{PlayerName, CL};
+use serde::{Deserialize, Serialize};
 
 pub mod event;
 pub use event::*;
@@ -8,9 +9,47 @@ pub mod inventory;
 pub use inventory::*;
 pub mod order;
 pub use order::*;
+pub mod settlement;
+pub use settlement::*;
+pub mod pnl;
+pub use pnl::*;
+pub mod estimator;
+pub use estimator::*;
+pub mod distribution;
+pub use distribution::*;
+pub mod price;
+pub use price::*;
+pub mod fair_value;
+pub use fair_value::*;
+pub mod history;
+pub use history::*;
+pub mod leaderboard;
+pub use leaderboard::*;
+pub mod audit;
+pub use audit::*;
+pub mod competitor;
+pub use competitor::*;
+pub mod report;
+pub use report::*;
 
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Color {
+    Black,
+    Red,
+}
+
+impl Color {
+    pub fn suits(&self) -> [Card; 2] {
+        match self {
+            Color::Black => [Card::Spade, Card::Club],
+            Color::Red => [Card::Diamond, Card::Heart],
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Card {
     Spade,
     Club,
@@ -19,6 +58,15 @@ pub enum Card {
 }
 
 impl Card {
+    pub fn get_index(&self) -> usize { // position in spades, clubs, diamonds, hearts ordering used by Inventory and Update
+        match self {
+            Card::Spade => 0,
+            Card::Club => 1,
+            Card::Diamond => 2,
+            Card::Heart => 3,
+        }
+    }
+
     pub fn get_other_cards(&self) -> (Card, Card, Card) { // common_suite, suit_1, suit_2
         match self {
             Card::Spade => (Card::Club, Card::Diamond, Card::Heart),
@@ -28,15 +76,18 @@ impl Card {
         }
     }
 
-    pub fn get_goal_suit(&self) -> Card {
+    pub fn color(&self) -> Color {
         match self {
-            Card::Spade => Card::Club,
-            Card::Club => Card::Spade,
-            Card::Heart => Card::Diamond,
-            Card::Diamond => Card::Heart
+            Card::Spade | Card::Club => Color::Black,
+            Card::Diamond | Card::Heart => Color::Red,
         }
     }
 
+    pub fn get_goal_suit(&self) -> Card { // the other suit of the same color
+        let [first, second] = self.color().suits();
+        if *self == first { second } else { first }
+    }
+
     pub fn get_book_colors(&self) -> (CL, CL, CL, CL) {
         match self {
             Card::Spade => (CL::LimeGreen, CL::DullTeal, CL::DullTeal, CL::DullTeal),
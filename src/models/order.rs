@@ -1,16 +1,202 @@
-use super::{Card, PlayerName};
+use super::{Book, Card, PlayerName};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Direction {
     Buy,
     Sell,
+    Cancel, // pulls every resting level the player has in the suit, price and size are ignored
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeInForce {
+    #[default]
+    GTC, // whatever doesn't trade rests on the book
+    IOC, // trade what crosses now, drop the rest
+    FOK, // trade the whole size now or nothing at all
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Order {
     pub player_name: PlayerName,
     pub price: usize,
     pub direction: Direction,
     pub card: Card,
-}
\ No newline at end of file
+    pub size: usize,
+    #[serde(default)] // clients that predate it get GTC
+    pub tif: TimeInForce,
+    #[serde(default)]
+    pub context: Option<OrderContext>, // the book as the player saw it when deciding, for measuring how stale the order was on arrival
+    #[serde(default)]
+    pub layer: bool, // rest alongside the player's other levels on this side rather than replacing them
+    #[serde(default)]
+    pub paired: Option<Box<Order>>, // the other leg of a two-sided quote, the exchange posts both or neither
+    #[serde(default)]
+    pub seq: u64, // stamped by the sending player, counting up from 1, the exchange answers with an OrderAck carrying it, 0 is never acked
+}
+
+impl Order {
+    pub fn new(player_name: PlayerName, price: usize, direction: Direction, card: Card, size: usize) -> Self { // a plain GTC limit order
+        Self {
+            player_name,
+            price,
+            direction,
+            card,
+            size,
+            tif: TimeInForce::GTC,
+            context: None,
+            layer: false,
+            paired: None,
+            seq: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderContext {
+    pub decided_at_ms: u64, // unix ms, wall clock since the player and the exchange share no other
+    pub bid: Option<usize>,
+    pub ask: Option<usize>,
+    pub last_trade: Option<usize>,
+}
+
+impl OrderContext {
+    pub fn from_book(book: &Book) -> Self {
+        Self {
+            decided_at_ms: now_ms(),
+            bid: book.bid().map(|bid| bid.price),
+            ask: book.ask().map(|ask| ask.price),
+            last_trade: book.last_price(),
+        }
+    }
+
+    pub fn age_ms(&self) -> u64 {
+        now_ms().saturating_sub(self.decided_at_ms)
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_millis() as u64).unwrap_or(0)
+}
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RejectReason {
+    SelfCross, // would have traded against the player's own resting level
+    NoInventory, // sell with nothing to deliver
+    NoCash, // buy the player can't pay for, not even one card at its own price
+    PriceNotImproving, // doesn't cross and doesn't beat the best quote already on that side
+    Killed, // FOK that couldn't be filled in full
+    OffLadder, // priced outside the suit's PriceLadder bounds
+    WouldCross, // a leg of a two-sided quote that would have traded, quotes only ever rest
+    LegRejected, // the other leg of its two-sided quote was thrown out, so this one was too
+}
+
+
+// =-= Ordering guarantees =-= //
+// - every player shares one FIFO channel into the exchange, so a player's orders are processed in the order it sent them
+// - an Update reflects every order processed before it, the book a strategy sees is as of the last Update it has read
+// - any trade resets all four books and drops every order still queued behind it, so nothing we sent before that Update survives it
+// - a quote is live from the first Update that shows it until the next Update carrying trades, or our cancel in that suit
+// the gap is the round trip: between sending and seeing the Update, our own quote isn't in our copy of the book,
+// so the player keeps what it has in flight here and holds back anything that would cross it (the exchange rejects a SelfCross as a backstop)
+// a side can hold several of our orders once we layer, a non-layering order replaces the ones before it just like on the book
+#[derive(Debug, Clone, Default)]
+pub struct OutstandingOrders {
+    pub resting: HashMap<(Card, Direction), Vec<Order>>,
+}
+
+impl OutstandingOrders {
+    pub fn new() -> Self {
+        Self { resting: HashMap::new() }
+    }
+
+    pub fn record(&mut self, order: &Order) {
+        match (&order.direction, &order.tif) {
+            (Direction::Cancel, _) => self.forget_card(&order.card),
+            (_, TimeInForce::GTC) => {
+                let side = self.resting.entry((order.card.clone(), order.direction.clone())).or_default();
+                if order.layer {
+                    side.retain(|resting| resting.price != order.price);
+                } else {
+                    side.clear();
+                }
+                side.push(order.clone());
+            },
+            _ => {} // IOC and FOK never rest
+        }
+    }
+
+    pub fn crosses_own(&self, order: &Order) -> bool { // against every level we have on the other side, not just the best
+        let opposite = match order.direction {
+            Direction::Buy => Direction::Sell,
+            Direction::Sell => Direction::Buy,
+            Direction::Cancel => return false,
+        };
+        self.resting.get(&(order.card.clone(), opposite)).is_some_and(|side| side.iter().any(|resting| match order.direction {
+            Direction::Buy => order.price >= resting.price,
+            _ => order.price <= resting.price,
+        }))
+    }
+
+    pub fn forget(&mut self, order: &Order) {
+        if let Some(side) = self.resting.get_mut(&(order.card.clone(), order.direction.clone())) {
+            side.retain(|resting| resting.price != order.price);
+        }
+    }
+
+    pub fn forget_card(&mut self, card: &Card) {
+        self.resting.retain(|(resting_card, _), _| resting_card != card);
+    }
+
+    pub fn clear(&mut self) {
+        self.resting.clear();
+    }
+}
+
+
+// what we've sent and not heard back about, by seq: an OrderAck resolves one, anything left past the timeout was lost on the way
+// (after a trade the exchange drops what's still queued and acks it as not accepted, so silence means it never arrived)
+#[derive(Debug, Clone, Default)]
+pub struct PendingAcks {
+    next_seq: u64,
+    pub pending: HashMap<u64, (Order, Instant)>,
+}
+
+impl PendingAcks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stamp(&mut self, order: &mut Order, now: Instant) { // call right before sending
+        self.next_seq += 1;
+        order.seq = self.next_seq;
+        self.pending.insert(order.seq, (order.clone(), now));
+    }
+
+    pub fn resolve(&mut self, seq: u64) -> Option<Order> {
+        self.pending.remove(&seq).map(|(order, _)| order)
+    }
+
+    pub fn expire(&mut self, now: Instant, timeout: Duration) -> Vec<Order> { // oldest first
+        let mut expired = Vec::new();
+        self.pending.retain(|_, (order, sent)| {
+            let waiting = now.saturating_duration_since(*sent) < timeout;
+            if !waiting {
+                expired.push(order.clone());
+            }
+            waiting
+        });
+        expired.sort_by_key(|order| order.seq);
+        expired
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
@@ -0,0 +1,66 @@
+use super::{Book, Card, Inventory, PlayerName, Trade};
+
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pnl {
+    pub realized: i64, // cash in minus cash out from trading this round
+    pub bought: [usize; 4], // cards per suit bought this round and still held, dealt cards cost nothing and aren't counted
+    pub cost: [i64; 4], // what those cards cost us, sells take out the average cost
+    pub trades: usize, // fills we were on either side of this round
+}
+
+impl Pnl {
+    pub fn new() -> Self {
+        Self { realized: 0, bought: [0; 4], cost: [0; 4], trades: 0 }
+    }
+
+    pub fn record(&mut self, trade: &Trade, player_name: &PlayerName) {
+        let index = trade.card.get_index();
+        if trade.buyer == *player_name {
+            self.trades += 1;
+            self.realized -= trade.price as i64;
+            self.bought[index] += 1;
+            self.cost[index] += trade.price as i64;
+        } else if trade.seller == *player_name {
+            self.trades += 1;
+            self.realized += trade.price as i64;
+            if self.bought[index] > 0 { // bought cards go first, anything past them came from the deal
+                self.cost[index] -= self.cost[index] / self.bought[index] as i64;
+                self.bought[index] -= 1;
+            }
+        }
+    }
+
+    // paper gain (loss if negative) on the cards of `card` we paid for, marked at the best bid since that's where we'd get out
+    // None when nobody is bidding, there's no price to mark at
+    pub fn unrealized(&self, card: &Card, book: &Book) -> Option<i64> {
+        let index = card.get_index();
+        let bid = book.bid()?;
+        Some(self.bought[index] as i64 * bid.price as i64 - self.cost[index])
+    }
+
+    // suits whose bought cards are down at least `max_drawdown` at the bid, books ordered as in mark_to_market
+    pub fn drawdowns(&self, books: &[Book; 4], max_drawdown: usize) -> Vec<Card> {
+        if max_drawdown == 0 {
+            return Vec::new();
+        }
+        [Card::Spade, Card::Club, Card::Diamond, Card::Heart].into_iter()
+            .filter(|card| self.unrealized(card, &books[card.get_index()]).is_some_and(|unrealized| unrealized <= -(max_drawdown as i64)))
+            .collect()
+    }
+
+    // books are ordered spades, clubs, diamonds, hearts (same as Inventory)
+    pub fn mark_to_market(&self, inventory: &Inventory, books: &[Book; 4]) -> i64 {
+        let held = [inventory.spades, inventory.clubs, inventory.diamonds, inventory.hearts];
+        let mut unrealized = 0.0;
+        for (amount, book) in held.iter().zip(books.iter()) {
+            unrealized += *amount as f32 * Self::get_mark(book);
+        }
+        self.realized + unrealized.round() as i64
+    }
+
+    fn get_mark(book: &Book) -> f32 {
+        // mid when both sides are quoted, otherwise the last print, otherwise nothing to go off of
+        book.mid().unwrap_or(book.last_price().unwrap_or(0) as f32)
+    }
+}
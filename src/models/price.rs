@@ -0,0 +1,88 @@
+use super::Card;
+use serde::{Deserialize, Serialize};
+
+
+pub const DEFAULT_MAX_PRICE: usize = 30; // a goal card pays 10 plus its cut of the pot, nothing sensible trades above this
+
+
+// prices stay plain usize on the wire, this is for the arithmetic strategies do on the way to an order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Price(pub usize);
+
+impl Price {
+    pub fn new(value: usize) -> Self {
+        Self(value)
+    }
+
+    pub fn from_f32(value: f32) -> Option<Self> { // rounds to the nearest tick, None for NaN, infinities and negatives
+        if !value.is_finite() || value < 0.0 {
+            return None;
+        }
+        Some(Self(value.round() as usize)) // float to int casts saturate, so a huge value can't wrap
+    }
+
+    pub fn saturating_add(self, ticks: usize) -> Self {
+        Self(self.0.saturating_add(ticks))
+    }
+
+    pub fn saturating_sub(self, ticks: usize) -> Self {
+        Self(self.0.saturating_sub(ticks))
+    }
+
+    pub fn clamp_to(self, max_price: usize) -> Self {
+        Self(self.0.min(max_price))
+    }
+
+    pub fn value(self) -> usize {
+        self.0
+    }
+}
+
+
+// the prices a suit is allowed to trade at, min..=max in steps of tick counted up from min
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PriceLadder {
+    pub min: usize,
+    pub max: usize,
+    pub tick: usize,
+}
+
+impl Default for PriceLadder {
+    fn default() -> Self {
+        Self { min: 1, max: DEFAULT_MAX_PRICE, tick: 1 }
+    }
+}
+
+impl PriceLadder {
+    pub fn contains(&self, price: usize) -> bool { // inside the bounds, on a tick or not
+        price >= self.min && price <= self.max
+    }
+
+    pub fn round(&self, price: usize) -> usize { // nearest rung, anything past the bounds lands on the end rung
+        let tick = self.tick.max(1);
+        let price = price.clamp(self.min, self.max.max(self.min));
+        let snapped = self.min + (price - self.min + tick / 2) / tick * tick;
+        if snapped > self.max { snapped - tick } else { snapped } // max itself needn't be a rung
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SuitLadders {
+    pub spades: PriceLadder,
+    pub clubs: PriceLadder,
+    pub diamonds: PriceLadder,
+    pub hearts: PriceLadder,
+}
+
+impl SuitLadders {
+    pub fn get(&self, card: &Card) -> &PriceLadder {
+        match card {
+            Card::Spade => &self.spades,
+            Card::Club => &self.clubs,
+            Card::Diamond => &self.diamonds,
+            Card::Heart => &self.hearts,
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use super::{Card, Inventory, PlayerName, Pnl, RoundResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+
+
+// one player's account of a round it just finished, sent from its EndRound handler
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundReport {
+    pub player: PlayerName,
+    pub goal_suit: Card,
+    pub final_inventory: Inventory, // as the exchange settled it
+    pub num_trades: usize,
+    pub realized_pnl: i64, // trading only, the payout isn't in here
+    pub goal_suit_held: usize,
+}
+
+impl RoundReport {
+    pub fn new(player: PlayerName, final_inventory: Inventory, pnl: &Pnl, result: &RoundResult) -> Self {
+        Self {
+            player,
+            goal_suit: result.goal_suit.clone(),
+            final_inventory,
+            num_trades: pnl.trades,
+            realized_pnl: pnl.realized,
+            goal_suit_held: final_inventory.get(&result.goal_suit),
+        }
+    }
+
+    pub fn goal_share(&self) -> f32 { // fraction of what we ended up holding that paid out
+        let held = self.final_inventory.spades + self.final_inventory.clubs + self.final_inventory.diamonds + self.final_inventory.hearts;
+        if held == 0 {
+            return 0.0;
+        }
+        self.goal_suit_held as f32 / held as f32
+    }
+}
+
+
+// every RoundReport from every player, in the order they came in
+// a strategy whose goal share sits well above a quarter is finding the goal suit, one below it is overpaying for the others
+#[derive(Debug, Clone, Default)]
+pub struct ReportCollector {
+    pub reports: Vec<RoundReport>,
+}
+
+impl ReportCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, report: RoundReport) {
+        self.reports.push(report);
+    }
+
+    pub fn len(&self) -> usize {
+        self.reports.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reports.is_empty()
+    }
+
+    pub fn average_goal_share(&self) -> HashMap<PlayerName, f32> { // per player, across every round it reported
+        let mut totals: HashMap<PlayerName, (f32, usize)> = HashMap::new();
+        for report in &self.reports {
+            let total = totals.entry(report.player.clone()).or_insert((0.0, 0));
+            total.0 += report.goal_share();
+            total.1 += 1;
+        }
+        totals.into_iter().map(|(player, (share, rounds))| (player, share / rounds as f32)).collect()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("player,goal_suit,spades,clubs,diamonds,hearts,num_trades,realized_pnl,goal_suit_held\n");
+        for report in &self.reports {
+            let inventory = &report.final_inventory;
+            csv += &format!("{:?},{:?},{},{},{},{},{},{},{}\n", report.player, report.goal_suit, inventory.spades, inventory.clubs, inventory.diamonds, inventory.hearts, report.num_trades, report.realized_pnl, report.goal_suit_held);
+        }
+        csv
+    }
+
+    pub fn write_csv(&self, file_path: &str) -> io::Result<()> {
+        std::fs::write(file_path, self.to_csv())
+    }
+}
@@ -0,0 +1,91 @@
+use super::{Card, Inventory, PlayerName, hand_size};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+
+pub const POT_SIZE: usize = 200; // split evenly into the antes, whatever the table size
+
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Pot {
+    pub ante: usize,
+    pub pot_total: usize,
+}
+
+impl Pot {
+    pub fn new(players: usize) -> Self {
+        Self {
+            ante: POT_SIZE / players.max(1),
+            pot_total: 0,
+        }
+    }
+
+    pub fn collect(&mut self, points: &mut usize) -> bool { // false if the player can't cover the ante
+        if *points < self.ante {
+            return false;
+        }
+        *points -= self.ante;
+        self.pot_total += self.ante;
+        true
+    }
+
+    // what a card has to sell for, on average, for a player who sells its whole hand to win its ante back
+    // the ante and the hand shrink together as the table grows, so it comes out at 5 for both 4 and 5 players
+    pub fn break_even_price(num_players: usize) -> f32 {
+        Self::new(num_players).ante as f32 / hand_size(num_players) as f32
+    }
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundResult {
+    pub goal_suit: Card,
+    pub pot: usize,
+    pub inventories: HashMap<PlayerName, Inventory>, // final hands, needed to figure out who holds the majority
+}
+
+impl RoundResult {
+    pub fn get_ante(&self) -> usize {
+        if self.inventories.is_empty() {
+            return 0;
+        }
+        self.pot / self.inventories.len()
+    }
+
+    pub fn get_majority(&self) -> (usize, usize) { // most goal cards held, number of players holding that many
+        let most = self.inventories.values().map(|inventory| inventory.get(&self.goal_suit)).max().unwrap_or(0);
+        let holders = self.inventories.values().filter(|inventory| inventory.get(&self.goal_suit) == most).count();
+        (most, holders)
+    }
+
+    pub fn get_remaining_pot(&self) -> usize { // what's left after the $10 per goal card has been paid out
+        let goal_cards: usize = self.inventories.values().map(|inventory| inventory.get(&self.goal_suit)).sum();
+        self.pot.saturating_sub(goal_cards * 10)
+    }
+
+    pub fn get_payout(&self, inventory: &Inventory) -> usize {
+        let goal_cards = inventory.get(&self.goal_suit);
+        let (most, holders) = self.get_majority();
+
+        let mut payout = goal_cards * 10;
+        if goal_cards == most && holders > 0 {
+            payout += self.get_remaining_pot() / holders; // ties split the remainder evenly
+        }
+        payout
+    }
+
+    pub fn get_dust(&self) -> usize { // the odd points an uneven tie can't split, nobody is paid these
+        let (_, holders) = self.get_majority();
+        if holders == 0 {
+            return self.get_remaining_pot();
+        }
+        self.get_remaining_pot() % holders
+    }
+}
+
+
+// net P&L of the round for the holder of `inventory`, after paying the ante (trading P&L not included)
+// takes the whole RoundResult rather than just the goal suit and pot, whether this hand has the majority depends on every other hand
+pub fn settle_round(inventory: &Inventory, result: &RoundResult) -> i64 {
+    result.get_payout(inventory) as i64 - result.get_ante() as i64
+}
@@ -0,0 +1,84 @@
+use super::{Event, Order, LogLevel, log};
+use tokio::sync::broadcast::Sender;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{Duration, Instant};
+use kanal::AsyncSender;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use std::sync::Arc;
+
+
+// sits between one player and the exchange, holding its orders and events back by `latency` plus up to `jitter`
+// - each direction keeps its own clock, nothing is reordered: a message never goes out before the one ahead of it
+// - orders keep whatever context the player stamped on them, so the exchange can see how stale the decision was by the time it landed
+pub struct Latency {
+    pub latency: Duration,
+    pub jitter: Duration,
+    pub seed: Option<u64>,
+}
+
+impl Latency {
+    pub fn new(latency: Duration, jitter: Duration) -> Self {
+        Self { latency, jitter, seed: None }
+    }
+
+    fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    fn sample(latency: Duration, jitter: Duration, rng: &mut StdRng) -> Duration {
+        if jitter.is_zero() {
+            return latency;
+        }
+        latency + Duration::from_micros(rng.gen_range(0..=jitter.as_micros() as u64))
+    }
+
+    // hand the player the returned sender in place of the exchange's
+    pub fn delay_orders(&self, order_sender: Arc<AsyncSender<Order>>) -> Arc<AsyncSender<Order>> {
+        let (tx, rx) = kanal::unbounded_async::<Order>();
+        let (latency, jitter, mut rng) = (self.latency, self.jitter, self.rng());
+
+        tokio::task::spawn(async move {
+            let mut release = Instant::now();
+            while let Ok(order) = rx.recv().await {
+                release = release.max(Instant::now() + Self::sample(latency, jitter, &mut rng));
+                tokio::time::sleep_until(release).await;
+                if order_sender.send(order).await.is_err() {
+                    break; // exchange is gone, dropping rx tells the player
+                }
+            }
+        });
+
+        Arc::new(tx)
+    }
+
+    // hand the player the returned sender in place of the exchange's, it subscribes to it as usual
+    pub fn delay_events(&self, event_sender: &Sender<Event>) -> Sender<Event> {
+        let mut source = event_sender.subscribe(); // subscribed now so nothing sent before the player listens is lost on our side
+        let (delayed, _) = tokio::sync::broadcast::channel::<Event>(100);
+        let forward = delayed.clone();
+        let (latency, jitter, mut rng) = (self.latency, self.jitter, self.rng());
+
+        tokio::task::spawn(async move {
+            let mut release = Instant::now();
+            loop {
+                let event = match source.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        log!(LogLevel::Warn, "[!] Latency |:| Fell behind, skipped {} events", skipped);
+                        continue;
+                    },
+                    Err(RecvError::Closed) => break, // dropping `forward` closes the player's side too
+                };
+                release = release.max(Instant::now() + Self::sample(latency, jitter, &mut rng));
+                tokio::time::sleep_until(release).await;
+                let _ = forward.send(event); // no receivers yet just means the player hasn't subscribed
+            }
+        });
+
+        delayed
+    }
+}
@@ -0,0 +1,7 @@
+use super::{Event, Order, PlayerName, CL, LogLevel, log};
+
+pub mod ws;
+pub use ws::WsGateway;
+
+pub mod latency;
+pub use latency::Latency;
@@ -0,0 +1,174 @@
+use super::{Event, Order, PlayerName, CL, LogLevel, log};
+use tokio::sync::broadcast::Sender;
+use kanal::{AsyncReceiver, AsyncSender};
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::Message;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+
+const INITIAL_BACKOFF_MS: u64 = 500;
+const MAX_BACKOFF_MS: u64 = 30_000;
+const DEFAULT_MAX_RETRIES: u32 = 8;
+
+
+// what goes up the socket: orders as they are, and resync requests for a player, answered with an Event::Snapshot
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Outbound<'a> {
+    Order(&'a Order),
+    Snapshot { snapshot: &'a PlayerName },
+}
+
+
+// bridges a remote exchange into the same channels the in-process MatchMaker uses,
+// players take `event_sender` and the senders handed out below exactly like they do locally
+pub struct WsGateway {
+    pub url: String,
+    pub max_retries: u32,
+    pub event_sender: Sender<Event>,
+    pub order_sender: Option<Arc<AsyncSender<Order>>>, // take() it and clone it out to the players, if we kept one the channel could never close
+    pub snapshot_sender: Option<Arc<AsyncSender<PlayerName>>>, // same, for their resync requests
+    order_receiver: AsyncReceiver<Order>,
+    snapshot_receiver: AsyncReceiver<PlayerName>,
+}
+
+impl WsGateway {
+    pub fn new(url: &str) -> Self {
+        let (event_sender, _) = tokio::sync::broadcast::channel::<Event>(100);
+        let (order_sender, order_receiver) = kanal::unbounded_async::<Order>();
+        let (snapshot_sender, snapshot_receiver) = kanal::unbounded_async::<PlayerName>();
+
+        Self {
+            url: url.to_string(),
+            max_retries: DEFAULT_MAX_RETRIES,
+            event_sender,
+            order_sender: Some(Arc::new(order_sender)),
+            snapshot_sender: Some(Arc::new(snapshot_sender)),
+            order_receiver,
+            snapshot_receiver,
+        }
+    }
+
+    // keeps a connection to the server alive, reconnecting with exponential backoff, until every player has dropped its order sender
+    // once the retries run out the order channel is closed so players see a send error instead of the process panicking
+    pub async fn run(&self) {
+        let mut retries = 0;
+        let mut backoff_ms = INITIAL_BACKOFF_MS;
+        let mut players: HashSet<PlayerName> = HashSet::new(); // everyone we've carried an order or a request for
+        let mut connected_before = false;
+
+        loop {
+            match tokio_tungstenite::connect_async(self.url.as_str()).await {
+                Ok((stream, _)) => {
+                    log!(LogLevel::Info, CL::Green, "[+] WsGateway |:| Connected to {}", self.url);
+                    retries = 0;
+                    backoff_ms = INITIAL_BACKOFF_MS;
+
+                    // whatever the server sent while we were away is gone, so everyone we know of gets resynced
+                    let resync = if connected_before { players.iter().cloned().collect() } else { Vec::new() };
+                    connected_before = true;
+                    self.pump(stream, &mut players, resync).await;
+                    log!(LogLevel::Warn, "[!] WsGateway |:| Connection to {} dropped", self.url);
+                },
+                Err(e) => {
+                    log!(LogLevel::Error, "[!] WsGateway |:| Error connecting to {}: {:?}", self.url, e);
+                }
+            }
+
+            if self.order_receiver.is_disconnected() {
+                log!(LogLevel::Info, "[+] WsGateway |:| Every player has gone, shutting down");
+                break;
+            }
+
+            retries += 1;
+            if retries > self.max_retries {
+                log!(LogLevel::Error, "[!] WsGateway |:| Giving up after {} retries, shutting down", self.max_retries);
+                break;
+            }
+
+            tokio::time::sleep(tokio::time::Duration::from_millis(backoff_ms)).await;
+            backoff_ms = (backoff_ms * 2).min(MAX_BACKOFF_MS);
+        }
+
+        let _ = self.order_receiver.close(); // already closed is fine
+        let _ = self.snapshot_receiver.close();
+    }
+
+    // forwards server events to the players and player orders to the server until either side goes away
+    async fn pump<S>(&self, stream: tokio_tungstenite::WebSocketStream<S>, players: &mut HashSet<PlayerName>, resync: Vec<PlayerName>)
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        let (mut write, mut read) = stream.split();
+
+        for player_name in &resync {
+            if !Self::send(&mut write, &Outbound::Snapshot { snapshot: player_name }).await {
+                return;
+            }
+        }
+
+        loop {
+            tokio::select! {
+                message = read.next() => {
+                    let text = match message {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Close(_))) | None => return,
+                        Some(Ok(_)) => continue, // pings are answered by tungstenite, binary frames aren't part of the protocol
+                        Some(Err(e)) => {
+                            log!(LogLevel::Error, "[!] WsGateway |:| Error reading from socket: {:?}", e);
+                            return;
+                        }
+                    };
+
+                    match serde_json::from_str::<Event>(&text) {
+                        Ok(event) => {
+                            if let Err(e) = self.event_sender.send(event) {
+                                log!(LogLevel::Warn, "[!] WsGateway |:| No players listening: {:?}", e);
+                            }
+                        },
+                        Err(e) => log!(LogLevel::Error, "[!] WsGateway |:| Could not parse event: {:?}", e),
+                    }
+                },
+                order = self.order_receiver.recv() => {
+                    let Ok(order) = order else {
+                        return; // every player has gone away
+                    };
+                    players.insert(order.player_name.clone());
+                    if !Self::send(&mut write, &Outbound::Order(&order)).await {
+                        return;
+                    }
+                },
+                request = self.snapshot_receiver.recv(), if !self.snapshot_receiver.is_disconnected() => {
+                    if let Ok(player_name) = request {
+                        players.insert(player_name.clone());
+                        if !Self::send(&mut write, &Outbound::Snapshot { snapshot: &player_name }).await {
+                            return;
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    // false once the socket is unusable, a message that won't serialize is only logged
+    async fn send<W>(write: &mut W, message: &Outbound<'_>) -> bool
+    where
+        W: futures_util::Sink<Message> + Unpin,
+        W::Error: std::fmt::Debug,
+    {
+        let text = match serde_json::to_string(message) {
+            Ok(text) => text,
+            Err(e) => {
+                log!(LogLevel::Error, "[!] WsGateway |:| Could not serialize message: {:?}", e);
+                return true;
+            }
+        };
+        if let Err(e) = write.send(Message::Text(text)).await {
+            log!(LogLevel::Error, "[!] WsGateway |:| Error sending to socket: {:?}", e);
+            return false;
+        }
+        true
+    }
+}
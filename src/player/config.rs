@@ -0,0 +1,203 @@
+use super::{Card, Color, SuitLadders};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+
+
+// every tunable number the strategies use, the defaults are what they were originally tuned with
+// any section or field left out of the toml falls back to its default
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyConfig {
+    pub seller: SellerConfig,
+    pub spread: SpreadConfig,
+    pub pick_off: PickOffConfig,
+    pub fade: FadeConfig,
+    pub noisy: NoisyConfig,
+    pub pro: ProConfig,
+    pub hedge: HedgeConfig,
+    pub risk: RiskConfig,
+    pub obfuscation: ObfuscationConfig,
+    pub ladder: SuitLadders, // also handed to the exchange, which holds every order to it
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SellerConfig {
+    pub reservation_open: usize, // lowest bid we'll dump the whole stack into at the start of the round
+    pub reservation_close: usize, // ... and at the bell, the reservation price slides linearly between the two
+    pub panic_seconds: u64, // with this many seconds left any bid will do, except in the suit we think is the goal, 0 never panics
+    pub limit: [usize; 4], // per quarter of the round: where we rest a single card
+    pub accumulate_goal: bool, // buy the suit the estimator likes instead of selling it, everything else is still sold
+    pub accumulate_confidence: f32, // ... but only once the estimator is at least this sure, below it we stick to selling
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpreadConfig {
+    pub requote_ticks: usize, // pull our quotes once the last trade has moved more than this
+    pub ask_offset: usize, // ticks above the reference price we offer
+    pub bid_offset: usize, // ticks below the reference price we bid
+    pub open_limit: usize, // with nothing to price off, we only improve asks above / bids below this
+    pub default_fair: usize, // what we quote around before the suit's first print, 0 prices off the book instead
+    pub buy_cutoff_seconds: u64, // stop bidding with this many seconds left, the widening below usually makes this unnecessary
+    pub skew_sensitivity: f32, // ticks both quotes move per card held above (or below) the average suit
+    pub toxicity_steepness: f32, // how sharply the spread widens into the close, 3 roughly doubles it over the final minute
+    pub join_inside: bool, // quote a tick inside everyone else's best bid and ask instead of around the reference price
+    pub min_spread: usize, // when joining inside, the tightest we'll make the market, any tighter and we don't quote
+    pub max_spread: usize, // the widest market we'll post around a reference, both quotes come in evenly past it, 0 leaves them be
+    pub target_inventory: [usize; 4], // per suit (spades, clubs, diamonds, hearts), what we'd like to be holding, 0 is a flat book
+    pub target_sensitivity: f32, // ticks both quotes move per card held above (or below) the target, on top of the average skew
+    pub improve_bid: bool, // lift our bid to a tick over everyone else's best, never past fair value, and drop it when even matching would be
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PickOffConfig {
+    pub prices: [(usize, usize); 5], // (open, close) for the last 1/12 of the round, then 1/6, 1/4, 1/2 and the rest
+    pub edge: f32, // how far under (over) fair value a card has to be before we buy (sell) it
+    pub max_orders_per_sec: f32, // anything past this is dropped, not queued, the next update will have a fresher idea anyway
+    pub cooldown_ms: u64, // after one of our fills in a suit, nothing else goes out in it for this long, 0 never waits
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProConfig {
+    pub accumulate_threshold: f32, // a suit the estimator puts at least this likely to be the goal is bought up, the rest are made markets in
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FadeConfig {
+    pub edge: f32, // how far past fair value a quote has to be before we call it noise and take it
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NoisyConfig {
+    pub mode: NoisyMode, // how the one suit Noisy trades each tick is picked
+}
+
+// pure random picks leave some suits quiet for a whole round by chance, the other two spread the flow out evenly
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NoisyMode {
+    #[default]
+    Random, // any suit, uniformly
+    RoundRobin, // spades, clubs, diamonds, hearts, then around again
+    InventoryWeighted, // by cards held plus one, so what we've piled up gets sold off and empty suits still see bids
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HedgeConfig {
+    pub colors: [[Card; 2]; 2], // the two same-color suit pairs, in standard Figgie black is spades/clubs and red is diamonds/hearts
+    pub band: usize, // how many more cards one color may hold than the other before we trade it back
+}
+
+// noise on the ticking players' size and cadence, so an opponent modelling us can't just learn our clock
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObfuscationConfig {
+    pub size_jitter: usize, // each buy or sell goes out up to this many cards bigger or smaller (never under 1), 0 sends what the strategy asked for
+    pub tick_jitter_ms: u64, // each sleep between ticks moves up to this much either way, on top of the lower..higher draw
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskConfig {
+    pub max_drawdown: usize, // paper loss on a suit's bought cards that sends every strategy into dumping it at the bid, 0 turns this off
+    pub edge_threshold: f32, // orders priced off a fair value need to beat it by this much to go out, 0 sends them regardless
+}
+
+impl Default for SellerConfig {
+    fn default() -> Self {
+        Self {
+            reservation_open: 6,
+            reservation_close: 3,
+            panic_seconds: 0,
+            limit: [8, 6, 6, 4],
+            accumulate_goal: false,
+            accumulate_confidence: 0.6,
+        }
+    }
+}
+
+impl Default for SpreadConfig {
+    fn default() -> Self {
+        Self {
+            requote_ticks: 2,
+            ask_offset: 2,
+            bid_offset: 1,
+            open_limit: 7,
+            default_fair: 5,
+            buy_cutoff_seconds: 0,
+            skew_sensitivity: 0.5,
+            toxicity_steepness: 3.0,
+            join_inside: false,
+            min_spread: 2,
+            max_spread: 0,
+            target_inventory: [0; 4],
+            target_sensitivity: 0.25,
+            improve_bid: false,
+        }
+    }
+}
+
+impl Default for PickOffConfig {
+    fn default() -> Self {
+        Self {
+            prices: [(0, 0), (2, 3), (3, 4), (4, 6), (5, 8)],
+            edge: 1.0,
+            max_orders_per_sec: 10.0,
+            cooldown_ms: 0,
+        }
+    }
+}
+
+impl Default for ProConfig {
+    fn default() -> Self {
+        Self { accumulate_threshold: 0.5 }
+    }
+}
+
+impl Default for FadeConfig {
+    fn default() -> Self {
+        Self { edge: 3.0 }
+    }
+}
+
+impl Default for HedgeConfig {
+    fn default() -> Self {
+        Self {
+            colors: [Color::Black.suits(), Color::Red.suits()],
+            band: 2,
+        }
+    }
+}
+
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "couldn't read config: {}", e),
+            ConfigError::Parse(e) => write!(f, "couldn't parse config: {}", e),
+        }
+    }
+}
+
+impl StrategyConfig {
+    pub fn load(file_path: &str) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(file_path).map_err(ConfigError::Io)?;
+        Self::from_toml(&contents)
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self, ConfigError> {
+        toml::from_str(contents).map_err(ConfigError::Parse)
+    }
+}
@@ -0,0 +1,66 @@
+use super::{Book, Card, Inventory};
+
+
+// everything a strategy needs to decide what to quote in one suit, gathered up front so the decision itself
+// can run without locks or channels (the `decide_*` functions), and be fed straight from a backtest
+#[derive(Debug, Clone)]
+pub struct MarketContext {
+    pub seconds_left: u64,
+    pub card: Card,
+    pub book: Book,
+    pub inventory: usize, // held in `card`
+    pub average_inventory: usize, // across all four suits
+    pub goal_premium: usize, // ticks to lean up by if this looks like the goal suit
+    pub fair_value: Option<f32>,
+    pub goal_probability: Option<f32>, // the estimator's odds that `card` is the goal suit
+    pub quoted_at: Option<usize>, // the last trade we priced this suit off, the previous time round
+}
+
+impl MarketContext {
+    pub fn new(seconds_left: u64, card: Card, book: Book, inventory: usize) -> Self {
+        Self {
+            seconds_left,
+            card,
+            book,
+            inventory,
+            average_inventory: inventory,
+            goal_premium: 0,
+            fair_value: None,
+            goal_probability: None,
+            quoted_at: None,
+        }
+    }
+}
+
+
+// the books and our hand as of the last event, replaced together so a reader never sees one update's books with another's inventory
+#[derive(Debug, Clone)]
+pub struct MarketState {
+    pub spades: Book,
+    pub clubs: Book,
+    pub diamonds: Book,
+    pub hearts: Book,
+    pub inventory: Inventory,
+}
+
+impl Default for MarketState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarketState {
+    pub fn new() -> Self {
+        Self {
+            spades: Book::new(),
+            clubs: Book::new(),
+            diamonds: Book::new(),
+            hearts: Book::new(),
+            inventory: Inventory::new(),
+        }
+    }
+
+    pub fn books(&self) -> [Book; 4] { // ordered spades, clubs, diamonds, hearts
+        [self.spades.clone(), self.clubs.clone(), self.diamonds.clone(), self.hearts.clone()]
+    }
+}
@@ -1,181 +1,573 @@
-use super::{Card, Direction, Book, Trade, Inventory, Order, Event, CL, PlayerName};
+use super::{DEFAULT_ROUND_SECONDS, DEFAULT_STALE_AFTER, DEFAULT_ACK_TIMEOUT, DEFAULT_MAX_PRICE, PositionLimits, StrategyConfig, MarketContext, MarketState, TradeHistory, Price, Card, Direction, TimeInForce, OrderContext, OutstandingOrders, PendingAcks, Book, Trade, Inventory, Order, Event, Pnl, SuitPriors, GoalSuitEstimator, DealContext, RoundReport, FairValue, TradeSink, Metrics, RateLimiter, Clock, SystemClock, CL, LogLevel, log, render_market, PlayerName, Strategy, PlayerError, settle_round};
 use kanal::AsyncSender;
 use tokio::sync::broadcast::Sender;
+use tokio::sync::broadcast::error::RecvError;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use std::collections::{HashMap, HashSet};
+
+const LIKELY_GOAL: f32 = 0.5; // past this, pick_off treats a suit as the goal suit
+
 
 pub struct EventDrivenPlayer {
     pub name: PlayerName,
-    pub timer: Instant,
-    pub verbose: bool,
+    pub strategy: Strategy,
+    pub timer: Instant, // when this round was dealt, by `clock`
+    pub clock: Arc<dyn Clock>, // swap in a ManualClock before start() to move the round along by hand
+    pub round_seconds: u64,
+    pub config: StrategyConfig,
     pub inventory: Inventory,
-    pub trades: Vec<Trade>,
-    pub event_receiver: Sender<Event>,
+    pub deal: DealContext, // our hand as dealt and what it says about the rest of the deck
+    pub estimator: GoalSuitEstimator,
+    pub books: [Book; 4], // spades, clubs, diamonds, hearts as of the last event, what a Diff gets merged onto
+    pub trades: TradeHistory, // this round's prints, most recent last
+    pub pnl: Pnl,
+    pub starting_cash: i64, // what we sat down at the table with
+    pub cash: i64, // starting_cash less antes, plus trading and payouts as rounds settle, trued up by every AccountUpdate
+    pub trade_logger: Option<Arc<dyn TradeSink>>, // every observed trade and our round reports go here when set, e.g. a TradeLogger for csv
+    pub metrics: Arc<Metrics>,
+    pub paper: bool, // log orders instead of sending them, and fill them against ourselves
+    pub allow_short: bool, // when false, sells need inventory behind them
+    pub layering: bool, // rest several quotes a side at different prices, otherwise each new one replaces the last
+    pub max_holding: Option<usize>, // cap on how many of a single suit we'll buy up to
+    pub position_limits: PositionLimits,
+    pub max_price: usize, // quotes above this get clamped down to it
+    pub rate_limiter: RateLimiter, // one update can fan out into an order per suit, and each of those into more updates
+    pub last_sent: HashMap<Card, Order>, // most recent order per suit, so a rejection can be pinned to it
+    pub rejected: HashMap<Card, Order>, // orders the exchange bounced, not resent until that suit trades
+    pub last_fill: HashMap<Card, Instant>, // when we last traded each suit, nothing goes out in it again until pick_off.cooldown_ms has passed
+    pub flattening: HashSet<Card>, // past the stop-loss, every update sells these into the bid until they're gone
+    pub event_receiver: Option<Sender<Event>>, // taken when we subscribe
     pub order_sender: Arc<AsyncSender<Order>>,
+    pub snapshot_sender: Option<Arc<AsyncSender<PlayerName>>>, // asks the exchange for a resync when we fall behind
+    pub report_sender: Option<Arc<AsyncSender<RoundReport>>>, // a RoundReport goes here after every EndRound when set
+    pub outstanding: OutstandingOrders, // sent and possibly resting, our snapshot of the book lags these by a round trip
+    pub acks: PendingAcks, // sent and not yet acked by the exchange
+    pub ack_timeout: Option<Duration>, // unacked this long and an order is given up on, checked every heartbeat, None waits forever
+    pub stale_after: Option<Duration>, // this long without an event, heartbeats included, and the feed is flagged stale, None never does
+    pub stale: Arc<AtomicBool>, // nothing acts while it's silent anyway, this is for whoever is watching us
     pub trading: Arc<AtomicBool>,
+    pub shutdown: Arc<AtomicBool>, // set to stop the event loop after the current event
 }
 
 impl EventDrivenPlayer {
     pub fn new(
         player_name: PlayerName,
-        verbose: bool,
+        strategy: Strategy,
+        config: StrategyConfig,
         event_receiver: Sender<Event>,
         order_sender: Arc<AsyncSender<Order>>,
     ) -> Self {
+        let position_limits = PositionLimits::for_strategy(&strategy);
+        let rate_limiter = RateLimiter::new(config.pick_off.max_orders_per_sec);
         Self {
             name: player_name,
-            timer: Instant::now(),
-            verbose,
+            strategy,
+            timer: SystemClock.now(),
+            clock: Arc::new(SystemClock),
+            round_seconds: DEFAULT_ROUND_SECONDS,
+            config,
             inventory: Inventory::new(),
-            trades: Vec::new(),
-            event_receiver,
+            deal: DealContext::default(),
+            trades: TradeHistory::default(),
+            estimator: GoalSuitEstimator::new(),
+            books: [Book::new(), Book::new(), Book::new(), Book::new()],
+            pnl: Pnl::new(),
+            starting_cash: 0,
+            cash: 0,
+            trade_logger: None,
+            metrics: Arc::new(Metrics::new()),
+            paper: false,
+            allow_short: false,
+            layering: false,
+            max_holding: None,
+            position_limits,
+            max_price: DEFAULT_MAX_PRICE,
+            rate_limiter,
+            last_sent: HashMap::new(),
+            rejected: HashMap::new(),
+            last_fill: HashMap::new(),
+            flattening: HashSet::new(),
+            event_receiver: Some(event_receiver),
             order_sender,
+            snapshot_sender: None,
+            report_sender: None,
+            outstanding: OutstandingOrders::new(),
+            acks: PendingAcks::new(),
+            ack_timeout: Some(DEFAULT_ACK_TIMEOUT),
+            stale_after: Some(DEFAULT_STALE_AFTER),
+            stale: Arc::new(AtomicBool::new(false)),
             trading: Arc::new(AtomicBool::new(false)),
+            shutdown: Arc::new(AtomicBool::new(false)),
         }
     }
 
 
 
     pub async fn start(&mut self) {
-        let mut event_receiver = self.event_receiver.subscribe();
+        self.cash = self.starting_cash;
+        let mut event_receiver = match self.event_receiver.take() { // don't hold a sender ourselves, or the channel could never close on us
+            Some(sender) => sender.subscribe(),
+            None => return, // already started once
+        };
+        self.request_snapshot().await; // we may be joining mid-round
 
         loop {
-            if let Ok(event) = event_receiver.recv().await {
-                match event {
-                    Event::Update(update) => {
-
-                        let trading_flag = self.trading.load(Ordering::Acquire);
-                        if !trading_flag {
-                            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                            continue;
-                        }
+            if self.shutdown.load(Ordering::Acquire) {
+                break;
+            }
+
+            let received = match self.stale_after {
+                Some(stale_after) => tokio::time::timeout(stale_after, event_receiver.recv()).await,
+                None => Ok(event_receiver.recv().await),
+            };
+            let Ok(received) = received else {
+                if self.trading.load(Ordering::Acquire) && !self.stale.swap(true, Ordering::AcqRel) {
+                    log!(LogLevel::Warn, "[!] {:?} |:| Nothing from the exchange for {:.1}s, the feed may be dead", self.name, self.stale_after.unwrap_or_default().as_secs_f32());
+                }
+                continue;
+            };
+            let event = match received {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    // inventory can be off until the next deal, the books fix themselves on the next update
+                    log!(LogLevel::Warn, "[!] {:?} |:| Fell behind, skipped {} events", self.name, skipped);
+                    self.request_snapshot().await;
+                    continue;
+                },
+                Err(RecvError::Closed) => {
+                    log!(LogLevel::Error, "[!] {:?} |:| Event sender dropped, shutting down", self.name);
+                    break;
+                }
+            };
+
+            if self.stale.swap(false, Ordering::AcqRel) {
+                log!(LogLevel::Info, "[+] {:?} |:| Heard from the exchange again", self.name);
+            }
+            let event = match event {
+                Event::Diff(diff) => Event::Update(diff.apply(&self.books)),
+                event => event,
+            };
+            match event {
+                Event::Update(update) => {
+                    self.books = [update.spades.clone(), update.clubs.clone(), update.diamonds.clone(), update.hearts.clone()];
 
-                        if let Some(trade) = update.trade { 
-                            self.trades.push(trade.clone()); // push trade for historical reasons (if we want to analyze) & update inventory
-                            if trade.buyer == self.name {
-                                self.inventory.change(trade.card, true);
-                            } else if trade.seller == self.name {
-                                self.inventory.change(trade.card, false);
-                            }
+                    let trading_flag = self.trading.load(Ordering::Acquire);
+                    if !trading_flag {
+                        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                        continue;
+                    }
+
+                    if !update.trades.is_empty() {
+                        self.outstanding.clear(); // every book was reset
+                    }
+                    for trade in update.trades {
+                        self.rejected.remove(&trade.card); // the books reset after a trade, so old rejections no longer apply
+                        self.trades.push(trade.clone()); // keep the recent prints for fair value & update inventory
+                        if trade.buyer == self.name {
+                            self.inventory.change(trade.card.clone(), true);
+                        } else if trade.seller == self.name {
+                            self.inventory.change(trade.card.clone(), false);
                         }
+                        if trade.buyer == self.name || trade.seller == self.name {
+                            self.last_fill.insert(trade.card.clone(), self.clock.now());
+                        }
+                        self.pnl.record(&trade, &self.name);
+                        self.estimator.observe_trade(&trade);
+                        self.metrics.trade_observed(trade.buyer == self.name || trade.seller == self.name);
+                        self.metrics.set_pnl(self.pnl.realized);
+                        if let Some(trade_logger) = &self.trade_logger {
+                            trade_logger.log(&trade);
+                        }
+                    }
 
-                        let seconds_left = 240 - self.timer.elapsed().as_secs();
+                    let seconds_left = self.seconds_left();
 
-                        let inventory = self.inventory;
+                    let inventory = self.inventory;
 
-                        let spades_book = update.spades;
-                        let clubs_book = update.clubs;
-                        let diamonds_book = update.diamonds;
-                        let hearts_book = update.hearts;
+                    let spades_book = update.spades;
+                    let clubs_book = update.clubs;
+                    let diamonds_book = update.diamonds;
+                    let hearts_book = update.hearts;
+                    self.estimator.observe_books(&[spades_book.clone(), clubs_book.clone(), diamonds_book.clone(), hearts_book.clone()]);
 
-                        //println!("{}{:?} | Inventory |:| Spades: {} | Clubs: {} | Diamonds: {} | Hearts: {}{}", CL::Dull.get(), self.name, inventory.spades, inventory.clubs, inventory.diamonds, inventory.hearts, CL::End.get());
+                    log!(LogLevel::Trace, "{:?} |:| Market\n{}", self.name, render_market(&MarketState { spades: spades_book.clone(), clubs: clubs_book.clone(), diamonds: diamonds_book.clone(), hearts: hearts_book.clone(), inventory }));
 
-                        // be careful with EventDriven, this can lead to a snowball of events if the # of orders leads from 1 -> many
-                        // core logic goes here (example below)
+                    // be careful with EventDriven, this can lead to a snowball of events if the # of orders leads from 1 -> many
+                    // core logic goes here (example below)
 
 
-                        
-                        
-                        match self.name {
-                            PlayerName::PickOff => {
-                                self.pick_off(seconds_left, inventory.spades, spades_book, Card::Spade).await;
-                                self.pick_off(seconds_left, inventory.clubs, clubs_book, Card::Club).await;
-                                self.pick_off(seconds_left, inventory.diamonds, diamonds_book, Card::Diamond).await;
-                                self.pick_off(seconds_left, inventory.hearts, hearts_book, Card::Heart).await;
-                            },
-                            _ => {}
-                        }
+                    
+                    
+                    if let Err(e) = self.react(seconds_left, &inventory, [spades_book, clubs_book, diamonds_book, hearts_book]).await {
+                        log!(LogLevel::Error, "[!] {:?} |:| {}, shutting down", self.name, e);
+                        break;
+                    }
 
+                }
+                Event::DealCards { inventories, pot } => {
+                    let Some(hand) = inventories.get(&self.name) else {
+                        log!(LogLevel::Error, "[!] {:?} |:| Dealt out of this round, sitting it out", self.name);
+                        self.trading.store(false, Ordering::Release);
+                        continue;
+                    };
+                    self.inventory = *hand;
+                    self.deal = DealContext::from_hand(self.inventory);
+                    self.estimator.reset(&self.deal.hand);
+                    self.pnl = Pnl::new();
+                    self.cash -= pot.ante as i64;
+                    self.rejected.clear();
+                    self.last_fill.clear();
+                    self.outstanding.clear();
+                    self.flattening.clear();
+                    
+                    log!(LogLevel::Debug, CL::DullGreen, "[+] {:?} |:| Received cards: {}", self.name, self.inventory);
+                    log!(LogLevel::Debug, CL::DullGreen, "[+] {:?} |:| Hand EV: {:.1}", self.name, self.inventory.expected_value(&SuitPriors::from_hand(&self.inventory)));
+                    
+                    self.trading.store(true, Ordering::Release);
+                    self.timer = self.clock.now();
+                },
+                Event::EndRound(result) => {
+                    self.trading.store(false, Ordering::Release);
+                    self.trades.clear(); // the TradeLogger keeps the full record, fair value only wants this round
+                    if let Some(settled) = result.inventories.get(&self.name) { // settle on what the exchange says we hold
+                        for drift in self.inventory.reconcile(settled) {
+                            log!(LogLevel::Warn, "[!] {:?} | {:?} |:| Inventory drifted, counted {} but holding {}", self.name, drift.card, drift.local, drift.expected);
+                        }
+                    }
+                    self.cash += self.pnl.realized + result.get_payout(&self.inventory) as i64;
+                    log!(LogLevel::Info, "[+] {:?} |:| Realized P&L: {} | Cash: {}", self.name, self.pnl.realized, self.cash);
+                    log!(LogLevel::Debug, CL::DullGreen, "[+] {:?} |:| Round settled: {} (goal suit: {:?}, pot: {})", self.name, settle_round(&self.inventory, &result), result.goal_suit, result.pot);
+                    let report = RoundReport::new(self.name.clone(), self.inventory, &self.pnl, &result);
+                    if let Some(trade_logger) = &self.trade_logger {
+                        trade_logger.log_round(&report);
                     }
-                    Event::DealCards(players_inventory) => {
-                        self.inventory = players_inventory.get(&self.name).unwrap().clone();
-                        
-                        if self.verbose {
-                            println!("{}[+] {:?} |:| Received cards: {:?}{}", CL::DullGreen.get(), self.name, self.inventory, CL::End.get());
+                    if let Some(report_sender) = &self.report_sender {
+                        if let Err(e) = report_sender.send(report).await {
+                            log!(LogLevel::Error, "[!] {:?} |:| Error sending round report: {:?}", self.name, e);
                         }
-                        
-                        self.trading.store(true, Ordering::Release);
-                        self.timer = Instant::now();
-                    },
-                    Event::EndRound => {
-                        self.trading.store(false, Ordering::Release);
+                    }
+                },
+                Event::OrderRejected { player_name, card, reason } => {
+                    if player_name != self.name {
+                        continue;
+                    }
+                    log!(LogLevel::Debug, CL::Orange, "[!] {:?} | {:?} |:| Order rejected: {:?}", self.name, card, reason);
+                    // remember it so the next update doesn't fire the exact same order straight back
+                    if let Some(order) = self.last_sent.remove(&card) {
+                        self.outstanding.forget(&order);
+                        self.rejected.insert(card, order);
+                    }
+                },
+                Event::Snapshot { player_name, spades, clubs, diamonds, hearts, inventory } => {
+                    if player_name != self.name {
+                        continue;
+                    }
+                    // a Lagged recv can swallow a diff or the trade that moved our inventory, the snapshot puts both right
+                    self.books = [spades, clubs, diamonds, hearts];
+                    for drift in self.inventory.reconcile(&inventory) {
+                        log!(LogLevel::Warn, "[!] {:?} | {:?} |:| Inventory drifted, counted {} but holding {}", self.name, drift.card, drift.local, drift.expected);
+                    }
+                }
+                Event::AccountUpdate { player_name, cash } => {
+                    if player_name == self.name {
+                        self.cash = cash as i64; // the exchange's count wins over ours
                     }
                 }
-            } else {
-                println!("{}[!] {:?} |:| Event receiver dropped{}", CL::Red.get(), self.name, CL::End.get());
+                Event::Diff(_) => {} // already merged into an Update above
+                Event::OrderAck { player_name, seq, accepted } => {
+                    if player_name != self.name {
+                        continue;
+                    }
+                    if let Some(order) = self.acks.resolve(seq).filter(|_| !accepted) {
+                        log!(LogLevel::Debug, CL::Orange, "[!] {:?} | {:?} |:| Order #{} not accepted", self.name, order.card, seq);
+                    }
+                },
+                Event::Heartbeat { .. } => self.expire_acks(), // resetting the recv timeout was the other point
             }
         }
     }
 
 
 
-    pub async fn send_order(&self, price: usize, direction: Direction, card: &Card, book: &Book) {
+    // the strategy's response to one update, an error means orders can no longer get out
+    async fn react(&mut self, seconds_left: u64, inventory: &Inventory, books: [Book; 4]) -> Result<(), PlayerError> {
+        if self.flatten(inventory, books.clone()).await? {
+            return Ok(()); // the stop-loss trumps the strategy
+        }
 
-        let mut trade = false;
+        let posterior = self.estimator.posterior();
+        let [spades_book, clubs_book, diamonds_book, hearts_book] = books;
+        match self.strategy {
+            Strategy::PickOff => {
+                self.pick_off(seconds_left, inventory.spades, spades_book, Card::Spade, posterior[0]).await?;
+                self.pick_off(seconds_left, inventory.clubs, clubs_book, Card::Club, posterior[1]).await?;
+                self.pick_off(seconds_left, inventory.diamonds, diamonds_book, Card::Diamond, posterior[2]).await?;
+                self.pick_off(seconds_left, inventory.hearts, hearts_book, Card::Heart, posterior[3]).await?;
+            },
+            Strategy::Fade => {
+                self.fade(seconds_left, inventory.spades, spades_book, Card::Spade, posterior[0]).await?;
+                self.fade(seconds_left, inventory.clubs, clubs_book, Card::Club, posterior[1]).await?;
+                self.fade(seconds_left, inventory.diamonds, diamonds_book, Card::Diamond, posterior[2]).await?;
+                self.fade(seconds_left, inventory.hearts, hearts_book, Card::Heart, posterior[3]).await?;
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub async fn flatten(&mut self, inventory: &Inventory, books: [Book; 4]) -> Result<bool, PlayerError> {
+        // selling into the bid realizes the loss we're running from, that's accepted once the threshold is crossed
+        for card in self.pnl.drawdowns(&books, self.config.risk.max_drawdown) {
+            if self.flattening.insert(card.clone()) {
+                log!(LogLevel::Warn, "[!] {:?} | {:?} |:| Drawdown past {}, flattening", self.name, card, self.config.risk.max_drawdown);
+            }
+        }
+        self.flattening.retain(|card| inventory.get(card) > 0);
+
+        let suits: Vec<Card> = self.flattening.iter().cloned().collect();
+        for card in &suits {
+            let book = &books[card.get_index()];
+            if let Some(bid) = book.bid() {
+                self.send_order(bid.price, Direction::Sell, card, book, inventory.get(card), TimeInForce::IOC).await?;
+            }
+        }
+        Ok(!suits.is_empty())
+    }
+
+    pub fn seconds_left(&self) -> u64 {
+        self.round_seconds.saturating_sub(self.clock.since(self.timer).as_secs()) // an overrun reads as zero rather than underflowing
+    }
+
+    pub async fn request_snapshot(&self) {
+        if let Some(snapshot_sender) = &self.snapshot_sender {
+            if let Err(e) = snapshot_sender.send(self.name.clone()).await {
+                log!(LogLevel::Error, "[!] {:?} |:| Error requesting snapshot: {:?}", self.name, e);
+            }
+        }
+    }
+
+    pub async fn send_order(&mut self, price: usize, direction: Direction, card: &Card, book: &Book, mut size: usize, tif: TimeInForce) -> Result<(), PlayerError> {
+        let price = Price::new(price).clamp_to(self.max_price).value();
+        let held = self.inventory.get(card);
         match direction {
-            Direction::Buy => {
-                if book.bid.price < price && book.bid.player_name != self.name {
-                    trade = true;
-                }
+            Direction::Sell if held == 0 && (!self.allow_short || self.position_limits.max_short == 0) => {
+                log!(LogLevel::Warn, "[!] {:?} | {:?} |:| Rejected sell, nothing to sell", self.name, card);
+                return Ok(());
             },
-            Direction::Sell => {
-                if book.ask.price > price && book.ask.player_name != self.name {
-                    trade = true;
+            Direction::Buy => if let Some(max_holding) = self.max_holding {
+                size = size.min(max_holding.saturating_sub(held));
+                if size == 0 {
+                    log!(LogLevel::Warn, "[!] {:?} | {:?} |:| Rejected buy, already holding {}", self.name, card, held);
+                    return Ok(());
                 }
-            }
+            },
+            _ => {}
         }
-        
-        if trade {
-            let order = Order {
+
+        let actionable = if self.layering { book.is_layerable(&self.name, price, &direction) } else { book.is_actionable(&self.name, price, &direction) };
+        if actionable {
+            let mut order = Order {
                 player_name: self.name.clone(),
                 price,
                 direction,
                 card: card.clone(),
+                size,
+                tif,
+                context: Some(OrderContext::from_book(book)),
+                layer: self.layering,
+                paired: None,
+                seq: 0, // stamped on the way out
             };
     
-            if self.verbose {
-                println!("{:?} |:| Sending order: {:?}", self.name, order);
+            if let Some(rejected) = self.rejected.get(card) {
+                if rejected.direction == order.direction && rejected.price == order.price {
+                    return Ok(());
+                }
             }
-    
-            if let Err(e) = self.order_sender.send(order).await {
-                println!("[!] {:?} |:| Error sending order: {:?}", self.name, e);
+
+            // our own fill moves the book, and the update that carries it would otherwise set us off again straight away
+            let cooldown = Duration::from_millis(self.config.pick_off.cooldown_ms);
+            if order.direction != Direction::Cancel && self.last_fill.get(card).is_some_and(|filled| self.clock.since(*filled) < cooldown) {
+                log!(LogLevel::Debug, "{:?} | {:?} |:| Held back, filled here under {:?} ago", self.name, card, cooldown);
+                return Ok(());
             }
+
+            // cancels always go out, they only ever take risk off
+            if order.direction != Direction::Cancel && !self.rate_limiter.try_acquire() {
+                self.metrics.order_dropped();
+                log!(LogLevel::Debug, CL::Orange, "[!] {:?} | {:?} |:| Dropped, over {} orders/s", self.name, card, self.rate_limiter.max_per_sec);
+                return Ok(());
+            }
+
+            log!(LogLevel::Debug, "{:?} |:| Sending order: {:?}", self.name, order);
+
+            if self.paper {
+                log!(LogLevel::Info, CL::Purple, "[~] {:?} |:| Paper order: {:?}", self.name, order);
+                self.paper_fill(&order);
+                return Ok(());
+            }
+
+            if self.outstanding.crosses_own(&order) {
+                log!(LogLevel::Warn, "[!] {:?} | {:?} |:| Held back, would cross our own order in flight", self.name, card);
+                return Ok(());
+            }
+            self.outstanding.record(&order);
+            self.acks.stamp(&mut order, self.clock.now());
+            self.last_sent.insert(card.clone(), order.clone());
+            self.metrics.order_sent();
+            self.order_sender.send(order).await?;
+        }
+        Ok(())
+    }
+
+    // an order the exchange never answered didn't make it there, so it isn't resting either
+    fn expire_acks(&mut self) {
+        let Some(ack_timeout) = self.ack_timeout else {
+            return;
+        };
+        for order in self.acks.expire(self.clock.now(), ack_timeout) {
+            log!(LogLevel::Warn, "[!] {:?} | {:?} |:| No ack for order #{} after {:?}, presuming it lost", self.name, order.card, order.seq, ack_timeout);
+            self.outstanding.forget(&order);
+        }
+    }
+
+    fn paper_fill(&mut self, order: &Order) { // pretend the whole order traded at its price, against nobody
+        let (buyer, seller, is_buy) = match order.direction {
+            Direction::Buy => (self.name.clone(), PlayerName::None, true),
+            Direction::Sell => (PlayerName::None, self.name.clone(), false),
+            Direction::Cancel => return,
+        };
+        let size = if is_buy { order.size } else { order.size.min(self.inventory.get(&order.card)) };
+        let timestamp = self.clock.since(self.timer).as_millis() as u64; // our own round clock, close enough to the exchange's
+        for _ in 0..size {
+            let trade = Trade {
+                card: order.card.clone(),
+                price: order.price,
+                buyer: buyer.clone(),
+                seller: seller.clone(),
+                aggressor: order.direction.clone(),
+                timestamp,
+            };
+            self.inventory.change(trade.card.clone(), is_buy);
+            self.pnl.record(&trade, &self.name);
         }
-        
     }
 
     pub fn get_max_price_from_seconds(&self, seconds_left: u64) -> (usize, usize) {
-        if seconds_left < 20 {
-            (0, 0)
-        } else if seconds_left < 40 {
-            (2, 3)
-        } else if seconds_left < 60 {
-            (3, 4)
-        } else if seconds_left < 120 {
-            (4, 6)
+        // windows are fractions of the round, for the default 240s: 20s, 40s, 60s, 120s
+        let prices = &self.config.pick_off.prices;
+        if seconds_left < self.round_seconds / 12 {
+            prices[0]
+        } else if seconds_left < self.round_seconds / 6 {
+            prices[1]
+        } else if seconds_left < self.round_seconds / 4 {
+            prices[2]
+        } else if seconds_left < self.round_seconds / 2 {
+            prices[3]
         } else {
-            (5, 8)
+            prices[4]
         }
     }
 
-    pub async fn pick_off(&self, seconds_left: u64, inventory: usize, book: Book, card: Card) {
-        let (open_price, close_price) = self.get_max_price_from_seconds(seconds_left);
-        if inventory <= 2 {
-            if book.ask.price < open_price {
-                self.send_order(book.ask.price, Direction::Buy, &card, &book).await;
+    pub async fn pick_off(&mut self, seconds_left: u64, inventory: usize, book: Book, card: Card, goal_probability: f32) -> Result<(), PlayerError> {
+        let fair = FairValue::with_goal(&card, self.trades.as_slice(), FairValue::suit_count_hint(&self.deal.hand, &card), &self.estimator.posterior());
+        let context = MarketContext { fair_value: Some(fair), goal_probability: Some(goal_probability), ..MarketContext::new(seconds_left, card.clone(), book.clone(), inventory) };
+
+        for order in self.decide_pick_off(&context) {
+            let price = self.config.ladder.get(&card).round(order.price);
+            self.send_order(price, order.direction, &card, &book, order.size, order.tif).await?;
+        }
+        Ok(())
+    }
+
+    pub fn decide_pick_off(&self, context: &MarketContext) -> Vec<Order> {
+        let mut orders = Vec::new();
+        let book = &context.book;
+        if book.is_crossed() {
+            return orders; // a crossed snapshot is about to trade, nothing to pick off
+        }
+
+        // the time windows still cap what we'll pay and decide when we start dumping, fair value decides what's cheap
+        // - fair value already leans towards the goal payout by the estimator's odds (FairValue::with_goal)
+        // - a suit more likely than not to be the goal lifts the cap to that payout and is never dumped at the close, the unlikely ones get the cap cut down to it
+        let (mut open_price, close_price) = self.get_max_price_from_seconds(context.seconds_left);
+        let fair = context.fair_value.unwrap_or(open_price as f32);
+        let mut likely_goal = false;
+        if let Some(probability) = context.goal_probability {
+            let goal_value = probability * FairValue::goal_card_value();
+            open_price = if probability >= 0.25 { open_price.max(goal_value as usize) } else { open_price.min(goal_value.ceil() as usize) };
+            likely_goal = probability >= LIKELY_GOAL;
+        }
+        let edge = self.config.pick_off.edge;
+        let best_bid = book.bid().map(|bid| bid.price);
+        let best_ask = book.ask().map(|ask| ask.price);
+        let order = |price: usize, direction: Direction, tif: TimeInForce| Order { tif, ..Order::new(self.name.clone(), price, direction, context.card.clone(), 1) };
+
+        if let Some(ask) = best_ask {
+            if self.position_limits.can_buy(context.inventory) && ask < open_price && ask as f32 <= fair - edge {
+                orders.push(order(ask, Direction::Buy, TimeInForce::IOC)); // taking, never leave it resting
+            }
+        }
+
+        if context.inventory > 0 {
+            if let Some(bid) = best_bid {
+                if (bid >= close_price && !likely_goal) || bid as f32 >= fair + edge {
+                    orders.push(order(bid, Direction::Sell, TimeInForce::IOC));
+                }
+            }
+            if let Some(ask) = best_ask {
+                if ask as f32 > fair.max(5.0) && book.spread().is_none_or(|spread| spread > 1) { // no room to improve inside a one-tick spread
+                    orders.push(order(ask - 1, Direction::Sell, TimeInForce::GTC));
+                }
             }
         }
 
-        if inventory > 0 {
-            if book.bid.price >= close_price {
-                self.send_order(book.bid.price, Direction::Sell, &card, &book).await;
+        orders
+    }
+
+    pub async fn fade(&mut self, seconds_left: u64, inventory: usize, book: Book, card: Card, goal_probability: f32) -> Result<(), PlayerError> {
+        let fair = FairValue::with_goal(&card, self.trades.as_slice(), FairValue::suit_count_hint(&self.deal.hand, &card), &self.estimator.posterior());
+        let context = MarketContext { fair_value: Some(fair), goal_probability: Some(goal_probability), ..MarketContext::new(seconds_left, card.clone(), book.clone(), inventory) };
+
+        for order in self.decide_fade(&context) {
+            self.send_order(order.price, order.direction, &card, &book, order.size, order.tif).await?;
+        }
+        Ok(())
+    }
+
+    pub fn decide_fade(&self, context: &MarketContext) -> Vec<Order> {
+        // - the counterparty to Noisy: its prices are uniform over 1..15 with no view behind them, so a quote far enough from fair value is
+        // more likely noise than information, and we take all of it that our limits allow
+        // - fair value leans towards the goal payout by the estimator's odds (FairValue::with_goal)
+        // - we never rest anything, if nobody is mispricing there's nothing for us to do
+        let mut orders = Vec::new();
+        let book = &context.book;
+        let Some(fair) = context.fair_value else {
+            return orders;
+        };
+        if book.is_crossed() {
+            return orders;
+        }
+        let edge = self.config.fade.edge;
+        let order = |price: usize, direction: Direction, size: usize| Order { tif: TimeInForce::IOC, ..Order::new(self.name.clone(), price, direction, context.card.clone(), size) };
+
+        if let Some(ask) = book.ask().filter(|ask| ask.player_name != self.name && ask.price as f32 <= fair - edge) {
+            let size = ask.size.min(self.position_limits.max_long.saturating_sub(context.inventory));
+            if size > 0 {
+                orders.push(order(ask.price, Direction::Buy, size));
             }
-            if book.ask.price > 5 {
-                self.send_order(book.ask.price - 1, Direction::Sell, &card, &book).await;
+        }
+        if let Some(bid) = book.bid().filter(|bid| bid.player_name != self.name && bid.price as f32 >= fair + edge) {
+            let size = bid.size.min(context.inventory);
+            if size > 0 {
+                orders.push(order(bid.price, Direction::Sell, size));
             }
         }
+
+        orders
     }
 
-}
\ No newline at end of file
+}
@@ -1,68 +1,164 @@
-use super::{Card, Direction, Book, Trade, Inventory, Order, Event, CL, PlayerName};
+use super::{DEFAULT_ROUND_SECONDS, DEFAULT_STALE_AFTER, DEFAULT_ACK_TIMEOUT, DEFAULT_MAX_PRICE, PositionLimits, StrategyConfig, NoisyMode, MarketContext, MarketState, TradeHistory, Price, Card, Direction, TimeInForce, OrderContext, OutstandingOrders, PendingAcks, Book, Level, Trade, Inventory, Order, Event, Pnl, GoalSuitEstimator, CompetitorModel, DealContext, RoundReport, FairValue, TradeSink, Metrics, Clock, SystemClock, CL, LogLevel, log, render_market, PlayerName, Strategy, PlayerError, settle_round, watch_listener};
 use kanal::{AsyncSender};
 use tokio::sync::broadcast::{Sender, Receiver};
+use tokio::sync::broadcast::error::RecvError;
 use std::sync::Arc;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 use rand::Rng;
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
 use std::sync::atomic::Ordering;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use std::collections::{HashMap, HashSet};
+
+
+const SUITS: [Card; 4] = [Card::Spade, Card::Club, Card::Diamond, Card::Heart];
+
+// Arb pricing
+const ARB_EDGE: f32 = 1.0;
+
+// Seller
+const HOARDING_CARDS: usize = 5; // half a 10-card goal suit, one more and the majority is theirs
 
 
 
 pub struct GenericPlayer {
     pub name: PlayerName,
-    pub verbose: bool,
-    pub timer: Arc<Mutex<Instant>>,
-    pub spades_book: Arc<Mutex<Book>>,
-    pub clubs_book: Arc<Mutex<Book>>,
-    pub diamonds_book: Arc<Mutex<Book>>,
-    pub hearts_book: Arc<Mutex<Book>>,
-    pub inventory: Arc<Mutex<Inventory>>,
-    pub trades: Arc<Mutex<Vec<Trade>>>,
-    pub lower_frequency: u64,
+    pub strategy: Strategy, // what this seat plays, several seats can share one
+    pub round_seconds: u64,
+    pub timer: Arc<Mutex<Instant>>, // when this round was dealt, by `clock`
+    pub clock: Arc<dyn Clock>, // swap in a ManualClock before start() to move the round along by hand
+    pub last_event: Arc<Mutex<Instant>>, // when the listener last heard anything, heartbeats included
+    pub stale_after: Option<Duration>, // this long without an event and we stop trading until one arrives, None never does (e.g. replaying a log without heartbeats)
+    pub stale: Arc<AtomicBool>,
+    pub market: Arc<RwLock<MarketState>>, // written once per event by the listener, the strategy loop reads a snapshot each tick
+    pub trades: Arc<Mutex<TradeHistory>>, // this round's prints, most recent last, swap in a bigger one before start() to keep more
+    pub pnl: Arc<Mutex<Pnl>>,
+    pub starting_cash: i64, // what we sat down at the table with
+    pub cash: Arc<Mutex<i64>>, // starting_cash less antes, plus trading and payouts as rounds settle, the exchange's AccountUpdate has the last word
+    pub estimator: Arc<Mutex<GoalSuitEstimator>>,
+    pub deal: Arc<Mutex<DealContext>>, // our hand as dealt, for how many of each suit could still be out there
+    pub competitors: Arc<Mutex<CompetitorModel>>, // inferred opponent hands, from the deal and every print since
+    pub trade_logger: Option<Arc<dyn TradeSink>>, // every observed trade and our round reports go here when set, e.g. a TradeLogger for csv
+    pub metrics: Arc<Metrics>,
+    pub quoted_at: Arc<Mutex<HashMap<Card, usize>>>, // last_trade our resting quotes were priced off
+    pub flattening: Arc<Mutex<HashSet<Card>>>, // suits that hit the stop-loss, dumped at the bid until we're out of them
+    pub accumulating: Arc<Mutex<HashSet<Card>>>, // suits Pro is buying up rather than making a market in
+    pub lower_frequency: u64, // ms between ticks is drawn from lower..=higher, equal bounds tick at a fixed interval
     pub higher_frequency: u64,
-    pub event_receiver: Sender<Event>,
+    pub suit_frequency: HashMap<Card, (u64, u64)>, // per-suit (lower, higher) overrides, a suit ticks on its own cadence and only trades when due, the rest follow the pair above
+    pub idle_suits: Arc<Mutex<HashSet<Card>>>, // suits with an override that aren't due this tick, nothing but cancels goes out in them
+    pub seed: Option<u64>, // fixes every random draw the strategies make, for reproducible runs
+    pub jitter: Arc<Mutex<StdRng>>, // draws the size noise, send_order has no rng of its own
+    pub config: StrategyConfig,
+    pub paper: bool, // log orders instead of sending them, and fill them against ourselves
+    pub allow_short: bool, // when false, sells need inventory behind them
+    pub layering: bool, // rest several quotes a side at different prices, otherwise each new one replaces the last
+    pub max_holding: Option<usize>, // cap on how many of a single suit we'll buy up to
+    pub position_limits: PositionLimits,
+    pub max_price: usize, // quotes above this get clamped down to it
+    pub short_window: usize, // momentum moving averages, in number of trades
+    pub long_window: usize,
+    pub revert_window: usize, // mean reversion lookback, in number of trades
+    pub revert_k: f32, // how many standard deviations from the mean before we fade a print
+    pub event_receiver: Option<Sender<Event>>, // taken when the listener subscribes
     pub order_sender: Arc<AsyncSender<Order>>,
+    pub snapshot_sender: Option<Arc<AsyncSender<PlayerName>>>, // asks the exchange for a resync when we fall behind
+    pub report_sender: Option<Arc<AsyncSender<RoundReport>>>, // a RoundReport goes here after every EndRound when set
+    pub outstanding: Arc<Mutex<OutstandingOrders>>, // what we've sent that may be resting, see the ordering notes in models/order.rs
+    pub acks: Arc<Mutex<PendingAcks>>, // sent and not yet acked by the exchange
+    pub ack_timeout: Option<Duration>, // unacked this long and an order is given up on, None waits forever
+    pub noisy_turn: Arc<AtomicUsize>, // the next suit NoisyMode::RoundRobin trades
     pub trading: Arc<AtomicBool>,
+    pub shutdown: Arc<AtomicBool>, // stops both the strategy loop and the listener, also set when the event sender goes away
 }
 
 impl GenericPlayer {
+    #[allow(clippy::too_many_arguments)] // mirrors the spawn blocks in main, a builder would just add noise
     pub fn new(
         player_name: PlayerName,
-        verbose: bool,
+        strategy: Strategy,
         lower_frequency: u64,
         higher_frequency: u64,
+        seed: Option<u64>,
+        config: StrategyConfig,
         event_receiver: Sender<Event>,
         order_sender: Arc<AsyncSender<Order>>,
     ) -> Self {
+        let position_limits = PositionLimits::for_strategy(&strategy);
+        let competitors = CompetitorModel::new(player_name.clone());
+        if lower_frequency > higher_frequency {
+            log!(LogLevel::Warn, "[!] {:?} |:| Tick bounds {}..{} are backwards, swapping them", player_name, lower_frequency, higher_frequency);
+        }
+        let (lower_frequency, higher_frequency) = (lower_frequency.min(higher_frequency), lower_frequency.max(higher_frequency));
         Self {
             name: player_name,
-            verbose,
-            timer: Arc::new(Mutex::new(std::time::Instant::now())),
-            spades_book: Arc::new(Mutex::new(Book::new())),
-            clubs_book: Arc::new(Mutex::new(Book::new())),
-            diamonds_book: Arc::new(Mutex::new(Book::new())),
-            hearts_book: Arc::new(Mutex::new(Book::new())),
-            inventory: Arc::new(Mutex::new(Inventory::new())),
-            trades: Arc::new(Mutex::new(Vec::new())),
+            strategy,
+            round_seconds: DEFAULT_ROUND_SECONDS,
+            timer: Arc::new(Mutex::new(SystemClock.now())),
+            clock: Arc::new(SystemClock),
+            last_event: Arc::new(Mutex::new(SystemClock.now())),
+            stale_after: Some(DEFAULT_STALE_AFTER),
+            stale: Arc::new(AtomicBool::new(false)),
+            market: Arc::new(RwLock::new(MarketState::new())),
+            trades: Arc::new(Mutex::new(TradeHistory::default())),
+            pnl: Arc::new(Mutex::new(Pnl::new())),
+            starting_cash: 0,
+            cash: Arc::new(Mutex::new(0)),
+            estimator: Arc::new(Mutex::new(GoalSuitEstimator::new())),
+            deal: Arc::new(Mutex::new(DealContext::default())),
+            competitors: Arc::new(Mutex::new(competitors)),
+            trade_logger: None,
+            metrics: Arc::new(Metrics::new()),
+            quoted_at: Arc::new(Mutex::new(HashMap::new())),
+            flattening: Arc::new(Mutex::new(HashSet::new())),
+            accumulating: Arc::new(Mutex::new(HashSet::new())),
             lower_frequency,
             higher_frequency,
-            event_receiver,
+            suit_frequency: HashMap::new(),
+            idle_suits: Arc::new(Mutex::new(HashSet::new())),
+            seed,
+            jitter: Arc::new(Mutex::new(seed.map_or_else(StdRng::from_entropy, |seed| StdRng::seed_from_u64(!seed)))),
+            config,
+            paper: false,
+            allow_short: false,
+            layering: false,
+            max_holding: None,
+            position_limits,
+            max_price: DEFAULT_MAX_PRICE,
+            short_window: 3,
+            long_window: 8,
+            revert_window: 10,
+            revert_k: 1.5,
+            event_receiver: Some(event_receiver),
             order_sender,
+            snapshot_sender: None,
+            report_sender: None,
+            outstanding: Arc::new(Mutex::new(OutstandingOrders::new())),
+            acks: Arc::new(Mutex::new(PendingAcks::new())),
+            ack_timeout: Some(DEFAULT_ACK_TIMEOUT),
+            noisy_turn: Arc::new(AtomicUsize::new(0)),
             trading: Arc::new(AtomicBool::new(false)),
+            shutdown: Arc::new(AtomicBool::new(false)),
         }
     }
 
 
 
     pub async fn start(&mut self) {
+        *self.cash.lock().await = self.starting_cash;
         self.listen_to_events().await;
 
-        let mut rng = StdRng::from_entropy();
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let mut next_due = [Instant::now(); 4]; // per suit, only consulted when suit_frequency has overrides
         loop {
+            if self.shutdown.load(Ordering::Acquire) {
+                break;
+            }
 
             let trading_flag = self.trading.load(Ordering::Acquire);
             if !trading_flag {
@@ -70,170 +166,620 @@ impl GenericPlayer {
                 continue;
             }
 
-            let seconds_left = 240 - self.timer.lock().await.elapsed().as_secs();
+            let silence = self.clock.since(*self.last_event.lock().await);
+            if self.stale_after.is_some_and(|stale_after| silence > stale_after) {
+                if !self.stale.swap(true, Ordering::AcqRel) {
+                    log!(LogLevel::Warn, "[!] {:?} |:| Nothing from the exchange for {:.1}s, pausing until it's heard from", self.name, silence.as_secs_f32());
+                }
+                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                continue;
+            }
 
-            let inventory = self.inventory.lock().await.clone();
+            let seconds_left = self.seconds_left().await;
 
-            let spades_book = self.spades_book.lock().await.clone();
-            let clubs_book = self.clubs_book.lock().await.clone();
-            let diamonds_book = self.diamonds_book.lock().await.clone();
-            let hearts_book = self.hearts_book.lock().await.clone();
+            let now = Instant::now();
+            let due: Vec<Card> = SUITS.iter().filter(|card| next_due[card.get_index()] <= now).cloned().collect();
+            if !self.suit_frequency.is_empty() {
+                *self.idle_suits.lock().await = SUITS.iter().filter(|card| !due.contains(card)).cloned().collect();
+            }
 
-            println!("{}{:?} | Inventory |:| Spades: {} | Clubs: {} | Diamonds: {} | Hearts: {}{}", CL::Dull.get(), self.name, inventory.spades, inventory.clubs, inventory.diamonds, inventory.hearts, CL::End.get());
+            if let Err(e) = self.tick(seconds_left, &mut rng).await {
+                log!(LogLevel::Error, "[!] {:?} |:| {}, shutting down", self.name, e);
+                self.shutdown.store(true, Ordering::Release); // takes the listener down with us
+                break;
+            }
 
-            // with the above information, we can now decide what to do
-            // core logic goes here (examples below)
+            let interval = self.tick_interval(&mut rng);
+            let interval = match self.suit_frequency.is_empty() {
+                true => interval,
+                false => self.reschedule(&mut next_due, &due, now, interval, &mut rng),
+            };
+            tokio::time::sleep(tokio::time::Duration::from_millis(interval)).await; // promote some sort of fairness, HFT route should go event-driven
+        }
+    }
 
 
-            // =-= Notes =-= //
-            // - The first possible edge comes from the difference of probabilities between the common suit and the others. For example,
-            // the probability of getting 4x of the common suit is 13%, while the probabilities of the others are either 7.4% (10 card suit) or 3.3% (8 card suit) (I think so anyway, using this calculator: https://stattrek.com/online-calculator/hypergeometric)
-            // - Another edge comes from understanding the value of the cards. Such as starting cost / value of cards given other strategies
-            // - Lastly, the flow of information throughout the game is highly important. If each trade is deliberate, it must contain some amount of information which can be used
-            // ------------- //
-            // - The role of a market maker in this system is quite interesting. If we extrapolate the values of the cards to the end of the game, we have 3/4 worth 0 and 1/4 worth 10 + possible bonus,
-            // this extrapolation leaves the market maker in an interesting position, exposed to the extremes of toxic flow. Like the real market, the experience of their competitors is highly correlated with the
-            // effectiveness of the market maker. A dumb market maker can win with noisy players, but will lose to players who are able to predict the goal suit. How to handle this is a fun problem to dive into
-            match self.name {
-                PlayerName::PrayingMantis => {
-                    self.praying_mantis_sell(seconds_left, inventory.spades, spades_book.clone(), Card::Spade).await;
-                    self.praying_mantis_sell(seconds_left, inventory.clubs, clubs_book.clone(), Card::Club).await;
-                    self.praying_mantis_sell(seconds_left, inventory.diamonds, diamonds_book.clone(), Card::Diamond).await;
-                    self.praying_mantis_sell(seconds_left, inventory.hearts, hearts_book.clone(), Card::Heart).await;
+    // one pass of the strategy over a snapshot of the market, an error means orders can no longer get out
+    async fn tick(&self, seconds_left: u64, rng: &mut StdRng) -> Result<(), PlayerError> {
+        self.expire_acks().await;
+        let market = self.market.read().await.clone();
+        log!(LogLevel::Trace, "{:?} |:| Market\n{}", self.name, render_market(&market));
+        let MarketState { spades: spades_book, clubs: clubs_book, diamonds: diamonds_book, hearts: hearts_book, inventory } = market;
 
-                    let mut cards = vec![
-                        (Card::Spade, spades_book.last_trade.unwrap_or(0)),
-                        (Card::Club, clubs_book.last_trade.unwrap_or(0)),
-                        (Card::Diamond, diamonds_book.last_trade.unwrap_or(0)),
-                        (Card::Heart, hearts_book.last_trade.unwrap_or(0)),
-                    ];
+        // with the above information, we can now decide what to do
+        // core logic goes here (examples below)
 
-                    cards.sort_by(|a, b| b.1.cmp(&a.1));
 
-                    let most_expensive_card = cards[0].0.clone();
-                    let (inventory, book) = match most_expensive_card {
-                        Card::Spade => (inventory.spades, spades_book),
-                        Card::Club => (inventory.clubs, clubs_book),
-                        Card::Diamond => (inventory.diamonds, diamonds_book),
-                        Card::Heart => (inventory.hearts, hearts_book),
-                    };
+        // =-= Notes =-= //
+        // - The first possible edge comes from the difference of probabilities between the common suit and the others. For example,
+        // the probability of getting 4x of the common suit is 13%, while the probabilities of the others are either 7.4% (10 card suit) or 3.3% (8 card suit) (I think so anyway, using this calculator: https://stattrek.com/online-calculator/hypergeometric)
+        // - Another edge comes from understanding the value of the cards. Such as starting cost / value of cards given other strategies
+        // - Lastly, the flow of information throughout the game is highly important. If each trade is deliberate, it must contain some amount of information which can be used
+        // ------------- //
+        // - The role of a market maker in this system is quite interesting. If we extrapolate the values of the cards to the end of the game, we have 3/4 worth 0 and 1/4 worth 10 + possible bonus,
+        // this extrapolation leaves the market maker in an interesting position, exposed to the extremes of toxic flow. Like the real market, the experience of their competitors is highly correlated with the
+        // effectiveness of the market maker. A dumb market maker can win with noisy players, but will lose to players who are able to predict the goal suit. How to handle this is a fun problem to dive into
 
-                    self.praying_mantis_snipe(seconds_left, inventory, book, most_expensive_card).await;
-                },
-                PlayerName::TheHoarder => {
-                    self.hoard(seconds_left, inventory.spades, spades_book, Card::Spade).await;
-                    self.hoard(seconds_left, inventory.clubs, clubs_book, Card::Club).await;
-                    self.hoard(seconds_left, inventory.diamonds, diamonds_book, Card::Diamond).await;
-                    self.hoard(seconds_left, inventory.hearts, hearts_book, Card::Heart).await;
-                },
-                PlayerName::Noisy => {
-                    self.noisy_trader(inventory, spades_book, clubs_book, diamonds_book, hearts_book, &mut rng).await;
-                },
-                PlayerName::Seller => {
-                    self.sell_inventory(seconds_left, inventory.spades, spades_book, Card::Spade).await;
-                    self.sell_inventory(seconds_left, inventory.clubs, clubs_book, Card::Club).await;
-                    self.sell_inventory(seconds_left, inventory.diamonds, diamonds_book, Card::Diamond).await;
-                    self.sell_inventory(seconds_left, inventory.hearts, hearts_book, Card::Heart).await;
-                },
-                PlayerName::Spread => {
-                    let average_inventory = (inventory.spades + inventory.clubs + inventory.diamonds + inventory.hearts) / 4;
-                    self.provide_spread(seconds_left, average_inventory, inventory.spades, spades_book, Card::Spade).await;
-                    self.provide_spread(seconds_left, average_inventory, inventory.clubs, clubs_book, Card::Club).await;
-                    self.provide_spread(seconds_left, average_inventory, inventory.diamonds, diamonds_book, Card::Diamond).await;
-                    self.provide_spread(seconds_left, average_inventory, inventory.hearts, hearts_book, Card::Heart).await;
-                },
-                _ => {}
-            }
 
-            tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(self.lower_frequency..self.higher_frequency))).await; // promote some sort of fairness, HFT route should go event-driven
+        // the stop-loss overrides whatever the strategy would have done this tick
+        if self.flatten(&inventory, [spades_book.clone(), clubs_book.clone(), diamonds_book.clone(), hearts_book.clone()]).await? {
+            return Ok(());
         }
+
+        match self.strategy {
+            Strategy::PrayingMantis => {
+                self.praying_mantis_sell(seconds_left, inventory.spades, spades_book.clone(), Card::Spade).await?;
+                self.praying_mantis_sell(seconds_left, inventory.clubs, clubs_book.clone(), Card::Club).await?;
+                self.praying_mantis_sell(seconds_left, inventory.diamonds, diamonds_book.clone(), Card::Diamond).await?;
+                self.praying_mantis_sell(seconds_left, inventory.hearts, hearts_book.clone(), Card::Heart).await?;
+
+                let mut cards = [
+                    (Card::Spade, spades_book.last_price().unwrap_or(0)),
+                    (Card::Club, clubs_book.last_price().unwrap_or(0)),
+                    (Card::Diamond, diamonds_book.last_price().unwrap_or(0)),
+                    (Card::Heart, hearts_book.last_price().unwrap_or(0)),
+                ];
+
+                cards.sort_by_key(|card| std::cmp::Reverse(card.1));
+
+                let most_expensive_card = cards[0].0.clone();
+                let (inventory, book) = match most_expensive_card {
+                    Card::Spade => (inventory.spades, spades_book),
+                    Card::Club => (inventory.clubs, clubs_book),
+                    Card::Diamond => (inventory.diamonds, diamonds_book),
+                    Card::Heart => (inventory.hearts, hearts_book),
+                };
+
+                self.praying_mantis_snipe(seconds_left, inventory, book, most_expensive_card).await?;
+            },
+            Strategy::TheHoarder => {
+                self.hoard(seconds_left, inventory.spades, spades_book, Card::Spade).await?;
+                self.hoard(seconds_left, inventory.clubs, clubs_book, Card::Club).await?;
+                self.hoard(seconds_left, inventory.diamonds, diamonds_book, Card::Diamond).await?;
+                self.hoard(seconds_left, inventory.hearts, hearts_book, Card::Heart).await?;
+            },
+            Strategy::Noisy => {
+                self.noisy_trader(inventory, spades_book, clubs_book, diamonds_book, hearts_book, rng).await?;
+            },
+            Strategy::Seller => {
+                // with accumulate_goal on, the one suit the estimator is sure enough of gets bought instead of sold
+                let (likely_goal, confidence) = self.estimator.lock().await.most_likely();
+                let seller = &self.config.seller;
+                let accumulating = seller.accumulate_goal && confidence >= seller.accumulate_confidence;
+                for (card, held, book) in [(Card::Spade, inventory.spades, spades_book), (Card::Club, inventory.clubs, clubs_book), (Card::Diamond, inventory.diamonds, diamonds_book), (Card::Heart, inventory.hearts, hearts_book)] {
+                    if accumulating && card == likely_goal {
+                        self.accumulate_goal(held, book, card).await?;
+                    } else {
+                        self.sell_inventory(seconds_left, held, book, card).await?;
+                    }
+                }
+            },
+            Strategy::Momentum => {
+                let trades = self.trades.lock().await.clone();
+                self.momentum(seconds_left, inventory.spades, spades_book, Card::Spade, trades.as_slice()).await?;
+                self.momentum(seconds_left, inventory.clubs, clubs_book, Card::Club, trades.as_slice()).await?;
+                self.momentum(seconds_left, inventory.diamonds, diamonds_book, Card::Diamond, trades.as_slice()).await?;
+                self.momentum(seconds_left, inventory.hearts, hearts_book, Card::Heart, trades.as_slice()).await?;
+            },
+            Strategy::MeanRevert => {
+                let trades = self.trades.lock().await.clone();
+                self.mean_revert(seconds_left, inventory.spades, spades_book, Card::Spade, trades.as_slice()).await?;
+                self.mean_revert(seconds_left, inventory.clubs, clubs_book, Card::Club, trades.as_slice()).await?;
+                self.mean_revert(seconds_left, inventory.diamonds, diamonds_book, Card::Diamond, trades.as_slice()).await?;
+                self.mean_revert(seconds_left, inventory.hearts, hearts_book, Card::Heart, trades.as_slice()).await?;
+            },
+            Strategy::Arb => {
+                let posterior = self.estimator.lock().await.posterior();
+                let fair = Self::implied_fair_values(&posterior, &[&spades_book, &clubs_book, &diamonds_book, &hearts_book]);
+                self.arb(seconds_left, inventory.spades, fair[0], spades_book, Card::Spade).await?;
+                self.arb(seconds_left, inventory.clubs, fair[1], clubs_book, Card::Club).await?;
+                self.arb(seconds_left, inventory.diamonds, fair[2], diamonds_book, Card::Diamond).await?;
+                self.arb(seconds_left, inventory.hearts, fair[3], hearts_book, Card::Heart).await?;
+            },
+            Strategy::Hedge => {
+                self.hedge(inventory, [spades_book, clubs_book, diamonds_book, hearts_book]).await?;
+            },
+            Strategy::Spread => {
+                let average_inventory = (inventory.spades + inventory.clubs + inventory.diamonds + inventory.hearts) / 4;
+                let (likely_goal, confidence) = self.estimator.lock().await.most_likely();
+                let goal_premium = |card: &Card| {
+                    if *card != likely_goal || confidence < 0.5 {
+                        0
+                    } else if confidence < 0.8 {
+                        1
+                    } else {
+                        2
+                    }
+                };
+                self.provide_spread(seconds_left, average_inventory, inventory.spades, goal_premium(&Card::Spade), spades_book, Card::Spade).await?;
+                self.provide_spread(seconds_left, average_inventory, inventory.clubs, goal_premium(&Card::Club), clubs_book, Card::Club).await?;
+                self.provide_spread(seconds_left, average_inventory, inventory.diamonds, goal_premium(&Card::Diamond), diamonds_book, Card::Diamond).await?;
+                self.provide_spread(seconds_left, average_inventory, inventory.hearts, goal_premium(&Card::Heart), hearts_book, Card::Heart).await?;
+            },
+            Strategy::Pro => {
+                self.pro(seconds_left, inventory, [spades_book, clubs_book, diamonds_book, hearts_book]).await?;
+            },
+            _ => {}
+        }
+        Ok(())
     }
 
 
-    pub async fn send_order(&self, price: usize, direction: Direction, card: &Card, book: &Book) {
+    pub async fn seconds_left(&self) -> u64 {
+        self.round_seconds.saturating_sub(self.clock.since(*self.timer.lock().await).as_secs()) // an overrun reads as zero rather than underflowing
+    }
+
+    pub fn tick_interval(&self, rng: &mut StdRng) -> u64 {
+        // the fields are public, so swapped bounds can still turn up after new() straightened them out
+        let (lower, higher) = (self.lower_frequency.min(self.higher_frequency), self.lower_frequency.max(self.higher_frequency));
+        let interval = rng.gen_range(lower..=higher);
+        match self.config.obfuscation.tick_jitter_ms {
+            0 => interval,
+            tick_jitter => (interval + rng.gen_range(0..=2 * tick_jitter)).saturating_sub(tick_jitter),
+        }
+    }
 
-        let mut trade = false;
+    // with suit_frequency set, each suit that just traded is due again after its own interval (the tick's for suits without one),
+    // and we sleep until the soonest of them, so the busiest suit sets the pace
+    fn reschedule(&self, next_due: &mut [Instant; 4], due: &[Card], now: Instant, interval: u64, rng: &mut StdRng) -> u64 {
+        for card in due {
+            let wait = match self.suit_frequency.get(card) {
+                Some(&(lower, higher)) => rng.gen_range(lower.min(higher)..=lower.max(higher)),
+                None => interval,
+            };
+            next_due[card.get_index()] = now + Duration::from_millis(wait);
+        }
+        let soonest = next_due.iter().min().copied().unwrap_or(now);
+        soonest.saturating_duration_since(Instant::now()).as_millis() as u64
+    }
+
+    pub async fn send_order(&self, price: usize, direction: Direction, card: &Card, book: &Book, size: usize) -> Result<(), PlayerError> {
+        let Some(order) = self.prepare_order(price, direction, card, book, size).await else {
+            return Ok(());
+        };
+        self.dispatch(order).await
+    }
+
+    // both legs go to the exchange as one message, it posts both or neither (see Exchange::process_quote),
+    // and the same goes on our side: if either leg is held back here, nothing is sent
+    pub async fn quote_two_sided(&self, bid: usize, ask: usize, card: &Card, book: &Book, size: usize) -> Result<(), PlayerError> {
+        let (Some(mut bid), Some(ask)) = (self.prepare_order(bid, Direction::Buy, card, book, size).await, self.prepare_order(ask, Direction::Sell, card, book, size).await) else {
+            log!(LogLevel::Debug, "{:?} | {:?} |:| Held back both sides, one leg can't go out", self.name, card);
+            return Ok(());
+        };
+        if bid.price >= ask.price { // the clamp can squeeze the two together
+            return Ok(());
+        }
+        bid.paired = Some(Box::new(ask));
+        self.dispatch(bid).await
+    }
+
+    // the checks every order goes through before it's worth sending, None when it shouldn't go out
+    async fn prepare_order(&self, price: usize, direction: Direction, card: &Card, book: &Book, mut size: usize) -> Option<Order> {
+        if direction != Direction::Cancel && self.idle_suits.lock().await.contains(card) {
+            return None;
+        }
+        let price = Price::new(price).clamp_to(self.max_price).value();
+        let size_jitter = self.config.obfuscation.size_jitter;
+        if size_jitter > 0 && direction != Direction::Cancel {
+            let jitter = self.jitter.lock().await.gen_range(0..=2 * size_jitter);
+            size = (size + jitter).saturating_sub(size_jitter).max(1);
+        }
+        let held = self.market.read().await.inventory.get(card);
         match direction {
-            Direction::Buy => {
-                if book.bid.price < price && book.bid.player_name != self.name {
-                    trade = true;
-                }
+            Direction::Sell if held == 0 && (!self.allow_short || self.position_limits.max_short == 0) => {
+                log!(LogLevel::Warn, "[!] {:?} | {:?} |:| Rejected sell, nothing to sell", self.name, card);
+                return None;
             },
-            Direction::Sell => {
-                if book.ask.price > price && book.ask.player_name != self.name {
-                    trade = true;
+            Direction::Buy => if let Some(max_holding) = self.max_holding {
+                size = size.min(max_holding.saturating_sub(held));
+                if size == 0 {
+                    log!(LogLevel::Warn, "[!] {:?} | {:?} |:| Rejected buy, already holding {}", self.name, card, held);
+                    return None;
                 }
+            },
+            _ => {}
+        }
+
+        let actionable = if self.layering { book.is_layerable(&self.name, price, &direction) } else { book.is_actionable(&self.name, price, &direction) };
+        if !actionable {
+            return None;
+        }
+        Some(Order {
+            player_name: self.name.clone(),
+            price,
+            direction,
+            card: card.clone(),
+            size,
+            tif: TimeInForce::GTC,
+            context: Some(OrderContext::from_book(book)),
+            layer: self.layering,
+            paired: None,
+            seq: 0, // stamped on the way out
+        })
+    }
+
+    async fn dispatch(&self, mut order: Order) -> Result<(), PlayerError> {
+        let legs: Vec<&Order> = std::iter::once(&order).chain(order.paired.as_deref()).collect();
+        log!(LogLevel::Debug, "{:?} |:| Sending order: {:?}", self.name, order);
+
+        if self.paper {
+            log!(LogLevel::Info, CL::Purple, "[~] {:?} |:| Paper order: {:?}", self.name, order);
+            for leg in legs {
+                self.paper_fill(leg).await;
             }
+            return Ok(());
         }
-        
-        if trade {
-            let order = Order {
-                player_name: self.name.clone(),
-                price,
-                direction,
-                card: card.clone(),
+
+        {
+            let mut outstanding = self.outstanding.lock().await;
+            if legs.iter().any(|leg| outstanding.crosses_own(leg)) {
+                log!(LogLevel::Warn, "[!] {:?} | {:?} |:| Held back, would cross our own order in flight", self.name, order.card);
+                return Ok(());
+            }
+            for leg in legs {
+                outstanding.record(leg);
+            }
+        }
+
+        self.acks.lock().await.stamp(&mut order, self.clock.now());
+        self.metrics.order_sent();
+        self.order_sender.send(order).await?;
+        Ok(())
+    }
+
+    // an order the exchange never answered didn't make it there, so it isn't resting either
+    async fn expire_acks(&self) {
+        let Some(ack_timeout) = self.ack_timeout else {
+            return;
+        };
+        let lost = self.acks.lock().await.expire(self.clock.now(), ack_timeout);
+        for order in lost {
+            log!(LogLevel::Warn, "[!] {:?} | {:?} |:| No ack for order #{} after {:?}, presuming it lost", self.name, order.card, order.seq, ack_timeout);
+            let mut outstanding = self.outstanding.lock().await;
+            outstanding.forget(&order);
+            if let Some(leg) = &order.paired {
+                outstanding.forget(leg);
+            }
+        }
+    }
+
+    // send_order for strategies that have a fair value in mind, anything that doesn't beat it by edge_threshold stays home
+    pub async fn send_order_with_edge(&self, price: usize, direction: Direction, card: &Card, book: &Book, size: usize, fair_value: f32) -> Result<(), PlayerError> {
+        let edge = match direction {
+            Direction::Buy => fair_value - price as f32,
+            Direction::Sell => price as f32 - fair_value,
+            Direction::Cancel => f32::INFINITY, // never in the way of taking risk off
+        };
+        let threshold = self.config.risk.edge_threshold;
+        if threshold > 0.0 && edge < threshold {
+            log!(LogLevel::Debug, "{:?} | {:?} |:| Held back {:?} at {}, edge {:.1} under {:.1}", self.name, card, direction, price, edge, threshold);
+            return Ok(());
+        }
+        self.send_order(price, direction, card, book, size).await
+    }
+
+    async fn paper_fill(&self, order: &Order) { // pretend the whole order traded at its price, against nobody
+        let (buyer, seller, is_buy) = match order.direction {
+            Direction::Buy => (self.name.clone(), PlayerName::None, true),
+            Direction::Sell => (PlayerName::None, self.name.clone(), false),
+            Direction::Cancel => return,
+        };
+        let mut market = self.market.write().await;
+        let inventory = &mut market.inventory;
+        let mut pnl = self.pnl.lock().await;
+        let size = if is_buy { order.size } else { order.size.min(inventory.get(&order.card)) };
+        let timestamp = self.clock.since(*self.timer.lock().await).as_millis() as u64; // our own round clock, close enough to the exchange's
+        for _ in 0..size {
+            let trade = Trade {
+                card: order.card.clone(),
+                price: order.price,
+                buyer: buyer.clone(),
+                seller: seller.clone(),
+                aggressor: order.direction.clone(),
+                timestamp,
             };
-    
-            if self.verbose {
-                println!("{:?} |:| Sending order: {:?}", self.name, order);
+            inventory.change(trade.card.clone(), is_buy);
+            pnl.record(&trade, &self.name);
+        }
+    }
+
+    pub async fn current_pnl(&self) -> i64 { // realized plus inventory marked at mid
+        let market = self.market.read().await.clone();
+        self.pnl.lock().await.mark_to_market(&market.inventory, &market.books())
+    }
+
+    // (best bid, best ask) per suit for whoever is watching from outside (a dashboard, a test) without subscribing to the feed
+    // only the top levels are copied and the read lock is let go straight away, so polling this doesn't hold up the listener,
+    // it's our copy of the books though: as of the last event we processed, anything still queued on the channel isn't in it
+    pub async fn best_quotes(&self) -> HashMap<Card, (Option<Level>, Option<Level>)> {
+        let market = self.market.read().await;
+        [Card::Spade, Card::Club, Card::Diamond, Card::Heart].into_iter().zip(market.books())
+            .map(|(card, book)| (card, (book.bid().cloned(), book.ask().cloned())))
+            .collect()
+    }
+
+    pub async fn flatten(&self, inventory: &Inventory, books: [Book; 4]) -> Result<bool, PlayerError> {
+        // once a suit's bought cards are down max_drawdown at the bid we stop arguing with the market and sell the lot into it,
+        // locking in the loss is the point, so there's no check against what they cost
+        let drawdowns = self.pnl.lock().await.drawdowns(&books, self.config.risk.max_drawdown);
+        let mut flattening = self.flattening.lock().await;
+        for card in drawdowns {
+            if flattening.insert(card.clone()) {
+                log!(LogLevel::Warn, "[!] {:?} | {:?} |:| Drawdown past {}, flattening", self.name, card, self.config.risk.max_drawdown);
+            }
+        }
+        flattening.retain(|card| inventory.get(card) > 0);
+        let suits: Vec<Card> = flattening.iter().cloned().collect();
+        drop(flattening);
+
+        for card in &suits {
+            let book = &books[card.get_index()];
+            if let Some(bid) = book.bid() {
+                self.send_order(bid.price, Direction::Sell, card, book, inventory.get(card)).await?;
+            }
+        }
+        Ok(!suits.is_empty())
+    }
+
+    pub async fn pro(&self, seconds_left: u64, inventory: Inventory, books: [Book; 4]) -> Result<(), PlayerError> {
+        // Spread's quotes in the suits that probably aren't the goal, the Seller's accumulate_goal in any that probably is,
+        // re-decided every tick off the posterior: a suit that changes sides has its old quotes pulled first, an ask resting
+        // in the suit we've just started buying would only sell it back out from under us
+        let posterior = self.estimator.lock().await.posterior();
+        let average_inventory = (inventory.spades + inventory.clubs + inventory.diamonds + inventory.hearts) / 4;
+        for (card, book) in [Card::Spade, Card::Club, Card::Diamond, Card::Heart].into_iter().zip(books) {
+            let accumulate = posterior[card.get_index()] >= self.config.pro.accumulate_threshold;
+            let flipped = if accumulate { self.accumulating.lock().await.insert(card.clone()) } else { self.accumulating.lock().await.remove(&card) };
+            if flipped {
+                log!(LogLevel::Debug, "{:?} | {:?} |:| {} at p = {:.2}", self.name, card, if accumulate { "Accumulating" } else { "Back to making a market" }, posterior[card.get_index()]);
+                self.cancel_orders(&card, &book).await?;
             }
-    
-            if let Err(e) = self.order_sender.send(order).await {
-                println!("[!] {:?} |:| Error sending order: {:?}", self.name, e);
+            if accumulate {
+                self.accumulate_goal(inventory.get(&card), book, card).await?;
+            } else {
+                self.provide_spread(seconds_left, average_inventory, inventory.get(&card), 0, book, card).await?;
             }
         }
-        
+        Ok(())
     }
 
-    pub async fn noisy_trader(&self, inventory: Inventory, spades_book: Book, clubs_book: Book, diamonds_book: Book, hearts_book: Book, rng: &mut StdRng,) {
-        let (random_card, current_inventory, book) = match rng.gen_range(1..=4) {
-            1 => (Card::Spade, inventory.spades, spades_book),
-            2 => (Card::Club, inventory.clubs, clubs_book),
-            3 => (Card::Diamond, inventory.diamonds, diamonds_book),
-            4 => (Card::Heart, inventory.hearts, hearts_book),
+    pub async fn cancel_orders(&self, card: &Card, book: &Book) -> Result<(), PlayerError> {
+        self.send_order(0, Direction::Cancel, card, book, 0).await
+    }
+
+    pub async fn noisy_trader(&self, inventory: Inventory, spades_book: Book, clubs_book: Book, diamonds_book: Book, hearts_book: Book, rng: &mut StdRng,) -> Result<(), PlayerError> {
+        let suit = match self.config.noisy.mode {
+            NoisyMode::Random => rng.gen_range(0..4),
+            NoisyMode::RoundRobin => self.noisy_turn.fetch_add(1, Ordering::Relaxed) % 4,
+            NoisyMode::InventoryWeighted => {
+                let weights = [inventory.spades, inventory.clubs, inventory.diamonds, inventory.hearts].map(|held| held + 1);
+                let mut draw = rng.gen_range(0..weights.iter().sum::<usize>());
+                weights.iter().position(|weight| {
+                    if draw < *weight { return true; }
+                    draw -= weight;
+                    false
+                }).unwrap_or(0)
+            },
+        };
+        let (random_card, current_inventory, book) = match suit {
+            0 => (Card::Spade, inventory.spades, spades_book),
+            1 => (Card::Club, inventory.clubs, clubs_book),
+            2 => (Card::Diamond, inventory.diamonds, diamonds_book),
+            3 => (Card::Heart, inventory.hearts, hearts_book),
             _ => (Card::Spade, 0, spades_book) // this should never happen
         };
         
-        let is_buy = rand::random::<bool>();
+        let is_buy = rng.gen_bool(0.5);
         match is_buy {
             true => {
                 let price = rng.gen_range(1..15);
-                if current_inventory < 4 {
-                    println!("NOISY |:| BUY | Random card: {:?} | Price: {}", random_card, price);
-                    self.send_order(price, Direction::Buy, &random_card, &book).await;
+                if self.position_limits.can_buy(current_inventory) {
+                    log!(LogLevel::Debug, "NOISY |:| BUY | Random card: {:?} | Price: {}", random_card, price);
+                    self.send_order(price, Direction::Buy, &random_card, &book, 1).await?;
                 }
             },
             false => {
                 let price = rng.gen_range(1..15);
                 if current_inventory > 0 {
-                    println!("NOISY |:| SELL | Random card: {:?} | Price: {} | current_inventory: {}", random_card, price, current_inventory);
-                    self.send_order(price, Direction::Sell, &random_card, &book).await;
+                    log!(LogLevel::Debug, "NOISY |:| SELL | Random card: {:?} | Price: {} | current_inventory: {}", random_card, price, current_inventory);
+                    self.send_order(price, Direction::Sell, &random_card, &book, 1).await?;
                 }
             }
         }
+        Ok(())
+    }
+
+    pub async fn momentum(&self, seconds_left: u64, inventory: usize, book: Book, card: Card, trades: &[Trade]) -> Result<(), PlayerError> {
+        // - chase the trend: buy when the short moving average of prints crosses above the long one, sell on the reverse cross
+        // - the cross is found by comparing the averages now against the averages one trade ago, so nothing needs to be remembered between ticks
+        // - late in the round a trend says more about who found the goal suit than about where price is going, so we stop chasing
+        // - a cross the resting size leans against is ignored, buying into a book stacked with sellers is how trends end
+        if seconds_left <= 30 || self.short_window == 0 || self.short_window >= self.long_window {
+            return Ok(());
+        }
+
+        let prices: Vec<usize> = trades.iter().filter(|trade| trade.card == card).map(|trade| trade.price).collect();
+        if prices.len() <= self.long_window {
+            return Ok(());
+        }
+
+        let average = |window: &[usize]| window.iter().sum::<usize>() as f32 / window.len() as f32;
+        let now = prices.len();
+        let short = average(&prices[now - self.short_window..]);
+        let long = average(&prices[now - self.long_window..]);
+        let previous_short = average(&prices[now - 1 - self.short_window..now - 1]);
+        let previous_long = average(&prices[now - 1 - self.long_window..now - 1]);
+
+        let imbalance = book.imbalance();
+        if previous_short <= previous_long && short > long && imbalance >= 0.0 {
+            if let Some(ask) = book.ask().filter(|_| self.position_limits.can_buy(inventory)) {
+                self.send_order(ask.price, Direction::Buy, &card, &book, 1).await?;
+            }
+        } else if previous_short >= previous_long && short < long && imbalance <= 0.0 && inventory > 0 {
+            if let Some(bid) = book.bid() {
+                self.send_order(bid.price, Direction::Sell, &card, &book, 1).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn mean_revert(&self, seconds_left: u64, inventory: usize, book: Book, card: Card, trades: &[Trade]) -> Result<(), PlayerError> {
+        // - fade the latest print when it's more than `revert_k` standard deviations away from the recent mean, the counterparty to Momentum
+        // - with fewer than 3 prints there's no dispersion to speak of, so we just sit a tick either side of the last trade
+        // - in the last 20 seconds a move is more likely the goal suit being found than noise, so we stand aside
+        // - we only fade a print in the direction its aggressor pushed it, a resting quote getting filled isn't an overshoot
+        if seconds_left <= 20 || self.revert_window == 0 {
+            return Ok(());
+        }
+
+        let prices: Vec<usize> = trades.iter().filter(|trade| trade.card == card).map(|trade| trade.price).collect();
+        let last_aggressor = trades.iter().rev().find(|trade| trade.card == card).map(|trade| trade.aggressor.clone());
+        let window = &prices[prices.len().saturating_sub(self.revert_window)..];
+        let last = match window.last() {
+            Some(last) => *last,
+            None => return Ok(()), // nothing to revert to yet
+        };
+
+        let mean = window.iter().sum::<usize>() as f32 / window.len() as f32;
+        let variance = window.iter().map(|price| (*price as f32 - mean).powi(2)).sum::<f32>() / window.len() as f32;
+        let sigma = variance.sqrt();
+
+        if window.len() < 3 || sigma == 0.0 {
+            if self.position_limits.can_buy(inventory) && last > 1 {
+                self.send_order(last - 1, Direction::Buy, &card, &book, 1).await?;
+            }
+            if inventory > 0 {
+                self.send_order(Price::new(last).saturating_add(1).value(), Direction::Sell, &card, &book, 1).await?;
+            }
+            return Ok(());
+        }
+
+        let deviation = (last as f32 - mean) / sigma;
+        if deviation > self.revert_k && inventory > 0 && last_aggressor == Some(Direction::Buy) {
+            match book.bid() {
+                Some(bid) if bid.price as f32 >= mean => self.send_order(bid.price, Direction::Sell, &card, &book, 1).await?, // still rich, take it
+                _ => self.send_order(last, Direction::Sell, &card, &book, 1).await?,
+            }
+        } else if deviation < -self.revert_k && self.position_limits.can_buy(inventory) && last_aggressor == Some(Direction::Sell) {
+            match book.ask() {
+                Some(ask) if ask.price as f32 <= mean => self.send_order(ask.price, Direction::Buy, &card, &book, 1).await?,
+                _ => self.send_order(last, Direction::Buy, &card, &book, 1).await?,
+            }
+        }
+        Ok(())
+    }
+
+    pub fn implied_fair_values(posterior: &[f32; 4], books: &[&Book; 4]) -> [f32; 4] {
+        // - exactly one suit pays out and the whole pot is all it pays, so the four fair values are one budget split by goal probability
+        // - a goal card is worth the $10 payout for sure, plus a share of what's left of the pot (~110 over ~9 cards),
+        // but that remainder only goes to the majority holder, so a single marginal card is only credited with part of it
+        // - the probability comes from the estimator, and once every suit has printed, half of it comes from the relative last trades instead
+        // (each suit's price over the sum is what the market as a whole thinks that suit's odds are)
+        let per_goal_card = FairValue::goal_card_value();
+
+        let last_trades: Vec<usize> = books.iter().filter_map(|book| book.last_price()).collect();
+        let total: usize = last_trades.iter().sum();
+        let mut probabilities = *posterior;
+        if last_trades.len() == 4 && total > 0 {
+            for (probability, last_trade) in probabilities.iter_mut().zip(last_trades.iter()) {
+                *probability = 0.5 * *probability + 0.5 * *last_trade as f32 / total as f32;
+            }
+        }
+
+        probabilities.map(|probability| probability * per_goal_card)
+    }
+
+    pub async fn arb(&self, seconds_left: u64, inventory: usize, fair: f32, book: Book, card: Card) -> Result<(), PlayerError> {
+        // buy what trades under the shared-budget fair value, sell what trades over it
+        // in the last 10 seconds there's no one left to sell to, so we stop adding
+        if let Some(ask) = book.ask() {
+            if seconds_left > 10 && self.position_limits.can_buy(inventory) && (ask.price as f32) < fair - ARB_EDGE {
+                self.send_order(ask.price, Direction::Buy, &card, &book, 1).await?;
+            }
+        }
+        if let Some(bid) = book.bid() {
+            if inventory > 0 && bid.price as f32 > fair + ARB_EDGE {
+                self.send_order(bid.price, Direction::Sell, &card, &book, 1).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn hedge(&self, inventory: Inventory, books: [Book; 4]) -> Result<(), PlayerError> {
+        // - the goal suit is the 12-card suit's same-color partner, so a pile of one color is a bet on that color either way
+        // - once one color holds more than `band` cards over the other, we lean it back by selling the partner of our longest suit there,
+        // keeping the long itself and shedding the exposure it shares with its pair (the long goes too if the partner is already empty)
+        let colors = &self.config.hedge.colors;
+        let exposure = |pair: &[Card; 2]| inventory.get(&pair[0]) + inventory.get(&pair[1]);
+        let (first, second) = (exposure(&colors[0]), exposure(&colors[1]));
+        let heavy_pair = if first.abs_diff(second) <= self.config.hedge.band {
+            return Ok(());
+        } else if first > second {
+            &colors[0]
+        } else {
+            &colors[1]
+        };
+
+        let (long, partner) = if inventory.get(&heavy_pair[0]) >= inventory.get(&heavy_pair[1]) {
+            (&heavy_pair[0], &heavy_pair[1])
+        } else {
+            (&heavy_pair[1], &heavy_pair[0])
+        };
+        let card = if inventory.get(partner) > 0 { partner } else { long };
+        let book = &books[card.get_index()];
+
+        match (book.bid(), book.ask()) {
+            (Some(bid), _) => self.send_order(bid.price, Direction::Sell, card, book, 1).await?,
+            (None, Some(ask)) if ask.price > 1 => self.send_order(ask.price - 1, Direction::Sell, card, book, 1).await?,
+            _ => {}
+        }
+        Ok(())
     }
 
-    pub async fn praying_mantis_sell(&self, seconds_left: u64, inventory: usize, book: Book, card: Card) {
+    pub async fn praying_mantis_sell(&self, seconds_left: u64, inventory: usize, book: Book, card: Card) -> Result<(), PlayerError> {
         // - this is the byproduct of seeing what happens with TheHoarder. Instead of hoarding, which incurs a disproportionate amount of toxic flow,
         // we'll wait to the last minute and buy up the inventory of the goal card. While we wait for the last minute, we'll sell off the other inventory
         // - something of note for this strategy: it assumes that the market is right and that the goal card is the most valuable card,
         // if the participants are not rational / operating at a high skill level, this strategy will simply not work
-        if seconds_left >= 60 {
-            if inventory > 0 && book.ask.price >= 7 {
-                self.send_order(book.ask.price - 1, Direction::Sell, &card, &book).await;
+        if let Some(ask) = book.ask() {
+            if seconds_left >= 60 && inventory > 0 && ask.price >= 7 {
+                self.send_order(ask.price - 1, Direction::Sell, &card, &book, 1).await?;
             }
         }
+        Ok(())
     }
 
-    pub async fn praying_mantis_snipe(&self, seconds_left: u64, inventory: usize, book: Book, card: Card) {
-        if seconds_left <= 60 {
-            if book.ask.price <= 9 { // at this point in the game, we shouldn't expect to gain the most goal suits, so we'll try and pick off any < 10 to net +(10-price)
-                self.send_order(book.ask.price, Direction::Buy, &card, &book).await;
+    pub async fn praying_mantis_snipe(&self, seconds_left: u64, _inventory: usize, book: Book, card: Card) -> Result<(), PlayerError> {
+        if let Some(ask) = book.ask() {
+            if seconds_left <= 60 && ask.price <= 9 { // at this point in the game, we shouldn't expect to gain the most goal suits, so we'll try and pick off any < 10 to net +(10-price)
+                self.send_order(ask.price, Direction::Buy, &card, &book, 1).await?;
             }
         }
+        Ok(())
     }
 
-    pub async fn hoard(&self, seconds_left: u64, inventory: usize, book: Book, card: Card) {
+    pub async fn hoard(&self, seconds_left: u64, inventory: usize, book: Book, card: Card) -> Result<(), PlayerError> {
         // - the goal for this strategy is to amass 6x of each card (add +16 cards in total), to mathematically guarantee a win and secure the pot
         // - the budget for each card: 7.5; which means, if, on average, each card is paid > 7.5 for, it loses money, and if < 7.5, it makes money
         // - this strategy and Seller go well together, however, the Seller gets a better deal, whereas TheHoarder still needs +8 other cards
@@ -249,190 +795,470 @@ impl GenericPlayer {
         // -- (side note) this won't work in a game where the participants are advanced, as they would have already known about this goal card ahead of you,
         // and buy up with more effective buying power
 
-        if inventory < 6 { // we need to buy more
+        if self.position_limits.can_buy(inventory) { // we need to buy more
+            let bid_price = book.bid().map_or(0, |bid| bid.price); // nobody bidding yet, we open at 1
             // we're going to aggressively buy up inventory at first, assuming that information about the goal card is not known,
             // keeping the goal premium lower, and then as time goes on we'll pick up other inventory at a lower price on the offchance
             if seconds_left >= 120 {
-                if book.ask.price <= 7 {
-                    self.send_order(book.ask.price, Direction::Buy, &card, &book).await;
-                } else {
-                    if book.bid.price < 7 {
-                        self.send_order(book.bid.price + 1, Direction::Buy, &card, &book).await;
+                match book.ask() {
+                    Some(ask) if ask.price <= 7 => self.send_order(ask.price, Direction::Buy, &card, &book, 1).await?,
+                    _ => if bid_price < 7 {
+                        self.send_order(bid_price + 1, Direction::Buy, &card, &book, 1).await?;
                     }
                 }
             } else if seconds_left > 60 && seconds_left < 120 {
-                if book.ask.price <= 6 {
-                    self.send_order(book.ask.price, Direction::Buy, &card, &book).await;
-                } else {
-                    if book.bid.price < 6 {
-                        self.send_order(book.bid.price + 1, Direction::Buy, &card, &book).await;
+                match book.ask() {
+                    Some(ask) if ask.price <= 6 => self.send_order(ask.price, Direction::Buy, &card, &book, 1).await?,
+                    _ => if bid_price < 6 {
+                        self.send_order(bid_price + 1, Direction::Buy, &card, &book, 1).await?;
                     }
                 }
             } else {
-                if book.ask.price <= 4 {
-                    self.send_order(book.ask.price, Direction::Buy, &card, &book).await;
-                } else {
-                    if book.bid.price < 4 {
-                        self.send_order(book.bid.price + 1, Direction::Buy, &card, &book).await;
+                match book.ask() {
+                    Some(ask) if ask.price <= 4 => self.send_order(ask.price, Direction::Buy, &card, &book, 1).await?,
+                    _ => if bid_price < 4 {
+                        self.send_order(bid_price + 1, Direction::Buy, &card, &book, 1).await?;
                     }
                 }
             }
         }
+        Ok(())
     }
 
-    pub async fn sell_inventory(&self, seconds_left: u64, inventory: usize, book: Book, card: Card) {
-        // - to net even with 5 players, the inventory must be sold at an average price of ~5
+    pub async fn sell_inventory(&self, seconds_left: u64, inventory: usize, book: Book, card: Card) -> Result<(), PlayerError> {
+        // - to net even, the inventory must be sold at an average of Pot::break_even_price, the ante over the cards dealt (5 with 4 or 5 players)
         // - we expect the worthless cards to be valued less and less as times goes on,
         // and the goal card to be valued more and more
         // - depending on the market participants, this process can vary in speed, but the general idea is
         // to follow this expectation and sell the inventory less and less - knowing the goal card will always be picked off
         // - strategy adaptation: see which inventory is picked off first, then quickly sell off the other inventory while
         // bidding back the picked off inventory (betting on the market bring right)
+        // phases are quarters of the round, for the default 240s: 180s, 120s, 60s
+        let three_quarters = self.round_seconds * 3 / 4;
+        let half = self.round_seconds / 2;
+        let quarter = self.round_seconds / 4;
         if inventory > 0 {
-            if seconds_left >= 180 {
-                // market: 6, limit: 7
-                if book.bid.price >= 6 {
-                    self.send_order(book.bid.price, Direction::Sell, &card, &book).await;
-                }
-                self.send_order(8, Direction::Sell, &card, &book).await;
-            } else if seconds_left > 120 && seconds_left < 180 {
-                // market: 5, limit: 6
-                if book.bid.price >= 5 {
-                    self.send_order(book.bid.price, Direction::Sell, &card, &book).await;
-                }
-                self.send_order(6, Direction::Sell, &card, &book).await;
-            } else if seconds_left > 60 && seconds_left < 120 {
-                // market: 4, limit: 5
-                if book.bid.price >= 4 {
-                    self.send_order(book.bid.price, Direction::Sell, &card, &book).await;
-                }
-                self.send_order(6, Direction::Sell, &card, &book).await;
+            let phase = if seconds_left >= three_quarters {
+                0
+            } else if seconds_left > half && seconds_left < three_quarters {
+                1
+            } else if seconds_left > quarter && seconds_left < half {
+                2
             } else {
-                // market: 3, limit: 4
-                if book.bid.price >= 3 {
-                    self.send_order(book.bid.price, Direction::Sell, &card, &book).await;
+                3
+            };
+            // don't hand the likely goal suit to whoever already looks set to take its majority
+            let (likely_goal, _) = self.estimator.lock().await.most_likely();
+            // unsold cards outside the goal suit pay nothing, so in the last seconds any bid beats holding them,
+            // the likely goal suit is worth 10 a card at the bell and is never dumped this way
+            let panic = seconds_left < self.config.seller.panic_seconds && card != likely_goal;
+            if panic {
+                if let Some(bid) = book.bid() {
+                    self.send_order(bid.price, Direction::Sell, &card, &book, inventory).await?;
                 }
-                self.send_order(4, Direction::Sell, &card, &book).await;
+                return Ok(());
+            }
+            let reservation = self.reservation_price(seconds_left);
+            let feeds_hoarder = match book.bid() {
+                Some(bid) => card == likely_goal && self.competitors.lock().await.estimate(&bid.player_name, &card) >= HOARDING_CARDS,
+                None => false,
+            };
+            let hand = self.market.read().await.inventory;
+            let posterior = self.estimator.lock().await.posterior();
+            let fair = FairValue::with_goal(&card, self.trades.lock().await.as_slice(), FairValue::suit_count_hint(&hand, &card), &posterior);
+            if let Some(bid) = book.bid().filter(|bid| bid.price >= reservation && !feeds_hoarder) {
+                self.send_order_with_edge(bid.price, Direction::Sell, &card, &book, inventory, fair).await?; // dump the whole stack into the bid
             }
+            self.send_order_with_edge(self.config.seller.limit[phase], Direction::Sell, &card, &book, 1, fair).await?;
         }
+        Ok(())
     }
 
-    pub async fn provide_spread(&self, seconds_left: u64, average_inventory: usize, inventory: usize, book: Book, card: Card) {
+    pub async fn accumulate_goal(&self, inventory: usize, book: Book, card: Card) -> Result<(), PlayerError> {
+        // the Seller's buy side, only used on the suit it thinks is the goal: a card of it is worth up to 10 at the bell,
+        // so take any ask under fair value and otherwise lean on the bid, never paying fair value or more
+        if !self.position_limits.can_buy(inventory) {
+            return Ok(());
+        }
+        let hand = self.market.read().await.inventory;
+        let posterior = self.estimator.lock().await.posterior();
+        let fair = FairValue::with_goal(&card, self.trades.lock().await.as_slice(), FairValue::suit_count_hint(&hand, &card), &posterior);
+        if let Some(ask) = book.ask().filter(|ask| (ask.price as f32) < fair) {
+            return self.send_order_with_edge(ask.price, Direction::Buy, &card, &book, 1, fair).await;
+        }
+        // holding more than everyone else could between them, the majority is ours whatever happens, so stop bidding the suit up
+        if inventory > self.deal.lock().await.cards_outstanding(&card, &hand) {
+            return Ok(());
+        }
+        let bid_price = book.bid().map_or(1, |bid| bid.price + 1);
+        if (bid_price as f32) < fair {
+            self.send_order_with_edge(bid_price, Direction::Buy, &card, &book, 1, fair).await?;
+        }
+        Ok(())
+    }
+
+    pub fn reservation_price(&self, seconds_left: u64) -> usize {
+        // the least the seller takes for a card, falling from reservation_open to reservation_close as the clock runs down
+        let seller = &self.config.seller;
+        let remaining = seconds_left.min(self.round_seconds) as f32 / self.round_seconds.max(1) as f32;
+        let (open, close) = (seller.reservation_open as f32, seller.reservation_close as f32);
+        (close + (open - close) * remaining).round() as usize
+    }
+
+    pub fn toxicity_factor(&self, seconds_left: u64) -> f32 {
+        // flow gets more informed as the round goes on, whoever still trades late likely knows the goal suit
+        // 1.0 for most of the round, climbing smoothly to 2.0 at the bell, the steepness sets how late the climb starts
+        1.0 + (-self.config.spread.toxicity_steepness * seconds_left as f32 / 60.0).exp()
+    }
+
+    pub async fn provide_spread(&self, seconds_left: u64, average_inventory: usize, inventory: usize, goal_premium: usize, mut book: Book, card: Card) -> Result<(), PlayerError> {
+        let quoted_at = match book.last_price() {
+            Some(last_trade) => self.quoted_at.lock().await.insert(card.clone(), last_trade),
+            None => None,
+        };
+        let fair_value = match self.config.spread.improve_bid {
+            true => {
+                let hand = self.market.read().await.inventory;
+                let posterior = self.estimator.lock().await.posterior();
+                Some(FairValue::with_goal(&card, self.trades.lock().await.as_slice(), FairValue::suit_count_hint(&hand, &card), &posterior))
+            },
+            false => None,
+        };
+        let context = MarketContext { average_inventory, goal_premium, quoted_at, fair_value, ..MarketContext::new(seconds_left, card.clone(), book.clone(), inventory) };
+
+        // a bid and an ask go out together as one two-sided quote, so the book never shows just one of them
+        let ladder = self.config.ladder.get(&card);
+        let (mut bid, mut ask) = (None, None);
+        for order in self.decide_spread(&context) {
+            match order.direction {
+                Direction::Cancel => {
+                    self.cancel_orders(&card, &book).await?;
+                    book.cancel(&self.name); // so the requotes below don't see our stale levels as live
+                },
+                Direction::Buy => bid = Some(order),
+                Direction::Sell => ask = Some(order),
+            }
+        }
+        match (bid, ask) {
+            (Some(bid), Some(ask)) => self.quote_two_sided(ladder.round(bid.price), ladder.round(ask.price), &card, &book, bid.size).await,
+            (Some(order), None) | (None, Some(order)) => self.send_order(ladder.round(order.price), order.direction, &card, &book, order.size).await,
+            (None, None) => Ok(()),
+        }
+    }
+
+    pub fn decide_spread(&self, context: &MarketContext) -> Vec<Order> {
+        let mut orders = Vec::new();
+        let mut book = context.book.clone();
+        let card = &context.card;
+
+        // pull our quotes if the market moved away from where we priced them (more than two ticks)
+        if let (Some(last_trade), Some(previous)) = (book.last_price(), context.quoted_at) {
+            if previous.abs_diff(last_trade) > self.config.spread.requote_ticks {
+                orders.push(Order::new(self.name.clone(), 0, Direction::Cancel, card.clone(), 0));
+                book.cancel(&self.name);
+            }
+        }
+
         // - spread should balance their book at the very least, inventory -> 0 or $$$, and if it's imbalanced, there's a higher chance it's worthless (due to asymmetric information)
         // - therefore, it should try to keep its book balanced to make up for the 0s it'll inevitably face at time 0
         // - buy more of the inventory that's lower than the average, sell more of the inventory that's higher than the average
-        // - in other words, let's skew our quotes a little bit
-        // - to do so, let's use "+2" vs "+1" and vice versa for the asks
-        if inventory > 0 {
-            if let Some(last_trade) = book.last_trade {
-                if inventory > average_inventory {
-                    self.send_order(last_trade + 1, Direction::Sell, &card, &book).await; // want to sell more
-                } else {
-                    self.send_order(last_trade + 2, Direction::Sell, &card, &book).await; // want to sell less
-                }
-            } else {
-                if book.ask.price > 7 {
-                    if inventory > average_inventory {
-                        self.send_order(book.ask.price - 2, Direction::Sell, &card, &book).await; // want to sell more
-                    } else {
-                        self.send_order(book.ask.price - 1, Direction::Sell, &card, &book).await; // want to sell less
-                    }
+        // - in other words, let's skew our quotes: both prices move down by `skew_ticks` when we're long (tighter ask, bid backs off) and up when we're short
+        // - separately, we lean towards `target_inventory`: over it the ask gets keener and the bid backs off, under it the reverse
+        // - on the suit the estimator thinks is the goal, both quotes lean up by `goal_premium`, it's worth more to everyone
+        // - at the position limit we stop bidding altogether rather than keep piling on
+        let (inventory, goal_premium) = (context.inventory, context.goal_premium);
+        let imbalance = inventory as f32 - context.average_inventory as f32;
+        let off_target = inventory as f32 - self.config.spread.target_inventory[card.get_index()] as f32;
+        let skew_ticks = (imbalance * self.config.spread.skew_sensitivity + off_target * self.config.spread.target_sensitivity).round() as i64;
+        let skewed = |price: usize| {
+            let price = Price::new(price);
+            let price = if skew_ticks > 0 { price.saturating_sub(skew_ticks as usize) } else { price.saturating_add(skew_ticks.unsigned_abs() as usize) };
+            price.value().max(1)
+        };
+        let quote = |price: usize, direction: Direction| Order::new(self.name.clone(), price, direction, card.clone(), 1);
+        let can_bid = context.seconds_left > self.config.spread.buy_cutoff_seconds && self.position_limits.can_buy(inventory);
+
+        if self.config.spread.join_inside {
+            // penny the best quotes that aren't ours for queue priority, skip both sides if that would leave less than min_spread between them
+            let best_bid = book.bids.iter().find(|level| level.player_name != self.name).map(|level| level.price);
+            let best_ask = book.asks.iter().find(|level| level.player_name != self.name).map(|level| level.price);
+            let bid = best_bid.map(|price| price + 1);
+            let ask = best_ask.map(|price| price.saturating_sub(1)).filter(|price| *price > 0);
+            if let (Some(bid), Some(ask)) = (bid, ask) {
+                if ask < bid + self.config.spread.min_spread.max(1) {
+                    return orders;
                 }
             }
-        } 
+            if let Some(ask) = ask.filter(|_| inventory > 0) {
+                orders.push(quote(ask, Direction::Sell));
+            }
+            if let Some(bid) = bid.filter(|_| can_bid) {
+                orders.push(quote(bid, Direction::Buy));
+            }
+            return orders;
+        }
 
-        if seconds_left > 20 { // we expect flow to gradually become more toxic as time goes on so we'll refrain from buying in these last 20 seconds
-            if let Some(last_trade) = book.last_trade {
-                if last_trade > 2 {
-                    if inventory > average_inventory {
-                        self.send_order(last_trade - 2, Direction::Buy, &card, &book).await; // want to buy less
-                    } else {
-                        self.send_order(last_trade - 1, Direction::Buy, &card, &book).await; // want to buy more
-                    }
-                } else {
-                    self.send_order(1, Direction::Buy, &card, &book).await;
-                }
+        // with nothing traded yet we quote around the default_fair prior rather than whoever quoted first,
+        // and only fall back to the mid when that's turned off
+        let anchor = Some(self.config.spread.default_fair).filter(|fair| *fair > 0);
+        let reference = book.last_price().or(anchor).or_else(|| book.mid().and_then(Price::from_f32).map(Price::value));
+        let toxicity = self.toxicity_factor(context.seconds_left);
+        let ask_offset = (self.config.spread.ask_offset as f32 * toxicity).round() as usize;
+        let bid_offset = (self.config.spread.bid_offset as f32 * toxicity).round() as usize;
+
+        // around a reference the two quotes are set together, and pulled in evenly (the odd tick off the ask) to max_spread
+        let around_reference = reference.map(|last_trade| {
+            let ask = skewed(last_trade + ask_offset + goal_premium);
+            let bid = skewed(last_trade.saturating_sub(bid_offset).max(1) + goal_premium);
+            let max_spread = self.config.spread.max_spread;
+            let excess = ask.saturating_sub(bid).saturating_sub(max_spread);
+            if max_spread > 0 && excess > 0 {
+                (ask - excess.div_ceil(2), bid + excess / 2)
             } else {
-                if book.bid.price < 7 {
-                    if inventory > average_inventory {
-                        self.send_order(book.bid.price + 1, Direction::Buy, &card, &book).await; // want to buy less
-                    } else {
-                        self.send_order(book.bid.price + 2, Direction::Buy, &card, &book).await; // want to buy more
-                    }
-                }
+                (ask, bid)
+            }
+        });
+
+        if inventory > 0 {
+            if let Some((ask, _)) = around_reference {
+                orders.push(quote(ask, Direction::Sell));
+            } else if let Some(ask) = book.ask().map(|ask| ask.price).filter(|price| *price > self.config.spread.open_limit) { // with no ask at all there's nothing to improve on
+                orders.push(quote(skewed(ask - 1), Direction::Sell));
             }
         }
-        
+
+        if can_bid { // the widening takes care of the toxic close, the cutoff is there to stop outright
+            let bid = match around_reference {
+                Some((_, bid)) => Some(bid),
+                None => {
+                    let bid = book.bid().map_or(0, |bid| bid.price); // an empty bid side means we open the market from the bottom
+                    Some(skewed(bid + 2 + goal_premium)).filter(|_| bid < self.config.spread.open_limit)
+                },
+            };
+            let bid = match self.config.spread.improve_bid {
+                true => bid.and_then(|bid| self.improve_or_match(bid, &book, context.fair_value)).filter(|bid| orders.iter().all(|ask| ask.price > *bid)), // lifted into our own ask, the pair would go out as neither
+                false => bid,
+            };
+            if let Some(bid) = bid {
+                orders.push(quote(bid, Direction::Buy));
+            }
+        }
+
+        orders
+    }
+
+    // a bid under everyone else's best never trades and never shows, so with improve_bid on it goes to a tick over theirs,
+    // but no higher than fair value: capped there it can end up only matching the best bid, and if even that costs more than fair we don't bid
+    pub fn improve_or_match(&self, bid: usize, book: &Book, fair_value: Option<f32>) -> Option<usize> {
+        let Some(best_bid) = book.bids.iter().find(|level| level.player_name != self.name).map(|level| level.price) else {
+            return Some(bid); // nobody else bidding, ours is already the best
+        };
+        let fair = fair_value.unwrap_or(f32::INFINITY);
+        if best_bid as f32 > fair {
+            return None;
+        }
+        let improved = bid.max(best_bid + 1);
+        Some(if improved as f32 > fair { best_bid.max(fair.floor() as usize) } else { improved })
     }
 
     pub async fn listen_to_events(&mut self) {
         
-        let mut event_receiver: Receiver<Event> = self.event_receiver.subscribe();
-
-        let diamonds_book: Arc<Mutex<Book>> = self.diamonds_book.clone();
-        let spades_book: Arc<Mutex<Book>> = self.spades_book.clone();
-        let hearts_book: Arc<Mutex<Book>> = self.hearts_book.clone();
-        let clubs_book: Arc<Mutex<Book>> = self.clubs_book.clone();
+        // don't hold on to a sender ourselves, or the channel could never close on us
+        let mut event_receiver: Receiver<Event> = match self.event_receiver.take() {
+            Some(sender) => sender.subscribe(),
+            None => return, // already listening
+        };
 
-        let inventory: Arc<Mutex<Inventory>> = self.inventory.clone();
-        let trades: Arc<Mutex<Vec<Trade>>> = self.trades.clone();
+        let market: Arc<RwLock<MarketState>> = self.market.clone();
+        let trades: Arc<Mutex<TradeHistory>> = self.trades.clone();
+        let pnl: Arc<Mutex<Pnl>> = self.pnl.clone();
+        let cash: Arc<Mutex<i64>> = self.cash.clone();
+        let estimator: Arc<Mutex<GoalSuitEstimator>> = self.estimator.clone();
+        let deal: Arc<Mutex<DealContext>> = self.deal.clone();
+        let competitors: Arc<Mutex<CompetitorModel>> = self.competitors.clone();
+        let flattening: Arc<Mutex<HashSet<Card>>> = self.flattening.clone();
+        let accumulating: Arc<Mutex<HashSet<Card>>> = self.accumulating.clone();
+        let trade_logger: Option<Arc<dyn TradeSink>> = self.trade_logger.clone();
+        let metrics: Arc<Metrics> = self.metrics.clone();
         let trading: Arc<AtomicBool> = self.trading.clone();
+        let shutdown: Arc<AtomicBool> = self.shutdown.clone();
+        let last_event: Arc<Mutex<Instant>> = self.last_event.clone();
+        let stale: Arc<AtomicBool> = self.stale.clone();
+        let snapshot_sender: Option<Arc<AsyncSender<PlayerName>>> = self.snapshot_sender.clone();
+        let report_sender: Option<Arc<AsyncSender<RoundReport>>> = self.report_sender.clone();
+        let outstanding: Arc<Mutex<OutstandingOrders>> = self.outstanding.clone();
+        let acks: Arc<Mutex<PendingAcks>> = self.acks.clone();
 
         let name: PlayerName = self.name.clone();
-        let verbose: bool = self.verbose;
         let timer = self.timer.clone();
-        
-        tokio::task::spawn(async move {
+        let clock: Arc<dyn Clock> = self.clock.clone();
+
+        let listener = tokio::task::spawn(async move {
+            let request_snapshot = || async {
+                if let Some(snapshot_sender) = &snapshot_sender {
+                    if let Err(e) = snapshot_sender.send(name.clone()).await {
+                        log!(LogLevel::Error, "[!] {:?} |:| Error requesting snapshot: {:?}", name, e);
+                    }
+                }
+            };
+            request_snapshot().await; // we may be joining mid-round
+
             loop {
+                if shutdown.load(Ordering::Acquire) {
+                    break;
+                }
 
-                let event = event_receiver.recv().await.unwrap();
+                let event = match event_receiver.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        // the skipped updates may have carried our trades, so the inventory needs a resync too
+                        log!(LogLevel::Warn, "[!] {:?} |:| Fell behind, skipped {} events", name, skipped);
+                        request_snapshot().await;
+                        continue;
+                    },
+                    Err(RecvError::Closed) => {
+                        log!(LogLevel::Error, "[!] {:?} |:| Event sender dropped, shutting down", name);
+                        trading.store(false, Ordering::Release);
+                        shutdown.store(true, Ordering::Release);
+                        break;
+                    }
+                };
+                *last_event.lock().await = clock.now();
+                if stale.swap(false, Ordering::AcqRel) {
+                    log!(LogLevel::Info, "[+] {:?} |:| Heard from the exchange again, resuming", name);
+                }
+                let event = match event {
+                    Event::Diff(diff) => Event::Update(diff.apply(&market.read().await.books())),
+                    event => event,
+                };
                 match event {
                     Event::Update(update) => {
-                        if let Some(trade) = update.trade { // push trade for historical reasons (if we want to analyze) & update inventory
+                        if !update.trades.is_empty() {
+                            outstanding.lock().await.clear(); // every book was reset
+                        }
+                        let mut market_lock = market.write().await; // held until the books are swapped in too, the update lands all at once
+                        for trade in update.trades { // keep the recent prints for the strategies that read them & update inventory
                             let mut trade_lock = trades.lock().await;
                             trade_lock.push(trade.clone());
 
-                            let mut inventory_lock = inventory.lock().await;
                             if trade.buyer == name {
-                                inventory_lock.change(trade.card, true);
+                                market_lock.inventory.change(trade.card.clone(), true);
                             } else if trade.seller == name {
-                                inventory_lock.change(trade.card, false);
+                                market_lock.inventory.change(trade.card.clone(), false);
+                            }
+                            let mut pnl_lock = pnl.lock().await;
+                            pnl_lock.record(&trade, &name);
+                            metrics.trade_observed(trade.buyer == name || trade.seller == name);
+                            metrics.set_pnl(pnl_lock.realized);
+                            estimator.lock().await.observe_trade(&trade);
+                            competitors.lock().await.observe_trade(&trade);
+                            if let Some(trade_logger) = &trade_logger {
+                                trade_logger.log(&trade);
                             }
                         }
 
+                        estimator.lock().await.observe_books(&[update.spades.clone(), update.clubs.clone(), update.diamonds.clone(), update.hearts.clone()]);
 
-                        let mut spades_lock = spades_book.lock().await;
-                        *spades_lock = update.spades;
-                        
-                        let mut clubs_lock = clubs_book.lock().await;
-                        *clubs_lock = update.clubs;
-
-                        let mut diamonds_lock = diamonds_book.lock().await;
-                        *diamonds_lock = update.diamonds;
-
-                        let mut hearts_lock = hearts_book.lock().await;
-                        *hearts_lock = update.hearts;
-
+                        market_lock.spades = update.spades;
+                        market_lock.clubs = update.clubs;
+                        market_lock.diamonds = update.diamonds;
+                        market_lock.hearts = update.hearts;
                     }
-                    Event::DealCards(players_inventory) => {
-                        let mut inventory_lock = inventory.lock().await;
-                        *inventory_lock = players_inventory.get(&name).unwrap().clone();
+                    Event::DealCards { inventories, pot } => {
+                        let Some(hand) = inventories.get(&name) else {
+                            log!(LogLevel::Error, "[!] {:?} |:| Dealt out of this round, sitting it out", name);
+                            trading.store(false, Ordering::Release);
+                            continue;
+                        };
+                        let mut market_lock = market.write().await;
+                        market_lock.inventory = *hand;
+                        let inventory_lock = market_lock.inventory;
+                        *pnl.lock().await = Pnl::new();
+                        flattening.lock().await.clear();
+                        accumulating.lock().await.clear();
+                        *cash.lock().await -= pot.ante as i64;
+                        outstanding.lock().await.clear();
+                        let mut estimator_lock = estimator.lock().await;
+                        estimator_lock.reset(&inventory_lock);
+                        *deal.lock().await = DealContext::from_hand(inventory_lock);
+                        let players: Vec<PlayerName> = inventories.keys().cloned().collect();
+                        competitors.lock().await.deal(&inventory_lock, &players);
                         
-                        if verbose {
-                            println!("{}[+] {:?} |:| Received cards: {:?}{}", CL::DullGreen.get(), name, inventory_lock, CL::End.get());
-                        }
+                        log!(LogLevel::Debug, CL::DullGreen, "[+] {:?} |:| Received cards: {}", name, inventory_lock);
+                        log!(LogLevel::Debug, CL::DullGreen, "[+] {:?} |:| Hand EV: {:.1}", name, inventory_lock.expected_value(&estimator_lock.priors()));
                         
                         trading.store(true, Ordering::Release);
                         let mut timer_lock = timer.lock().await;
-                        *timer_lock = Instant::now();
+                        *timer_lock = clock.now();
                     },
-                    Event::EndRound => {
+                    Event::EndRound(result) => {
                         trading.store(false, Ordering::Release);
+                        trades.lock().await.clear(); // next round's suits mean something else entirely, the TradeLogger keeps the full record
+                        if let Some(settled) = result.inventories.get(&name) { // settle on what the exchange says we hold
+                            for drift in market.write().await.inventory.reconcile(settled) {
+                                log!(LogLevel::Warn, "[!] {:?} | {:?} |:| Inventory drifted, counted {} but holding {}", name, drift.card, drift.local, drift.expected);
+                            }
+                        }
+                        let round_pnl = *pnl.lock().await;
+                        let realized = round_pnl.realized;
+                        let mut cash_lock = cash.lock().await;
+                        let settled_inventory = market.read().await.inventory;
+                        *cash_lock += realized + result.get_payout(&settled_inventory) as i64;
+                        log!(LogLevel::Info, "[+] {:?} |:| Realized P&L: {} | Cash: {}", name, realized, *cash_lock);
+                        log!(LogLevel::Debug, CL::DullGreen, "[+] {:?} |:| Round settled: {} (goal suit: {:?}, pot: {})", name, settle_round(&settled_inventory, &result), result.goal_suit, result.pot);
+                        drop(cash_lock);
+                        let report = RoundReport::new(name.clone(), settled_inventory, &round_pnl, &result);
+                        if let Some(trade_logger) = &trade_logger {
+                            trade_logger.log_round(&report);
+                        }
+                        if let Some(report_sender) = &report_sender {
+                            if let Err(e) = report_sender.send(report).await {
+                                log!(LogLevel::Error, "[!] {:?} |:| Error sending round report: {:?}", name, e);
+                            }
+                        }
+                    },
+                    Event::OrderRejected { player_name, card, reason } => {
+                        if player_name == name {
+                            outstanding.lock().await.forget_card(&card); // we can't tell which one bounced, better to forget too much
+                            log!(LogLevel::Debug, CL::Orange, "[!] {:?} | {:?} |:| Order rejected: {:?}", name, card, reason);
+                        }
+                    },
+                    Event::Snapshot { player_name, spades, clubs, diamonds, hearts, inventory: snapshot_inventory } => {
+                        if player_name != name {
+                            continue;
+                        }
+                        let mut market_lock = market.write().await;
+                        for drift in market_lock.inventory.reconcile(&snapshot_inventory) {
+                            log!(LogLevel::Warn, "[!] {:?} | {:?} |:| Inventory drifted, counted {} but holding {}", name, drift.card, drift.local, drift.expected);
+                        }
+                        market_lock.spades = spades;
+                        market_lock.clubs = clubs;
+                        market_lock.diamonds = diamonds;
+                        market_lock.hearts = hearts;
+                    }
+                    Event::AccountUpdate { player_name, cash: account_cash } => {
+                        if player_name == name {
+                            *cash.lock().await = account_cash as i64;
+                        }
                     }
+                    Event::Diff(_) => {} // already merged into an Update above
+                    Event::OrderAck { player_name, seq, accepted } => {
+                        if player_name != name {
+                            continue;
+                        }
+                        // a rejection has its own OrderRejected with the reason, a drop behind a trade is covered by the book reset
+                        if let Some(order) = acks.lock().await.resolve(seq) {
+                            if !accepted {
+                                log!(LogLevel::Debug, CL::Orange, "[!] {:?} | {:?} |:| Order #{} not accepted", name, order.card, seq);
+                            }
+                        }
+                    },
+                    Event::Heartbeat { .. } => {} // only here to move last_event along
                 }
 
             }
         });
+        watch_listener(self.name.clone(), listener, self.trading.clone(), Some(self.shutdown.clone()));
     }
 
 }
\ No newline at end of file
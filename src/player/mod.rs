@@ -1,4 +1,7 @@
-use super::{Card, Direction, Book, Trade, Inventory, Order, Event, CL};
+use super::{Card, Color, Direction, TradeHistory, TimeInForce, OrderContext, OutstandingOrders, PendingAcks, Book, Level, Trade, Inventory, Order, Event, Pnl, GoalSuitEstimator, CompetitorModel, DealContext, RoundReport, SuitPriors, FairValue, TradeSink, Metrics, RateLimiter, Clock, SystemClock, CL, LogLevel, log, render_market, Price, DEFAULT_MAX_PRICE, SuitLadders, settle_round};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 pub mod event_driven;
 pub use event_driven::*;
@@ -9,8 +12,63 @@ pub use generic::GenericPlayer;
 pub mod tilt;
 pub use tilt::TiltInventory;
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub mod config;
+pub use config::{StrategyConfig, NoisyMode, ConfigError};
+
+pub mod context;
+pub use context::{MarketContext, MarketState};
+
+pub const DEFAULT_ROUND_SECONDS: u64 = 240; // 4 minutes, matches the MatchMaker's round
+pub const DEFAULT_STALE_AFTER: std::time::Duration = std::time::Duration::from_secs(5); // five missed heartbeats
+pub const DEFAULT_ACK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2); // an order unanswered this long is presumed lost
+
+// a seat at the table, two players can run the same strategy as long as their names differ
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum PlayerName {
+    Named(String),
+    None, // nobody, e.g. the other side of a paper fill
+}
+
+impl PlayerName {
+    pub fn new(name: &str) -> Self {
+        PlayerName::Named(name.to_string())
+    }
+}
+
+impl fmt::Debug for PlayerName { // just the name, it ends up in every log line
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayerName::Named(name) => write!(f, "{}", name),
+            PlayerName::None => write!(f, "None"),
+        }
+    }
+}
+
+
+// why a player couldn't get an order out, anything here means it should stop trading rather than retry next tick
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PlayerError {
+    OrderChannelClosed, // the exchange dropped its receiver, every send from here on fails the same way
+}
+
+impl fmt::Display for PlayerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayerError::OrderChannelClosed => write!(f, "order channel closed"),
+        }
+    }
+}
+
+impl From<kanal::SendError> for PlayerError {
+    fn from(_: kanal::SendError) -> Self { // closed from either side, we can't tell the exchange anything more
+        PlayerError::OrderChannelClosed
+    }
+}
+
+
+// what a player actually does, players are dispatched on this rather than their name
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Strategy {
     Spread,
     Seller,
     Taker,
@@ -20,8 +78,82 @@ pub enum PlayerName {
     TiltInventory,
     TheHoarder,
     PrayingMantis,
-    None,
+    Momentum,
+    Arb,
+    MeanRevert,
+    Hedge,
+    Fade,
+    Pro,
 }
 
+impl FromStr for Strategy {
+    type Err = String;
 
+    fn from_str(name: &str) -> Result<Self, Self::Err> { // the variant names as written, for picking a roster on the command line
+        match name.trim() {
+            "Spread" => Ok(Strategy::Spread),
+            "Seller" => Ok(Strategy::Seller),
+            "Taker" => Ok(Strategy::Taker),
+            "Noisy" => Ok(Strategy::Noisy),
+            "WildestDreams" => Ok(Strategy::WildestDreams),
+            "PickOff" => Ok(Strategy::PickOff),
+            "TiltInventory" => Ok(Strategy::TiltInventory),
+            "TheHoarder" => Ok(Strategy::TheHoarder),
+            "PrayingMantis" => Ok(Strategy::PrayingMantis),
+            "Momentum" => Ok(Strategy::Momentum),
+            "Arb" => Ok(Strategy::Arb),
+            "MeanRevert" => Ok(Strategy::MeanRevert),
+            "Hedge" => Ok(Strategy::Hedge),
+            "Fade" => Ok(Strategy::Fade),
+            "Pro" => Ok(Strategy::Pro),
+            other => Err(format!("unknown strategy '{}'", other)),
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct PositionLimits {
+    pub max_long: usize, // strategies stop buying a suit once they hold this many
+    pub max_short: usize, // how far past flat a sell may go, only consulted when the player allows shorting
+}
+
+impl PositionLimits {
+    pub fn for_strategy(strategy: &Strategy) -> Self { // the limits each strategy was originally tuned with
+        let max_long = match strategy {
+            Strategy::Noisy | Strategy::Momentum | Strategy::MeanRevert | Strategy::Fade => 4,
+            Strategy::PickOff => 3,
+            Strategy::Arb => 5,
+            Strategy::Spread | Strategy::Pro => 8,
+            Strategy::TheHoarder => 6,
+            _ => usize::MAX,
+        };
+        Self { max_long, max_short: 0 }
+    }
+
+    pub fn can_buy(&self, inventory: usize) -> bool {
+        inventory < self.max_long
+    }
+}
+
+
+// a listener that panics (a hand missing from the deal, say) takes the player's view of the market down with it, and left alone
+// the strategy loop would keep trading off the last books it saw, so this waits on the listener and stops the player if it dies
+// (it isn't restarted: the listener holds the only subscription, and whatever it was halfway through updating can't be trusted)
+pub fn watch_listener(name: PlayerName, listener: tokio::task::JoinHandle<()>, trading: std::sync::Arc<std::sync::atomic::AtomicBool>, shutdown: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>) {
+    tokio::task::spawn(async move {
+        let Err(e) = listener.await else {
+            return; // wound down on its own
+        };
+        let reason = match e.try_into_panic() {
+            Ok(panic) => panic.downcast_ref::<&str>().map(|reason| reason.to_string()).or_else(|| panic.downcast_ref::<String>().cloned()).unwrap_or_default(),
+            Err(_) => String::from("cancelled"),
+        };
+        log!(LogLevel::Error, CL::Red, "[!] {:?} |:| Event listener died ({}), no longer trading", name, reason);
+        trading.store(false, std::sync::atomic::Ordering::Release);
+        if let Some(shutdown) = shutdown {
+            shutdown.store(true, std::sync::atomic::Ordering::Release);
+        }
+    });
+}
 
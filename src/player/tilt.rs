@@ -1,6 +1,7 @@
-use super::{Card, Direction, Book, Trade, Inventory, Order, Event, CL, PlayerName};
+use super::{DEFAULT_ROUND_SECONDS, Card, Direction, TimeInForce, Book, Trade, Inventory, Order, Event, MarketState, CL, LogLevel, log, render_market, PlayerName, PlayerError, settle_round, watch_listener};
 use kanal::{AsyncSender};
 use tokio::sync::broadcast::{Sender, Receiver};
+use tokio::sync::broadcast::error::RecvError;
 use std::sync::Arc;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
@@ -14,7 +15,7 @@ use std::time::Instant;
 
 pub struct TiltInventory {
     pub name: PlayerName,
-    pub verbose: bool,
+    pub round_seconds: u64,
     pub timer: Arc<Mutex<Instant>>,
     pub spades_book: Arc<Mutex<Book>>,
     pub clubs_book: Arc<Mutex<Book>>,
@@ -33,7 +34,6 @@ pub struct TiltInventory {
 impl TiltInventory {
     pub fn new(
         player_name: PlayerName,
-        verbose: bool,
         lower_frequency: u64,
         higher_frequency: u64,
         event_receiver: Sender<Event>,
@@ -41,7 +41,7 @@ impl TiltInventory {
     ) -> Self {
         Self {
             name: player_name,
-            verbose,
+            round_seconds: DEFAULT_ROUND_SECONDS,
             timer: Arc::new(Mutex::new(std::time::Instant::now())),
             spades_book: Arc::new(Mutex::new(Book::new())),
             clubs_book: Arc::new(Mutex::new(Book::new())),
@@ -72,98 +72,93 @@ impl TiltInventory {
                 continue;
             }
 
-            let seconds_left = 240 - self.timer.lock().await.elapsed().as_secs();
-
-            let inventory = self.inventory.lock().await.clone();
-
-            let spades_book = self.spades_book.lock().await.clone();
-            let clubs_book = self.clubs_book.lock().await.clone();
-            let diamonds_book = self.diamonds_book.lock().await.clone();
-            let hearts_book = self.hearts_book.lock().await.clone();
-
-            println!("{}{:?} | Inventory |:| Spades: {} | Clubs: {} | Diamonds: {} | Hearts: {}{}", CL::Dull.get(), self.name, inventory.spades, inventory.clubs, inventory.diamonds, inventory.hearts, CL::End.get());
-
-            // only buy lowest card we were dealt and aggressively sell everything else
-            let goal_suit = self.highest_card.lock().await.clone();
-            for card in [Card::Spade, Card::Club, Card::Diamond, Card::Heart].iter() {
-                if *card != goal_suit {
-                    // sell 
-                    let (book, current_inventory) = match card {
-                        Card::Spade => (spades_book.clone(), inventory.spades),
-                        Card::Club => (clubs_book.clone(), inventory.clubs),
-                        Card::Diamond => (diamonds_book.clone(), inventory.diamonds),
-                        Card::Heart => (hearts_book.clone(), inventory.hearts),
-                    };
-
-                    if current_inventory > 0 {
-                        if seconds_left > 30 {
-                            if book.ask.price > 4 {
-                                self.send_order(book.ask.price - 1, Direction::Sell, &card, &book).await;
-                            }
-                        } else {
-                            self.send_order(3, Direction::Sell, &card, &book).await; // 3 since this is the break-point between a blanket buy-all strategy becoming profitable buying up equal amounts of inventory at 3
-                        }
-                    }
-                }
-            }
+            let seconds_left = self.round_seconds.saturating_sub(self.timer.lock().await.elapsed().as_secs()); // an overrun reads as zero rather than underflowing
 
-            let book = match goal_suit {
-                Card::Spade => spades_book,
-                Card::Club => clubs_book,
-                Card::Diamond => diamonds_book,
-                Card::Heart => hearts_book,
-            };
-
-            if book.ask.price <= 5 {
-                // send market buy
-                self.send_order(book.ask.price, Direction::Buy, &goal_suit, &book).await;
+            if let Err(e) = self.tick(seconds_left).await {
+                log!(LogLevel::Error, "[!] {:?} |:| {}, shutting down", self.name, e);
+                break;
             }
 
-            if book.bid.price < 8 {
-                self.send_order(book.bid.price + 1, Direction::Buy, &goal_suit, &book).await;
-            }
-            
-
-
             tokio::time::sleep(tokio::time::Duration::from_millis(rng.gen_range(self.lower_frequency..self.higher_frequency))).await; // promote some sort of fairness, HFT route should go event-driven
         }
     }
 
 
 
-    pub async fn send_order(&self, price: usize, direction: Direction, card: &Card, book: &Book) {
+    async fn tick(&self, seconds_left: u64) -> Result<(), PlayerError> {
+        let inventory = *self.inventory.lock().await;
 
-        let mut trade = false;
-        match direction {
-            Direction::Buy => {
-                if book.bid.price < price && book.bid.player_name != self.name {
-                    trade = true;
-                }
-            },
-            Direction::Sell => {
-                if book.ask.price > price && book.ask.player_name != self.name {
-                    trade = true;
+        let spades_book = self.spades_book.lock().await.clone();
+        let clubs_book = self.clubs_book.lock().await.clone();
+        let diamonds_book = self.diamonds_book.lock().await.clone();
+        let hearts_book = self.hearts_book.lock().await.clone();
+
+        log!(LogLevel::Trace, "{:?} |:| Market\n{}", self.name, render_market(&MarketState { spades: spades_book.clone(), clubs: clubs_book.clone(), diamonds: diamonds_book.clone(), hearts: hearts_book.clone(), inventory }));
+
+        // only buy lowest card we were dealt and aggressively sell everything else
+        let goal_suit = self.highest_card.lock().await.clone();
+        for card in [Card::Spade, Card::Club, Card::Diamond, Card::Heart].iter() {
+            if *card != goal_suit {
+                // sell 
+                let (book, current_inventory) = match card {
+                    Card::Spade => (spades_book.clone(), inventory.spades),
+                    Card::Club => (clubs_book.clone(), inventory.clubs),
+                    Card::Diamond => (diamonds_book.clone(), inventory.diamonds),
+                    Card::Heart => (hearts_book.clone(), inventory.hearts),
+                };
+
+                if current_inventory > 0 {
+                    if seconds_left > 30 {
+                        if let Some(ask) = book.ask().filter(|ask| ask.price > 4) {
+                            self.send_order(ask.price - 1, Direction::Sell, card, &book, 1).await?;
+                        }
+                    } else {
+                        self.send_order(3, Direction::Sell, card, &book, 1).await?; // 3 since this is the break-point between a blanket buy-all strategy becoming profitable buying up equal amounts of inventory at 3
+                    }
                 }
             }
         }
-        
-        if trade {
+
+        let book = match goal_suit {
+            Card::Spade => spades_book,
+            Card::Club => clubs_book,
+            Card::Diamond => diamonds_book,
+            Card::Heart => hearts_book,
+        };
+
+        if let Some(ask) = book.ask().filter(|ask| ask.price <= 5) {
+            // send market buy
+            self.send_order(ask.price, Direction::Buy, &goal_suit, &book, 1).await?;
+        }
+
+        let bid_price = book.bid().map_or(0, |bid| bid.price);
+        if bid_price < 8 {
+            self.send_order(bid_price + 1, Direction::Buy, &goal_suit, &book, 1).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn send_order(&self, price: usize, direction: Direction, card: &Card, book: &Book, size: usize) -> Result<(), PlayerError> {
+
+        if book.is_actionable(&self.name, price, &direction) {
             let order = Order {
                 player_name: self.name.clone(),
                 price,
                 direction,
                 card: card.clone(),
+                size,
+                tif: TimeInForce::GTC,
+                context: None,
+                layer: false,
+                paired: None,
+                seq: 0, // we don't track acks
             };
     
-            if self.verbose {
-                println!("{:?} |:| Sending order: {:?}", self.name, order);
-            }
+            log!(LogLevel::Debug, "{:?} |:| Sending order: {:?}", self.name, order);
     
-            if let Err(e) = self.order_sender.send(order).await {
-                println!("[!] {:?} |:| Error sending order: {:?}", self.name, e);
-            }
+            self.order_sender.send(order).await?;
         }
-        
+        Ok(())
     }
 
 
@@ -181,17 +176,33 @@ impl TiltInventory {
         let trading: Arc<AtomicBool> = self.trading.clone();
 
         let name: PlayerName = self.name.clone();
-        let verbose: bool = self.verbose;
         let timer = self.timer.clone();
         let highest_card = self.highest_card.clone();
-        
-        tokio::task::spawn(async move {
+
+        let listener = tokio::task::spawn(async move {
             loop {
 
-                let event = event_receiver.recv().await.unwrap();
+                let event = match event_receiver.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        log!(LogLevel::Warn, "[!] {:?} |:| Fell behind, skipped {} events", name, skipped);
+                        continue;
+                    },
+                    Err(RecvError::Closed) => {
+                        trading.store(false, Ordering::Release);
+                        break;
+                    }
+                };
+                let event = match event {
+                    Event::Diff(diff) => {
+                        let books = [spades_book.lock().await.clone(), clubs_book.lock().await.clone(), diamonds_book.lock().await.clone(), hearts_book.lock().await.clone()];
+                        Event::Update(diff.apply(&books))
+                    },
+                    event => event,
+                };
                 match event {
                     Event::Update(update) => {
-                        if let Some(trade) = update.trade { // push trade for historical reasons (if we want to analyze) & update inventory
+                        for trade in update.trades { // push trade for historical reasons (if we want to analyze) & update inventory
                             let mut trade_lock = trades.lock().await;
                             trade_lock.push(trade.clone());
 
@@ -217,9 +228,9 @@ impl TiltInventory {
                         *hearts_lock = update.hearts;
 
                     }
-                    Event::DealCards(players_inventory) => {
+                    Event::DealCards { inventories, .. } => {
                         let mut inventory_lock = inventory.lock().await;
-                        *inventory_lock = players_inventory.get(&name).unwrap().clone();
+                        *inventory_lock = *inventories.get(&name).unwrap();
 
                         // doesn't take into account ties for lowest card
                         let mut highest = (Card::Spade, 0);
@@ -238,21 +249,22 @@ impl TiltInventory {
                         let goal_suit = highest.0.get_goal_suit();
                         *highest_card.lock().await = goal_suit;
                         
-                        if verbose {
-                            println!("{}[+] {:?} |:| Received cards: {:?}{}", CL::DullGreen.get(), name, inventory_lock, CL::End.get());
-                        }
+                        log!(LogLevel::Debug, CL::DullGreen, "[+] {:?} |:| Received cards: {}", name, inventory_lock);
                         
                         trading.store(true, Ordering::Release);
                         let mut timer_lock = timer.lock().await;
                         *timer_lock = Instant::now();
                     },
-                    Event::EndRound => {
+                    Event::EndRound(result) => {
                         trading.store(false, Ordering::Release);
-                    }
+                        log!(LogLevel::Debug, CL::DullGreen, "[+] {:?} |:| Round settled: {} (goal suit: {:?}, pot: {})", name, settle_round(&*inventory.lock().await, &result), result.goal_suit, result.pot);
+                    },
+                    Event::OrderRejected { .. } | Event::Snapshot { .. } | Event::AccountUpdate { .. } | Event::Diff(_) | Event::Heartbeat { .. } | Event::OrderAck { .. } => {}
                 }
 
             }
         });
+        watch_listener(self.name.clone(), listener, self.trading.clone(), None);
     }
 
 }
\ No newline at end of file
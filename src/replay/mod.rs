@@ -0,0 +1,178 @@
+use super::{Event, FileHandler, CL};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::Sender;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::watch;
+use tokio::io::AsyncBufReadExt;
+use std::io;
+use std::sync::Arc;
+use std::time::Instant;
+
+
+// one line of an event log, `elapsed_ms` is measured from when recording started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub elapsed_ms: u64,
+    pub event: Event,
+}
+
+
+// subscribes to the game's events and appends each one as a json line until the sender is dropped
+pub async fn record(path: &str, sender: Sender<Event>) -> io::Result<()> {
+    let mut file_handler = FileHandler::new(path)?;
+    let mut event_receiver = sender.subscribe();
+    drop(sender); // don't keep the channel alive ourselves
+
+    let start = Instant::now();
+    loop {
+        match event_receiver.recv().await {
+            Ok(event) => {
+                let recorded = RecordedEvent {
+                    elapsed_ms: start.elapsed().as_millis() as u64,
+                    event,
+                };
+                match serde_json::to_string(&recorded) {
+                    Ok(line) => file_handler.write_line(line)?,
+                    Err(e) => println!("{}[!] Recorder |:| Error serializing event: {:?}{}", CL::Red.get(), e, CL::End.get()),
+                }
+            },
+            Err(RecvError::Lagged(skipped)) => {
+                println!("{}[!] Recorder |:| Fell behind, {} events missing from the log{}", CL::Orange.get(), skipped, CL::End.get());
+            },
+            Err(RecvError::Closed) => break,
+        }
+    }
+    Ok(())
+}
+
+
+#[derive(Debug, Clone, Copy)]
+struct Playback {
+    paused: bool,
+    speed: f32, // recorded delays are divided by this, infinity sends everything back to back
+    steps: u64, // how many single steps have been asked for since the replay started
+}
+
+// drives a replay from outside while it runs: pause it, step it one event at a time, or change how fast it plays
+// e.g. to see why pick_off fired, start paused with the player subscribed, then step() through the Updates and watch what
+// comes out of its order channel after each one
+#[derive(Debug, Clone)]
+pub struct ReplayControl {
+    playback: Arc<watch::Sender<Playback>>,
+}
+
+impl ReplayControl {
+    pub fn new(speed: f32) -> Self { // playing from the start
+        let (playback, _) = watch::channel(Playback { paused: false, speed, steps: 0 });
+        Self { playback: Arc::new(playback) }
+    }
+
+    pub fn paused() -> Self { // nothing goes out until play() or step()
+        let control = Self::new(1.0);
+        control.pause();
+        control
+    }
+
+    pub fn play(&self) {
+        self.playback.send_modify(|playback| playback.paused = false);
+    }
+
+    pub fn pause(&self) {
+        self.playback.send_modify(|playback| playback.paused = true);
+    }
+
+    pub fn step(&self) { // sends the next event straight away, then stays paused
+        self.playback.send_modify(|playback| {
+            playback.paused = true;
+            playback.steps += 1;
+        });
+    }
+
+    pub fn set_speed(&self, speed: f32) {
+        if speed.is_nan() || speed <= 0.0 {
+            println!("{}[!] Replay |:| Ignoring speed {}, pause() is the way to stop{}", CL::Orange.get(), speed, CL::End.get());
+            return;
+        }
+        self.playback.send_modify(|playback| playback.speed = speed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.playback.borrow().paused
+    }
+
+    // waits until the next event may go out, Some(speed) to space it out by when playing, None when it's a single step
+    async fn next_turn(&self, receiver: &mut watch::Receiver<Playback>, stepped: &mut u64) -> Option<f32> {
+        loop {
+            let playback = *receiver.borrow_and_update();
+            if playback.steps > *stepped {
+                *stepped += 1;
+                return None;
+            }
+            if !playback.paused {
+                return Some(playback.speed);
+            }
+            if receiver.changed().await.is_err() {
+                return Some(playback.speed); // can't happen while we hold the sender, but don't hang if it does
+            }
+        }
+    }
+}
+
+
+// rebroadcasts a recorded json-lines log, with the original spacing between events when `realtime` is set
+// or as fast as possible otherwise, returns how many events were sent
+pub async fn replay(path: &str, sender: Sender<Event>, realtime: bool) -> io::Result<usize> {
+    let speed = if realtime { 1.0 } else { f32::INFINITY };
+    replay_with(path, sender, &ReplayControl::new(speed)).await
+}
+
+// replay under a ReplayControl, the recorded gaps between events are scaled by its speed and skipped when stepping
+// DealCards/EndRound go out exactly where they were recorded, so the players' trading flag toggles the same way it did live
+pub async fn replay_with(path: &str, sender: Sender<Event>, control: &ReplayControl) -> io::Result<usize> {
+    let file = tokio::fs::File::open(path).await?;
+    let mut lines = tokio::io::BufReader::new(file).lines();
+    let mut playback = control.playback.subscribe();
+    let mut stepped = 0;
+
+    let mut previous_ms = 0;
+    let mut sent = 0;
+    let mut in_round = false;
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let recorded: RecordedEvent = match serde_json::from_str(&line) {
+            Ok(recorded) => recorded,
+            Err(e) => {
+                println!("{}[!] Replay |:| Skipping malformed line: {:?}{}", CL::Red.get(), e, CL::End.get());
+                continue;
+            }
+        };
+
+        if let Some(speed) = control.next_turn(&mut playback, &mut stepped).await {
+            if recorded.elapsed_ms > previous_ms {
+                let delay = (recorded.elapsed_ms - previous_ms) as f32 / 1000.0 / speed;
+                let delay = tokio::time::Duration::try_from_secs_f32(delay).unwrap_or(tokio::time::Duration::MAX); // a crawl rather than a panic at tiny speeds
+                tokio::time::sleep(delay).await;
+            }
+        }
+        previous_ms = recorded.elapsed_ms;
+
+        match recorded.event {
+            Event::DealCards { .. } => in_round = true,
+            Event::EndRound(_) => in_round = false,
+            _ => {}
+        }
+
+        if let Err(e) = sender.send(recorded.event) {
+            println!("{}[!] Replay |:| No one is listening: {:?}{}", CL::Red.get(), e, CL::End.get());
+        }
+        sent += 1;
+    }
+
+    if in_round {
+        println!("{}[!] Replay |:| Log ended mid-round, players will keep trading until the next DealCards/EndRound{}", CL::Orange.get(), CL::End.get());
+    }
+    Ok(sent)
+}
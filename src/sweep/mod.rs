@@ -0,0 +1,84 @@
+use super::{Order, Event, CL, PlayerName, Strategy, StrategyConfig, MatchMaker, Deal, spawn_player, STARTING_BALANCE};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::sync::Arc;
+
+
+// one config's showing over the whole set of deals, from the point of view of the seat being tuned
+#[derive(Debug, Clone)]
+pub struct SweepResult {
+    pub config: StrategyConfig,
+    pub pnls: Vec<i64>, // the seat's net on each deal, in the order they were played
+    pub mean_pnl: f32,
+    pub variance: f32,
+}
+
+impl SweepResult {
+    pub fn std_dev(&self) -> f32 {
+        self.variance.sqrt()
+    }
+}
+
+
+// the same `count` deals for every config, so they're compared on the cards rather than on luck
+pub fn draw_deals(count: usize, player_names: &[PlayerName], seed: u64) -> Vec<Deal> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..count).map(|_| Deal::draw(player_names, &mut rng)).collect()
+}
+
+// the Seller's reservation curve over a grid of (open, close) pairs, close never above open, everything else as in `base`
+pub fn seller_grid(base: &StrategyConfig) -> Vec<StrategyConfig> {
+    let mut configs = Vec::new();
+    for open in 4..=8 {
+        for close in 1..=open.min(5) {
+            let mut config = base.clone();
+            config.seller.reservation_open = open;
+            config.seller.reservation_close = close;
+            configs.push(config);
+        }
+    }
+    configs
+}
+
+// plays `table` through every deal once per config, seated and seeded the same way each time, and measures `subject`
+// a full game per config, so this takes configs * deals * (round_seconds + the deal pauses) of wall clock
+// best mean first, ties broken by the steadier config
+pub async fn sweep(configs: Vec<StrategyConfig>, deals: Vec<Deal>, table: &[(PlayerName, Strategy)], subject: &PlayerName, round_seconds: u64, seed: u64) -> Vec<SweepResult> {
+    let player_names: Vec<PlayerName> = table.iter().map(|(player_name, _)| player_name.clone()).collect();
+    let mut results = Vec::new();
+
+    for (idx, config) in configs.into_iter().enumerate() {
+        println!("{}[+] Sweep |:| Config {} of the grid, {} deals{}", CL::DimLightBlue.get(), idx + 1, deals.len(), CL::End.get());
+
+        let (order_sender, order_receiver) = kanal::unbounded_async::<Order>();
+        let order_sender = Arc::new(order_sender);
+        let (snapshot_sender, snapshot_receiver) = kanal::unbounded_async::<PlayerName>();
+        let snapshot_sender = Arc::new(snapshot_sender);
+        let (event_sender, _) = tokio::sync::broadcast::channel::<Event>(100);
+
+        let players: Vec<tokio::task::JoinHandle<()>> = table.iter().enumerate().map(|(seat, (player_name, strategy))| {
+            let seed = seed.wrapping_add(seat as u64 + 1); // the same per-seat draws main uses for a seeded game
+            spawn_player(player_name.clone(), *strategy, round_seconds, Some(seed), config.clone(), event_sender.clone(), Arc::clone(&order_sender), Arc::clone(&snapshot_sender), None, STARTING_BALANCE)
+        }).collect();
+        drop(order_sender);
+        drop(snapshot_sender);
+
+        let mut match_maker = MatchMaker::new(STARTING_BALANCE, player_names.clone(), event_sender, Arc::new(order_receiver), Arc::new(snapshot_receiver));
+        match_maker.max_rounds = Some(deals.len() as u32);
+        match_maker.round_seconds = round_seconds;
+        match_maker.between_rounds = tokio::time::Duration::ZERO;
+        match_maker.exchange.ladders = config.ladder;
+        match_maker.deals = deals.iter().cloned().collect();
+        match_maker.start().await;
+
+        for player in players {
+            player.abort(); // TiltInventory never notices the game ending on its own
+        }
+
+        let standing = match_maker.leaderboard.standings.get(subject).cloned().unwrap_or_default();
+        results.push(SweepResult { config, mean_pnl: standing.average(), variance: standing.variance(), pnls: standing.history });
+    }
+
+    results.sort_by(|a, b| b.mean_pnl.total_cmp(&a.mean_pnl).then(a.variance.total_cmp(&b.variance)));
+    results
+}
@@ -0,0 +1,55 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+
+// where the players get the time from, so anything that depends on seconds_left can be driven by hand
+// sleeping still goes through tokio::time::sleep, which tokio::time::pause() already takes control of
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+
+    fn since(&self, earlier: Instant) -> Duration { // zero if `earlier` is somehow in our future
+        self.now().saturating_duration_since(earlier)
+    }
+}
+
+
+// tokio's clock rather than std's, so a paused runtime freezes this too
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+}
+
+
+// stands still until told to move
+#[derive(Debug)]
+pub struct ManualClock {
+    start: Instant,
+    offset: Mutex<Duration>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self { start: Instant::now(), offset: Mutex::new(Duration::ZERO) }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut offset = self.offset.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *offset += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + *self.offset.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
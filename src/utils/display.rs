@@ -0,0 +1,55 @@
+use crate::{Book, Card, Direction, Inventory, CL};
+use crate::player::MarketState;
+use std::fmt;
+
+
+// =-= Display =-= //
+// how books and hands read in the logs, the same colors the exchange prints its books in: bids green, asks red, prints light blue
+
+impl fmt::Display for Book {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { // best of each side, '-' when nobody's there
+        let side = |level: Option<&crate::Level>, color: CL| match level {
+            Some(level) => format!("{}{}{} ({:?}, {}x)", color.get(), level.price, CL::Dull.get(), level.player_name, level.size),
+            None => format!("{}-", CL::Dull.get()),
+        };
+        write!(f, "{}Bid: {} {}| Ask: {} {}| Last: {}{}", CL::Dull.get(), side(self.bid(), CL::Green), CL::Dull.get(), side(self.ask(), CL::PeachRed), CL::Dull.get(), last_print(self), CL::End.get())
+    }
+}
+
+impl fmt::Display for Inventory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Spades: {} | Clubs: {} | Diamonds: {} | Hearts: {}", self.spades, self.clubs, self.diamonds, self.hearts)
+    }
+}
+
+fn last_print(book: &Book) -> String { // price, then whoever crossed the spread: ^ a buyer lifted, v a seller hit
+    match &book.last_trade {
+        Some(print) => {
+            let arrow = if print.aggressor == Direction::Sell { "v" } else { "^" };
+            format!("{}{}{}{}", CL::DimLightBlue.get(), print.price, arrow, CL::Dull.get())
+        },
+        None => String::from("-"),
+    }
+}
+
+// all four suits one row each, the columns padded before the colors go on so they stay lined up on a terminal
+pub fn render_market(market: &MarketState) -> String {
+    let cell = |value: Option<usize>, color: CL| match value {
+        Some(value) => format!("{}{:>5}{}", color.get(), value, CL::Dull.get()),
+        None => format!("{:>5}", "-"),
+    };
+    let mut table = format!("{}Suit     |   Bid |   Ask |  Last | Volume | Held", CL::Dull.get());
+    let books = market.books();
+    for (card, book) in [Card::Spade, Card::Club, Card::Diamond, Card::Heart].iter().zip(books.iter()) {
+        table += &format!(
+            "\n{:<8} | {} | {} | {} | {:>6} | {:>4}",
+            format!("{:?}", card),
+            cell(book.bid().map(|bid| bid.price), CL::Green),
+            cell(book.ask().map(|ask| ask.price), CL::PeachRed),
+            cell(book.last_price(), CL::DimLightBlue),
+            book.volume_since_deal,
+            market.inventory.get(card),
+        );
+    }
+    table + CL::End.get()
+}
@@ -0,0 +1,73 @@
+use super::CL;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+
+// how chatty the table is, anything below the current level is dropped
+// - Trace: per-tick state, e.g. every player's inventory each loop
+// - Debug: what a player decided and why, orders sent, hands dealt, rejections
+// - Info: results worth reading in a normal run, settlements and P&L
+// - Warn: something went sideways but we carried on
+// - Error: something is broken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn color(&self) -> CL { // used when a log line doesn't pick its own
+        match self {
+            LogLevel::Trace => CL::Dull,
+            LogLevel::Debug => CL::End,
+            LogLevel::Info => CL::DullGreen,
+            LogLevel::Warn => CL::Orange,
+            LogLevel::Error => CL::Red,
+        }
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        match level.trim().to_lowercase().as_str() {
+            "trace" => Ok(LogLevel::Trace),
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            other => Err(format!("unknown log level '{}'", other)),
+        }
+    }
+}
+
+
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+pub fn set_level(level: LogLevel) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn enabled(level: LogLevel) -> bool {
+    level as u8 >= LEVEL.load(Ordering::Relaxed)
+}
+
+
+// log!(LogLevel::Warn, "...", args) prints in the level's color, log!(LogLevel::Info, CL::Green, "...", args) picks its own
+macro_rules! log {
+    ($level:expr, $fmt:literal $(, $arg:expr)* $(,)?) => {
+        if $crate::utils::logging::enabled($level) {
+            println!("{}{}{}", $level.color().get(), format_args!($fmt $(, $arg)*), $crate::utils::CL::End.get());
+        }
+    };
+    ($level:expr, $color:expr, $fmt:literal $(, $arg:expr)* $(,)?) => {
+        if $crate::utils::logging::enabled($level) {
+            println!("{}{}{}", $color.get(), format_args!($fmt $(, $arg)*), $crate::utils::CL::End.get());
+        }
+    };
+}
+pub(crate) use log;
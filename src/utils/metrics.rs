@@ -0,0 +1,103 @@
+use crate::PlayerName;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+
+
+// =-= Metrics =-= //
+// per-player counters for tuning, everything is a relaxed atomic so bumping them never takes a lock
+#[derive(Debug, Default)]
+pub struct Metrics {
+    pub orders_sent: AtomicU64,
+    pub orders_dropped: AtomicU64, // held back by the rate limiter
+    pub orders_filled: AtomicU64, // units we were on either side of
+    pub trades_observed: AtomicU64, // every unit that printed, ours or not
+    pub pnl: AtomicI64, // realized P&L of the current round
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetricsSnapshot {
+    pub orders_sent: u64,
+    pub orders_dropped: u64,
+    pub orders_filled: u64,
+    pub trades_observed: u64,
+    pub pnl: i64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn order_sent(&self) {
+        self.orders_sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn order_dropped(&self) {
+        self.orders_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn trade_observed(&self, ours: bool) {
+        self.trades_observed.fetch_add(1, Ordering::Relaxed);
+        if ours {
+            self.orders_filled.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn set_pnl(&self, pnl: i64) {
+        self.pnl.store(pnl, Ordering::Relaxed);
+    }
+
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            orders_sent: self.orders_sent.load(Ordering::Relaxed),
+            orders_dropped: self.orders_dropped.load(Ordering::Relaxed),
+            orders_filled: self.orders_filled.load(Ordering::Relaxed),
+            trades_observed: self.trades_observed.load(Ordering::Relaxed),
+            pnl: self.pnl.load(Ordering::Relaxed),
+        }
+    }
+}
+
+
+type Series = (&'static str, &'static str, fn(&MetricsSnapshot) -> String); // name, type, value
+
+
+// prometheus text exposition format, one series per player
+pub fn render_prometheus(players: &[(PlayerName, Arc<Metrics>)]) -> String {
+    let series: [Series; 5] = [
+        ("figgie_orders_sent_total", "counter", |snapshot| snapshot.orders_sent.to_string()),
+        ("figgie_orders_dropped_total", "counter", |snapshot| snapshot.orders_dropped.to_string()),
+        ("figgie_orders_filled_total", "counter", |snapshot| snapshot.orders_filled.to_string()),
+        ("figgie_trades_observed_total", "counter", |snapshot| snapshot.trades_observed.to_string()),
+        ("figgie_pnl", "gauge", |snapshot| snapshot.pnl.to_string()),
+    ];
+
+    let snapshots: Vec<(&PlayerName, MetricsSnapshot)> = players.iter().map(|(player_name, metrics)| (player_name, metrics.metrics_snapshot())).collect();
+    let mut output = String::new();
+    for (name, kind, value) in series {
+        output += &format!("# TYPE {} {}\n", name, kind);
+        for (player_name, snapshot) in &snapshots {
+            output += &format!("{}{{player=\"{:?}\"}} {}\n", name, player_name, value(snapshot));
+        }
+    }
+    output
+}
+
+
+// bare-bones scrape endpoint, answers every request on `addr` with the current metrics
+#[cfg(feature = "metrics-http")]
+pub async fn serve_metrics(addr: &str, players: Vec<(PlayerName, Arc<Metrics>)>) -> std::io::Result<()> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    loop {
+        let (mut socket, _) = listener.accept().await?;
+        let body = render_prometheus(&players);
+        tokio::spawn(async move {
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await; // we don't route, any path gets the metrics
+            let response = format!("HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+            let _ = socket.write_all(response.as_bytes()).await;
+        });
+    }
+}
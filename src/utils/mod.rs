@@ -1,6 +1,24 @@
 use std::fs::OpenOptions;
 use std::io::{self, Write};
 
+pub mod trade_logger;
+pub use trade_logger::{TradeLogger, TradeSink};
+#[cfg(feature = "sqlite")]
+pub mod sqlite_logger;
+#[cfg(feature = "sqlite")]
+pub use sqlite_logger::SqliteLogger;
+pub mod metrics;
+pub use metrics::{Metrics, MetricsSnapshot};
+pub mod rate_limiter;
+pub use rate_limiter::RateLimiter;
+pub mod logging;
+pub use logging::LogLevel;
+pub mod clock;
+pub use clock::{Clock, SystemClock, ManualClock};
+pub mod display;
+pub use display::render_market;
+pub(crate) use logging::log;
+
 
 #[derive(Debug, Clone)]
 pub enum CL {
@@ -52,7 +70,6 @@ impl FileHandler {
     pub fn new(file_path: &str) -> io::Result<Self> {
         let file = OpenOptions::new()
             .create(true)
-            .write(true)
             .append(true)
             .open(file_path)?;
         Ok(Self { file })
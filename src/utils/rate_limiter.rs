@@ -0,0 +1,38 @@
+use std::time::Instant;
+
+
+// =-= RateLimiter =-= //
+// token bucket: refills at `max_per_sec`, holds at most one second's worth (but never less than one token, or a rate under 1/s
+// would never fill up to a whole one), so a burst can't go past that either
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    pub max_per_sec: f32,
+    tokens: f32,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(max_per_sec: f32) -> Self {
+        Self {
+            max_per_sec,
+            tokens: max_per_sec.max(1.0),
+            last_refill: Instant::now(),
+        }
+    }
+
+    pub fn try_acquire(&mut self) -> bool { // takes a token if there's one to take
+        let now = Instant::now();
+        self.tokens = (self.tokens + now.duration_since(self.last_refill).as_secs_f32() * self.max_per_sec).min(self.capacity());
+        self.last_refill = now;
+
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+
+    fn capacity(&self) -> f32 {
+        self.max_per_sec.max(1.0)
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{Trade, RoundReport};
+use super::TradeSink;
+use kanal::Sender;
+use rusqlite::{params, Connection};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+
+// one table of prints and one of round reports, times are unix ms as logged
+// a round's trades are the ones logged between its player's previous report and this one
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS trades (
+    id INTEGER PRIMARY KEY,
+    logged_at INTEGER NOT NULL,
+    buyer TEXT NOT NULL,
+    seller TEXT NOT NULL,
+    card TEXT NOT NULL,
+    price INTEGER NOT NULL,
+    aggressor TEXT NOT NULL,
+    round_ms INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS rounds (
+    id INTEGER PRIMARY KEY,
+    logged_at INTEGER NOT NULL,
+    player TEXT NOT NULL,
+    goal_suit TEXT NOT NULL,
+    spades INTEGER NOT NULL,
+    clubs INTEGER NOT NULL,
+    diamonds INTEGER NOT NULL,
+    hearts INTEGER NOT NULL,
+    num_trades INTEGER NOT NULL,
+    realized_pnl INTEGER NOT NULL,
+    goal_suit_held INTEGER NOT NULL
+);
+";
+
+enum Record {
+    Trade(Trade, u64),
+    Round(RoundReport, u64),
+}
+
+
+// =-= SqliteLogger =-= //
+// TradeLogger's queryable sibling: the same hand-off to a thread of its own, which writes whatever has queued up
+// since its last pass in one transaction, so a burst of prints costs one commit and the event loop never waits on the disk
+pub struct SqliteLogger {
+    sender: Sender<Record>,
+}
+
+impl SqliteLogger {
+    pub fn new(file_path: &str) -> rusqlite::Result<Self> {
+        let mut connection = Connection::open(file_path)?;
+        connection.execute_batch(SCHEMA)?;
+        let (sender, receiver) = kanal::unbounded::<Record>();
+
+        std::thread::spawn(move || {
+            // recv only errors once every sender (i.e. the logger) is dropped
+            while let Ok(first) = receiver.recv() {
+                let mut batch = vec![first];
+                while let Ok(Some(record)) = receiver.try_recv() {
+                    batch.push(record);
+                }
+                if let Err(e) = Self::write(&mut connection, &batch) {
+                    println!("[!] SqliteLogger |:| Error writing {} records: {:?}", batch.len(), e);
+                }
+            }
+        });
+
+        Ok(Self { sender })
+    }
+
+    fn write(connection: &mut Connection, batch: &[Record]) -> rusqlite::Result<()> {
+        let transaction = connection.transaction()?;
+        for record in batch {
+            match record {
+                Record::Trade(trade, logged_at) => {
+                    transaction.execute(
+                        "INSERT INTO trades (logged_at, buyer, seller, card, price, aggressor, round_ms) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        params![*logged_at as i64, format!("{:?}", trade.buyer), format!("{:?}", trade.seller), format!("{:?}", trade.card), trade.price as i64, format!("{:?}", trade.aggressor), trade.timestamp as i64],
+                    )?;
+                },
+                Record::Round(report, logged_at) => {
+                    let inventory = &report.final_inventory;
+                    transaction.execute(
+                        "INSERT INTO rounds (logged_at, player, goal_suit, spades, clubs, diamonds, hearts, num_trades, realized_pnl, goal_suit_held) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                        params![*logged_at as i64, format!("{:?}", report.player), format!("{:?}", report.goal_suit), inventory.spades as i64, inventory.clubs as i64, inventory.diamonds as i64, inventory.hearts as i64, report.num_trades as i64, report.realized_pnl, report.goal_suit_held as i64],
+                    )?;
+                },
+            }
+        }
+        transaction.commit()
+    }
+
+    fn queue(&self, record: Record) {
+        if let Err(e) = self.sender.send(record) {
+            println!("[!] SqliteLogger |:| Error queueing record: {:?}", e);
+        }
+    }
+}
+
+impl TradeSink for SqliteLogger {
+    fn log(&self, trade: &Trade) {
+        self.queue(Record::Trade(trade.clone(), now_ms()));
+    }
+
+    fn log_round(&self, report: &RoundReport) {
+        self.queue(Record::Round(report.clone(), now_ms()));
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_millis() as u64).unwrap_or(0)
+}
@@ -0,0 +1,87 @@
+use crate::{Trade, RoundReport};
+use kanal::Sender;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+
+// where a player hands every trade it sees, and its report at the end of each round, for keeping
+// TradeLogger (csv) and, with the `sqlite` feature, SqliteLogger both plug into the players' trade_logger
+pub trait TradeSink: Send + Sync {
+    fn log(&self, trade: &Trade);
+    fn log_round(&self, _report: &RoundReport) {} // the csv only has trades
+}
+
+
+// =-= TradeLogger =-= //
+// appends every trade it's handed to a csv file: timestamp,buyer,seller,card,price,aggressor,round_ms
+// the writing happens on its own thread fed by a channel, so logging never blocks the event loop
+pub struct TradeLogger {
+    sender: Sender<Trade>,
+}
+
+impl TradeLogger {
+    pub fn new(file_path: &str, max_rows: Option<usize>) -> io::Result<Self> { // max_rows: rotate to `<file_path>.1` once the file holds this many trades
+        let writer = Self::open(file_path)?;
+        let (sender, receiver) = kanal::unbounded::<Trade>();
+        let file_path = file_path.to_string();
+
+        std::thread::spawn(move || {
+            let mut writer = writer;
+            let mut rows = 0;
+
+            // recv only errors once every sender (i.e. the logger) is dropped
+            while let Ok(trade) = receiver.recv() {
+                if let Some(max_rows) = max_rows {
+                    if rows >= max_rows {
+                        let _ = writer.flush();
+                        let _ = std::fs::rename(&file_path, format!("{}.1", file_path));
+                        writer = match Self::open(&file_path) {
+                            Ok(writer) => writer,
+                            Err(e) => {
+                                println!("[!] TradeLogger |:| Error rotating {}: {:?}", file_path, e);
+                                return;
+                            }
+                        };
+                        rows = 0;
+                    }
+                }
+
+                let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_millis()).unwrap_or(0);
+                if let Err(e) = writeln!(writer, "{},{:?},{:?},{:?},{},{:?},{}", timestamp, trade.buyer, trade.seller, trade.card, trade.price, trade.aggressor, trade.timestamp) {
+                    println!("[!] TradeLogger |:| Error writing trade: {:?}", e);
+                }
+                rows += 1;
+
+                if receiver.is_empty() { // only hit the disk once we've caught up
+                    let _ = writer.flush();
+                }
+            }
+            let _ = writer.flush();
+        });
+
+        Ok(Self { sender })
+    }
+
+    fn open(file_path: &str) -> io::Result<BufWriter<File>> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(file_path)?;
+        let is_new = file.metadata()?.len() == 0;
+
+        let mut writer = BufWriter::new(file);
+        if is_new {
+            writeln!(writer, "timestamp,buyer,seller,card,price,aggressor,round_ms")?;
+        }
+        Ok(writer)
+    }
+}
+
+impl TradeSink for TradeLogger {
+    fn log(&self, trade: &Trade) {
+        if let Err(e) = self.sender.send(trade.clone()) {
+            println!("[!] TradeLogger |:| Error queueing trade: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use figgie_auto::{Book, Card, Direction, Event, EventDrivenPlayer, GenericPlayer, Level, LogLevel, MarketContext, Order, PlayerName, Strategy, StrategyConfig, TimeInForce};
+use figgie_auto::player::config::SpreadConfig;
+use std::sync::Arc;
+
+
+// nothing is sent anywhere, the decide_* functions only need a player to read its name, config and limits from
+fn generic(strategy: Strategy, config: StrategyConfig) -> GenericPlayer {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    let (event_sender, _) = tokio::sync::broadcast::channel::<Event>(1);
+    let (order_sender, _) = kanal::unbounded_async::<Order>();
+    GenericPlayer::new(PlayerName::new(&format!("{:?}", strategy)), strategy, 1000, 2000, Some(0), config, event_sender, Arc::new(order_sender))
+}
+
+fn event_driven(strategy: Strategy, config: StrategyConfig) -> EventDrivenPlayer {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    let (event_sender, _) = tokio::sync::broadcast::channel::<Event>(1);
+    let (order_sender, _) = kanal::unbounded_async::<Order>();
+    EventDrivenPlayer::new(PlayerName::new(&format!("{:?}", strategy)), strategy, config, event_sender, Arc::new(order_sender))
+}
+
+fn level(price: usize, player: &str) -> Level {
+    Level { price, player_name: PlayerName::new(player), size: 1 }
+}
+
+// (direction, price, tif) of each order, in the order they'd go out
+fn summary(orders: &[Order]) -> Vec<(Direction, usize, TimeInForce)> {
+    orders.iter().map(|order| (order.direction.clone(), order.price, order.tif.clone())).collect()
+}
+
+// nothing printed and nobody quoting: Spread quotes around default_fair (5) at its 2/1 offsets, PickOff has nothing to take
+#[test]
+fn an_empty_book_quotes_around_the_anchor_and_takes_nothing() {
+    let spread = generic(Strategy::Spread, StrategyConfig::default());
+    let orders = spread.decide_spread(&MarketContext::new(240, Card::Spade, Book::new(), 1));
+    assert_eq!(summary(&orders), vec![(Direction::Sell, 7, TimeInForce::GTC), (Direction::Buy, 4, TimeInForce::GTC)]);
+
+    let pick_off = event_driven(Strategy::PickOff, StrategyConfig::default());
+    let context = MarketContext { fair_value: Some(8.0), ..MarketContext::new(240, Card::Spade, Book::new(), 2) };
+    assert_eq!(summary(&pick_off.decide_pick_off(&context)), Vec::new());
+}
+
+// a cheap ask is taken, unless the bid is through it: a crossed snapshot is about to trade and there's nothing to pick off
+#[test]
+fn a_crossed_book_is_left_alone() {
+    let pick_off = event_driven(Strategy::PickOff, StrategyConfig::default());
+    let mut book = Book::new();
+    book.insert(&Direction::Sell, level(3, "Seller"));
+    let context = MarketContext { fair_value: Some(8.0), ..MarketContext::new(240, Card::Spade, book.clone(), 0) };
+    assert_eq!(summary(&pick_off.decide_pick_off(&context)), vec![(Direction::Buy, 3, TimeInForce::IOC)]);
+
+    book.insert(&Direction::Buy, level(6, "Buyer"));
+    let context = MarketContext { fair_value: Some(8.0), ..MarketContext::new(240, Card::Spade, book, 0) };
+    assert_eq!(summary(&pick_off.decide_pick_off(&context)), Vec::new());
+}
+
+// at the position limit (PickOff 3, Spread 8) neither buys any more, Spread still offers what it holds
+#[test]
+fn the_position_limit_stops_buying() {
+    let pick_off = event_driven(Strategy::PickOff, StrategyConfig::default());
+    let mut book = Book::new();
+    book.insert(&Direction::Sell, level(3, "Seller"));
+    let under = MarketContext { fair_value: Some(8.0), ..MarketContext::new(240, Card::Spade, book.clone(), 2) };
+    assert!(pick_off.decide_pick_off(&under).iter().any(|order| order.direction == Direction::Buy));
+    let at = MarketContext { fair_value: Some(8.0), ..MarketContext::new(240, Card::Spade, book, 3) };
+    assert!(pick_off.decide_pick_off(&at).iter().all(|order| order.direction != Direction::Buy));
+
+    let spread = generic(Strategy::Spread, StrategyConfig { spread: SpreadConfig { target_sensitivity: 0.0, ..Default::default() }, ..Default::default() });
+    let orders = spread.decide_spread(&MarketContext::new(240, Card::Spade, Book::new(), 7));
+    assert!(orders.iter().any(|order| order.direction == Direction::Buy));
+    let orders = spread.decide_spread(&MarketContext::new(240, Card::Spade, Book::new(), 8));
+    assert_eq!(summary(&orders), vec![(Direction::Sell, 7, TimeInForce::GTC)]);
+}
+
+// quotes priced off a print more than requote_ticks (2) from the last one are pulled before the new pair goes out
+#[test]
+fn a_moved_market_cancels_before_requoting() {
+    let spread = generic(Strategy::Spread, StrategyConfig::default());
+    let mut book = Book::new();
+    book.insert(&Direction::Buy, level(4, "Spread"));
+    book.insert(&Direction::Sell, level(7, "Spread"));
+    book.record_trade(9, Direction::Buy, 1);
+
+    let moved = MarketContext { quoted_at: Some(5), ..MarketContext::new(240, Card::Spade, book.clone(), 1) };
+    let orders = spread.decide_spread(&moved);
+    assert_eq!(summary(&orders), vec![(Direction::Cancel, 0, TimeInForce::GTC), (Direction::Sell, 11, TimeInForce::GTC), (Direction::Buy, 8, TimeInForce::GTC)]);
+
+    let close = MarketContext { quoted_at: Some(8), ..MarketContext::new(240, Card::Spade, book, 1) };
+    assert!(spread.decide_spread(&close).iter().all(|order| order.direction != Direction::Cancel));
+}
+
+// offsets wider than max_spread come in evenly to it (the odd tick off the ask), and with the cap off by default the close
+// doubles the 2/1 offsets around the anchor rather than being trimmed back
+#[test]
+fn max_spread_clamps_the_quotes_without_undoing_the_close() {
+    let spread = generic(Strategy::Spread, StrategyConfig { spread: SpreadConfig { ask_offset: 6, bid_offset: 4, max_spread: 4, ..Default::default() }, ..Default::default() });
+    let mut book = Book::new();
+    book.record_trade(10, Direction::Buy, 1);
+    let orders = spread.decide_spread(&MarketContext::new(240, Card::Spade, book, 1));
+    assert_eq!(summary(&orders), vec![(Direction::Sell, 13, TimeInForce::GTC), (Direction::Buy, 9, TimeInForce::GTC)]);
+
+    let spread = generic(Strategy::Spread, StrategyConfig::default());
+    let orders = spread.decide_spread(&MarketContext::new(1, Card::Spade, Book::new(), 1));
+    assert_eq!(summary(&orders), vec![(Direction::Sell, 9, TimeInForce::GTC), (Direction::Buy, 3, TimeInForce::GTC)]);
+}
+
+// holding past target_inventory keens the ask (and backs the bid off) by target_sensitivity a card, short of it the reverse
+#[test]
+fn above_target_inventory_the_ask_gets_aggressive() {
+    let spread = generic(Strategy::Spread, StrategyConfig { spread: SpreadConfig { target_inventory: [3, 0, 0, 0], target_sensitivity: 1.0, ..Default::default() }, ..Default::default() });
+    let quotes = |held: usize| summary(&spread.decide_spread(&MarketContext::new(240, Card::Spade, Book::new(), held)));
+    assert_eq!(quotes(3), vec![(Direction::Sell, 7, TimeInForce::GTC), (Direction::Buy, 4, TimeInForce::GTC)]);
+    assert_eq!(quotes(5), vec![(Direction::Sell, 5, TimeInForce::GTC), (Direction::Buy, 2, TimeInForce::GTC)]);
+    assert_eq!(quotes(1), vec![(Direction::Sell, 9, TimeInForce::GTC), (Direction::Buy, 6, TimeInForce::GTC)]);
+}
+
+// before a suit first prints Spread quotes around default_fair whoever else is quoting, after it the print takes over,
+// and with the anchor off (0) it's the mid of the book
+#[test]
+fn quotes_centre_on_the_anchor_until_the_first_print() {
+    let mut book = Book::new();
+    book.insert(&Direction::Buy, level(2, "Bidder"));
+    book.insert(&Direction::Sell, level(12, "Seller"));
+    let spread = generic(Strategy::Spread, StrategyConfig::default());
+    let orders = spread.decide_spread(&MarketContext::new(240, Card::Spade, book.clone(), 1));
+    assert_eq!(summary(&orders), vec![(Direction::Sell, 7, TimeInForce::GTC), (Direction::Buy, 4, TimeInForce::GTC)]);
+
+    let mut printed = book.clone();
+    printed.record_trade(9, Direction::Buy, 1);
+    let orders = spread.decide_spread(&MarketContext::new(240, Card::Spade, printed, 1));
+    assert_eq!(summary(&orders), vec![(Direction::Sell, 11, TimeInForce::GTC), (Direction::Buy, 8, TimeInForce::GTC)]);
+
+    let unanchored = generic(Strategy::Spread, StrategyConfig { spread: SpreadConfig { default_fair: 0, ..Default::default() }, ..Default::default() });
+    let orders = unanchored.decide_spread(&MarketContext::new(240, Card::Spade, book, 1));
+    assert_eq!(summary(&orders), vec![(Direction::Sell, 9, TimeInForce::GTC), (Direction::Buy, 6, TimeInForce::GTC)]);
+}
+
+// with improve_bid on, a bid that goes out is never under someone else's best: a tick over it while fair value allows,
+// matching it when fair only covers that, and no bid at all once even matching costs more than fair
+#[test]
+fn an_improved_bid_is_never_under_the_best_bid() {
+    let spread = generic(Strategy::Spread, StrategyConfig { spread: SpreadConfig { improve_bid: true, ..Default::default() }, ..Default::default() });
+    let mut book = Book::new();
+    book.insert(&Direction::Buy, level(6, "Bidder"));
+    let bid = |fair: f32| {
+        let context = MarketContext { fair_value: Some(fair), ..MarketContext::new(240, Card::Spade, book.clone(), 0) };
+        spread.decide_spread(&context).into_iter().find(|order| order.direction == Direction::Buy).map(|order| order.price)
+    };
+    assert_eq!(bid(9.0), Some(7));
+    assert_eq!(bid(6.5), Some(6));
+    assert_eq!(bid(5.5), None);
+    for tenths in 0..150 {
+        assert!(bid(tenths as f32 / 10.0).is_none_or(|price| price >= 6), "fair {}", tenths as f32 / 10.0);
+    }
+}
+
+// a config file only has to name what it changes: these offsets widen Spread's quotes around the anchor, everything else
+// (default_fair among it) keeps its default, and strategy.toml as shipped parses too
+#[test]
+fn a_toml_config_reaches_the_quotes() {
+    let config = StrategyConfig::from_toml("[spread]\nask_offset = 4\nbid_offset = 3\n").unwrap();
+    assert_eq!((config.spread.ask_offset, config.spread.default_fair), (4, 5));
+    let spread = generic(Strategy::Spread, config);
+    let orders = spread.decide_spread(&MarketContext::new(240, Card::Spade, Book::new(), 1));
+    assert_eq!(summary(&orders), vec![(Direction::Sell, 9, TimeInForce::GTC), (Direction::Buy, 2, TimeInForce::GTC)]);
+
+    assert!(StrategyConfig::from_toml("[spread]\nask_offset = \"wide\"\n").is_err());
+    assert!(StrategyConfig::load("strategy.toml").is_ok());
+}
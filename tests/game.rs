@@ -0,0 +1,148 @@
+use figgie_auto::{audit_reports, audit_round, spawn_player, Card, Deal, DealContext, Event, EventDrivenPlayer, Exchange, GenericPlayer, Inventory, LogLevel, MatchMaker, Order, PlayerName, Pot, RateLimiter, RoundReport, RoundResult, Strategy, StrategyConfig, DECK_SIZE, STARTING_BALANCE};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+
+const SEED: u64 = 7;
+const ROUND_SECONDS: u64 = 3;
+const SUITS: [Card; 4] = [Card::Spade, Card::Club, Card::Diamond, Card::Heart];
+
+// one seeded round at a full table run to EndRound in-process, then checked from both sides: the exchange's books have to
+// conserve cards and points and pay out on the deal's goal suit, and every player's own P&L has to agree with the exchange
+// (TiltInventory sends no reports, so it sits this one out)
+#[tokio::test(flavor = "multi_thread")]
+async fn seeded_round_conserves_cards_and_points() {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+
+    let table = [Strategy::Spread, Strategy::Seller, Strategy::Noisy, Strategy::PickOff];
+    let players: Vec<PlayerName> = table.iter().map(|strategy| PlayerName::new(&format!("{:?}", strategy))).collect();
+    let deal = Deal::draw(&players, &mut StdRng::seed_from_u64(SEED));
+    let dealt = deal.inventories.clone();
+
+    let (order_sender, order_receiver) = kanal::unbounded_async::<Order>();
+    let order_sender = Arc::new(order_sender);
+    let (snapshot_sender, snapshot_receiver) = kanal::unbounded_async::<PlayerName>();
+    let snapshot_sender = Arc::new(snapshot_sender);
+    let (report_sender, report_receiver) = kanal::unbounded_async::<RoundReport>();
+    let report_sender = Arc::new(report_sender);
+    let (event_sender, _) = tokio::sync::broadcast::channel::<Event>(100);
+
+    for (seat, (player_name, strategy)) in players.iter().zip(table).enumerate() {
+        spawn_player(player_name.clone(), strategy, ROUND_SECONDS, Some(SEED + seat as u64 + 1), StrategyConfig::default(), event_sender.clone(), Arc::clone(&order_sender), Arc::clone(&snapshot_sender), Some(Arc::clone(&report_sender)), STARTING_BALANCE);
+    }
+    drop(order_sender);
+    drop(snapshot_sender);
+    drop(report_sender);
+
+    let mut match_maker = MatchMaker::new(STARTING_BALANCE, players.clone(), event_sender, Arc::new(order_receiver), Arc::new(snapshot_receiver));
+    match_maker.max_rounds = Some(1);
+    match_maker.round_seconds = ROUND_SECONDS;
+    match_maker.between_rounds = tokio::time::Duration::ZERO;
+    match_maker.deals.push_back(deal);
+    let points_before = match_maker.exchange.player_points.clone();
+    match_maker.start().await;
+
+    let result = RoundResult {
+        goal_suit: match_maker.goal_suit.clone(),
+        pot: Pot::new(players.len()).ante * players.len(),
+        inventories: match_maker.exchange.player_inventories.clone(),
+    };
+    let points_after = match_maker.exchange.player_points.clone();
+
+    // inventories are usize and the exchange saturates, so a card sold from an empty hand shows up as one missing from the deck
+    let held: usize = result.inventories.values().map(|inventory| inventory.spades + inventory.clubs + inventory.diamonds + inventory.hearts).sum();
+    assert_eq!(held, DECK_SIZE);
+    assert_eq!(audit_round(&dealt, &result, &points_before, &points_after), Vec::new());
+
+    let mut reports: HashMap<PlayerName, RoundReport> = HashMap::new();
+    while reports.len() < players.len() {
+        match tokio::time::timeout(tokio::time::Duration::from_secs(5), report_receiver.recv()).await {
+            Ok(Ok(report)) => {
+                reports.insert(report.player.clone(), report);
+            },
+            _ => panic!("only {} of {} players reported", reports.len(), players.len()),
+        }
+    }
+    let reports: Vec<RoundReport> = reports.into_values().collect();
+    assert!(reports.iter().any(|report| report.num_trades > 0), "nothing traded, the round checked nothing");
+    assert_eq!(audit_reports(&reports, &result, &points_before, &points_after), Vec::new());
+}
+
+// Fade against Noisy alone, run in lockstep so a seed always plays out the same: every Noisy tick goes through the exchange,
+// then Fade looks at each book once. Fade's take is what the exchange paid it plus its payout at the end, less the payout
+// the hand it was dealt would have got standing pat
+#[tokio::test]
+async fn fade_beats_noisy_over_seeded_rounds() {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    const TICKS: usize = 300;
+
+    let players: Vec<PlayerName> = ["Noisy", "Fade", "Idle#1", "Idle#2"].iter().map(|name| PlayerName::new(name)).collect();
+    let (noisy_name, fade_name) = (players[0].clone(), players[1].clone());
+    let mut edge = 0;
+    let mut fills = 0;
+    for seed in 0..10 {
+        let deal = Deal::draw(&players, &mut StdRng::seed_from_u64(seed));
+        let (event_sender, _events) = tokio::sync::broadcast::channel::<Event>(1024); // rejections go out here, nobody reads them
+        let (_order_sender, order_receiver) = kanal::unbounded_async::<Order>();
+        let (_snapshot_sender, snapshot_receiver) = kanal::unbounded_async::<PlayerName>();
+        let mut exchange = Exchange::new(STARTING_BALANCE, players.clone(), event_sender.clone(), Arc::new(order_receiver), Arc::new(snapshot_receiver));
+        exchange.player_inventories = deal.inventories.clone();
+
+        let (noisy_sender, noisy_orders) = kanal::unbounded_async::<Order>();
+        let noisy = GenericPlayer::new(noisy_name.clone(), Strategy::Noisy, 10, 10, None, StrategyConfig::default(), event_sender.clone(), Arc::new(noisy_sender));
+        noisy.market.write().await.inventory = deal.inventories[&noisy_name];
+
+        let (fade_sender, fade_orders) = kanal::unbounded_async::<Order>();
+        let mut fade = EventDrivenPlayer::new(fade_name.clone(), Strategy::Fade, StrategyConfig::default(), event_sender, Arc::new(fade_sender));
+        fade.rate_limiter = RateLimiter::new(1_000_000.0); // the lockstep loop runs far faster than any real feed
+        fade.inventory = deal.inventories[&fade_name];
+        fade.deal = DealContext::from_hand(fade.inventory);
+        fade.estimator.reset(&fade.inventory);
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        for _ in 0..TICKS {
+            let books = |exchange: &Exchange| SUITS.map(|card| exchange.books[&card].clone());
+            let [spades, clubs, diamonds, hearts] = books(&exchange);
+            let inventory = noisy.market.read().await.inventory;
+            noisy.noisy_trader(inventory, spades, clubs, diamonds, hearts, &mut rng).await.unwrap();
+            let mut trades = match noisy_orders.try_recv().unwrap() {
+                Some(order) => exchange.process_order(order).unwrap_or_default(),
+                None => Vec::new(),
+            };
+
+            let posterior = fade.estimator.posterior();
+            for (index, (card, book)) in SUITS.iter().zip(books(&exchange)).enumerate() {
+                fade.fade(240, fade.inventory.get(card), book, card.clone(), posterior[index]).await.unwrap();
+            }
+            while let Some(order) = fade_orders.try_recv().unwrap() {
+                trades.extend(exchange.process_order(order).unwrap_or_default());
+            }
+            fade.outstanding.clear(); // all IOC, nothing of Fade's is left resting
+
+            if trades.is_empty() {
+                continue;
+            }
+            exchange.reset_books();
+            noisy.outstanding.lock().await.clear();
+            noisy.market.write().await.inventory = exchange.player_inventories[&noisy_name];
+            fade.inventory = exchange.player_inventories[&fade_name];
+            for trade in trades {
+                fills += usize::from(trade.buyer == fade_name || trade.seller == fade_name);
+                fade.pnl.record(&trade, &fade_name);
+                fade.estimator.observe_trade(&trade);
+                fade.trades.push(trade);
+            }
+            fade.rejected.clear();
+        }
+
+        let result = |inventories: HashMap<PlayerName, Inventory>| RoundResult { goal_suit: deal.goal_suit.clone(), pot: Pot::new(players.len()).ante * players.len(), inventories };
+        let (dealt, settled) = (result(deal.inventories.clone()), result(exchange.player_inventories.clone()));
+        let cash = exchange.player_points[&fade_name] as i64 - STARTING_BALANCE as i64;
+        assert_eq!(cash, fade.pnl.realized, "seed {}", seed);
+        edge += cash + settled.get_payout(&settled.inventories[&fade_name]) as i64 - dealt.get_payout(&dealt.inventories[&fade_name]) as i64;
+    }
+    assert!(fills > 0, "Fade never traded");
+    assert!(edge > 0, "Fade came out {} behind standing pat", -edge);
+}
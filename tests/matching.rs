@@ -0,0 +1,291 @@
+use figgie_auto::{Card, Deal, Direction, Event, Exchange, Inventory, LogLevel, Order, PlayerName, PriceLadder, RejectReason, TimeInForce};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
+
+const SUITS: [Card; 4] = [Card::Spade, Card::Club, Card::Diamond, Card::Heart];
+const ORDERS_PER_SEED: usize = 20_000;
+
+fn exchange(player_names: &[PlayerName]) -> Exchange {
+    let (event_sender, _) = tokio::sync::broadcast::channel::<Event>(16); // room for a test that subscribes to read a few rejections
+    let (_, order_receiver) = kanal::unbounded_async::<Order>();
+    let (_, snapshot_receiver) = kanal::unbounded_async::<PlayerName>();
+    Exchange::new(500, player_names.to_vec(), event_sender, Arc::new(order_receiver), Arc::new(snapshot_receiver))
+}
+
+// anything a well-formed player could send: limit orders of every time in force, cancels and the odd two-sided quote
+fn random_order(rng: &mut StdRng, player_names: &[PlayerName]) -> Order {
+    let player_name = player_names[rng.gen_range(0..player_names.len())].clone();
+    let card = SUITS[rng.gen_range(0..4)].clone();
+    let roll = rng.gen_range(0..20);
+    if roll == 0 {
+        return Order::new(player_name, 0, Direction::Cancel, card, 0);
+    }
+    if roll == 1 {
+        let bid = rng.gen_range(1..10);
+        let mut quote = Order::new(player_name.clone(), bid, Direction::Buy, card.clone(), 1);
+        quote.paired = Some(Box::new(Order::new(player_name, bid + rng.gen_range(1..5), Direction::Sell, card, 1)));
+        return quote;
+    }
+    let direction = if roll % 2 == 0 { Direction::Buy } else { Direction::Sell };
+    let tif = match rng.gen_range(0..4) {
+        0 => TimeInForce::IOC,
+        1 => TimeInForce::FOK,
+        _ => TimeInForce::GTC,
+    };
+    Order { tif, ..Order::new(player_name, rng.gen_range(1..15), direction, card, rng.gen_range(1..4)) }
+}
+
+// what must hold after every order, whatever it was: None when it all does
+fn violation(exchange: &Exchange, dealt: &Inventory, cash: usize) -> Option<String> {
+    if let Some((card, book)) = exchange.books.iter().find(|(_, book)| book.is_crossed()) {
+        return Some(format!("{:?} book crossed, bid {:?} ask {:?}", card, book.bid(), book.ask()));
+    }
+    for card in SUITS.iter() {
+        let held: usize = exchange.player_inventories.values().map(|inventory| inventory.get(card)).sum();
+        if held != dealt.get(card) {
+            return Some(format!("{:?} not conserved, dealt {} but {} held", card, dealt.get(card), held)); // a sale from nothing shows up here, inventories saturate at 0
+        }
+    }
+    let points: usize = exchange.player_points.values().sum();
+    if points != cash {
+        return Some(format!("cash not conserved, started with {} but {} held", cash, points));
+    }
+    None
+}
+
+// random order streams straight into the matching engine, checking after every order that the books are never left crossed
+// and that trading only moved cards and cash around, a failure names the seed and step to replay it from
+#[test]
+fn random_order_streams_keep_the_engine_invariants() {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    for seed in 1..=8 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let player_names: Vec<PlayerName> = (0..5).map(|seat| PlayerName::new(&format!("Fuzz#{}", seat))).collect();
+        let mut exchange = exchange(&player_names);
+        exchange.player_inventories = Deal::draw(&player_names, &mut rng).inventories;
+
+        let mut dealt = Inventory::new();
+        for inventory in exchange.player_inventories.values() {
+            for card in SUITS.iter() {
+                for _ in 0..inventory.get(card) {
+                    dealt.change(card.clone(), true);
+                }
+            }
+        }
+        let cash: usize = exchange.player_points.values().sum();
+
+        let mut trades = 0;
+        for step in 0..ORDERS_PER_SEED {
+            let order = random_order(&mut rng, &player_names);
+            let sent = format!("{:?}", order);
+            let found = match std::panic::catch_unwind(AssertUnwindSafe(|| exchange.process_order(order))) {
+                Ok(filled) => {
+                    let filled = filled.unwrap_or_default();
+                    if !filled.is_empty() {
+                        trades += filled.len();
+                        exchange.reset_books(); // as Exchange::run does after every trade
+                    }
+                    violation(&exchange, &dealt, cash)
+                },
+                Err(_) => Some(String::from("the engine panicked")),
+            };
+            assert!(found.is_none(), "seed {} failed at order {}: {}\nthe order: {}", seed, step, found.unwrap_or_default(), sent);
+        }
+        assert!(trades > 0, "seed {} never traded", seed);
+    }
+}
+
+// a seller with fewer cards than a resting bid wants fills what it can, and the rest of the bid stays up
+#[test]
+fn a_short_seller_leaves_the_rest_of_a_bid_resting() {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    let (buyer, seller) = (PlayerName::new("Buyer"), PlayerName::new("Seller"));
+    let mut exchange = exchange(&[buyer.clone(), seller.clone()]);
+    exchange.player_inventories.get_mut(&seller).unwrap().spades = 2;
+
+    assert!(exchange.process_order(Order::new(buyer, 7, Direction::Buy, Card::Spade, 5)).is_some_and(|trades| trades.is_empty()));
+    let trades = exchange.process_order(Order::new(seller.clone(), 7, Direction::Sell, Card::Spade, 5)).unwrap_or_default();
+    assert_eq!(trades.len(), 2);
+    assert_eq!(exchange.player_inventories.get(&seller).unwrap().spades, 0);
+    assert_eq!(exchange.books.get(&Card::Spade).unwrap().bid().map(|bid| bid.size), Some(3));
+}
+
+// nobody can spend points they don't have: an incoming buy is cut to what its owner can pay for, one it can't pay for at all is
+// rejected, and a resting bid whose owner spent its points elsewhere fills what it still can and comes off
+#[test]
+fn a_buyer_never_spends_more_than_it_has() {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    let (buyer, seller, other) = (PlayerName::new("Buyer"), PlayerName::new("Seller"), PlayerName::new("Other"));
+    let mut exchange = exchange(&[buyer.clone(), seller.clone(), other.clone()]);
+    exchange.player_inventories.get_mut(&seller).unwrap().spades = 5;
+    exchange.player_inventories.get_mut(&other).unwrap().clubs = 1;
+    exchange.player_points.insert(buyer.clone(), 10);
+    let mut events = exchange.event_sender.subscribe();
+
+    assert!(exchange.process_order(Order::new(seller.clone(), 4, Direction::Sell, Card::Spade, 5)).is_some_and(|trades| trades.is_empty()));
+    let trades = exchange.process_order(Order::new(buyer.clone(), 4, Direction::Buy, Card::Spade, 3)).unwrap_or_default();
+    assert_eq!(trades.len(), 2);
+    assert_eq!(exchange.player_points.get(&buyer), Some(&2));
+
+    assert_eq!(exchange.process_order(Order::new(buyer.clone(), 3, Direction::Buy, Card::Spade, 1)), None);
+    assert!(matches!(events.try_recv(), Ok(Event::OrderRejected { reason: RejectReason::NoCash, .. })));
+
+    exchange.player_points.insert(buyer.clone(), 12);
+    assert!(exchange.process_order(Order::new(buyer.clone(), 6, Direction::Buy, Card::Diamond, 2)).is_some_and(|trades| trades.is_empty()));
+    assert!(exchange.process_order(Order::new(other, 6, Direction::Sell, Card::Club, 1)).is_some_and(|trades| trades.is_empty()));
+    assert_eq!(exchange.process_order(Order::new(buyer.clone(), 6, Direction::Buy, Card::Club, 1)).map(|trades| trades.len()), Some(1));
+    exchange.player_inventories.get_mut(&seller).unwrap().diamonds = 2;
+    let trades = exchange.process_order(Order::new(seller, 6, Direction::Sell, Card::Diamond, 2)).unwrap_or_default();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(exchange.player_points.get(&buyer), Some(&0));
+    assert!(exchange.books.get(&Card::Diamond).unwrap().bid().is_none());
+}
+
+// a buy that crosses two asks takes the cheaper one first, at its price, however the two arrived
+#[test]
+fn a_buy_fills_the_cheapest_ask_first() {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    let (buyer, dear, cheap) = (PlayerName::new("Buyer"), PlayerName::new("Dear"), PlayerName::new("Cheap"));
+    let mut exchange = exchange(&[buyer.clone(), dear.clone(), cheap.clone()]);
+    exchange.player_inventories.get_mut(&dear).unwrap().spades = 1;
+    exchange.player_inventories.get_mut(&cheap).unwrap().spades = 1;
+
+    assert!(exchange.process_order(Order::new(dear.clone(), 6, Direction::Sell, Card::Spade, 1)).is_some_and(|trades| trades.is_empty()));
+    assert!(exchange.process_order(Order::new(cheap.clone(), 4, Direction::Sell, Card::Spade, 1)).is_some_and(|trades| trades.is_empty()));
+    let trades = exchange.process_order(Order::new(buyer, 10, Direction::Buy, Card::Spade, 2)).unwrap_or_default();
+    let fills: Vec<(PlayerName, usize)> = trades.into_iter().map(|trade| (trade.seller, trade.price)).collect();
+    assert_eq!(fills, vec![(cheap, 4), (dear, 6)]);
+}
+
+// one card offered at 5 to a buyer after 3, the exchange for each time in force
+fn thin_book() -> (Exchange, PlayerName) {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    let (buyer, seller) = (PlayerName::new("Buyer"), PlayerName::new("Seller"));
+    let mut exchange = exchange(&[buyer.clone(), seller.clone()]);
+    exchange.player_inventories.get_mut(&seller).unwrap().spades = 1;
+    assert!(exchange.process_order(Order::new(seller, 5, Direction::Sell, Card::Spade, 1)).is_some_and(|trades| trades.is_empty()));
+    (exchange, buyer)
+}
+
+// GTC takes what's there, the rest would rest but the trade is about to reset the books, and with nothing to cross it does rest
+#[test]
+fn gtc_takes_the_size_there_is() {
+    let (mut exchange, buyer) = thin_book();
+    assert_eq!(exchange.process_order(Order::new(buyer.clone(), 5, Direction::Buy, Card::Spade, 3)).map(|trades| trades.len()), Some(1));
+    assert!(exchange.process_order(Order::new(buyer, 4, Direction::Buy, Card::Spade, 3)).is_some_and(|trades| trades.is_empty()));
+    assert_eq!(exchange.books.get(&Card::Spade).unwrap().bid().map(|bid| (bid.price, bid.size)), Some((4, 3)));
+}
+
+// IOC takes what's there and never rests the rest
+#[test]
+fn ioc_takes_the_size_there_is_and_drops_the_rest() {
+    let (mut exchange, buyer) = thin_book();
+    let order = Order { tif: TimeInForce::IOC, ..Order::new(buyer.clone(), 5, Direction::Buy, Card::Spade, 3) };
+    assert_eq!(exchange.process_order(order).map(|trades| trades.len()), Some(1));
+    let order = Order { tif: TimeInForce::IOC, ..Order::new(buyer, 4, Direction::Buy, Card::Spade, 3) };
+    assert!(exchange.process_order(order).is_some_and(|trades| trades.is_empty()));
+    assert!(exchange.books.get(&Card::Spade).unwrap().bid().is_none());
+}
+
+// FOK can't get all 3, so it's killed without touching the book
+#[test]
+fn fok_short_of_size_is_killed() {
+    let (mut exchange, buyer) = thin_book();
+    let mut events = exchange.event_sender.subscribe();
+    let order = Order { tif: TimeInForce::FOK, ..Order::new(buyer.clone(), 5, Direction::Buy, Card::Spade, 3) };
+    assert_eq!(exchange.process_order(order), None);
+    assert!(matches!(events.try_recv(), Ok(Event::OrderRejected { reason: RejectReason::Killed, .. })));
+    assert_eq!(exchange.books.get(&Card::Spade).unwrap().ask().map(|ask| ask.size), Some(1));
+    assert_eq!(exchange.player_inventories.get(&buyer).unwrap().spades, 0);
+}
+
+// a sell through a bigger bid trades and leaves the rest of that bid up, under the ask it was always under:
+// the Update that goes out for it shows every book with its bid at or below its ask
+#[test]
+fn an_update_after_a_cross_is_never_crossed() {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    let (bidder, seller, offerer) = (PlayerName::new("Bidder"), PlayerName::new("Seller"), PlayerName::new("Offerer"));
+    let mut exchange = exchange(&[bidder.clone(), seller.clone(), offerer.clone()]);
+    exchange.player_inventories.get_mut(&seller).unwrap().spades = 1;
+    exchange.player_inventories.get_mut(&offerer).unwrap().spades = 1;
+    let mut events = exchange.event_sender.subscribe();
+
+    assert!(exchange.process_order(Order::new(bidder, 5, Direction::Buy, Card::Spade, 2)).is_some_and(|trades| trades.is_empty()));
+    assert!(exchange.process_order(Order::new(offerer, 7, Direction::Sell, Card::Spade, 1)).is_some_and(|trades| trades.is_empty()));
+    let trades = exchange.process_order(Order::new(seller, 4, Direction::Sell, Card::Spade, 1)).unwrap_or_default();
+    assert_eq!(trades.len(), 1);
+    exchange.broadcast_update(trades);
+
+    let Ok(Event::Update(update)) = events.try_recv() else {
+        panic!("no update went out");
+    };
+    for book in [&update.spades, &update.clubs, &update.diamonds, &update.hearts] {
+        if let (Some(bid), Some(ask)) = (book.bid(), book.ask()) {
+            assert!(bid.price <= ask.price, "bid {} over ask {}", bid.price, ask.price);
+        }
+    }
+    assert_eq!((update.spades.bid().map(|bid| bid.price), update.spades.ask().map(|ask| ask.price)), (Some(5), Some(7)));
+}
+
+// a quote whose ask can't go out (nothing to sell) takes its bid down with it, the book never shows one side of it alone
+#[test]
+fn a_rejected_ask_leg_drops_the_bid() {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    let quoter = PlayerName::new("Quoter");
+    let mut exchange = exchange(std::slice::from_ref(&quoter));
+    let mut events = exchange.event_sender.subscribe();
+
+    let mut quote = Order::new(quoter.clone(), 4, Direction::Buy, Card::Spade, 1);
+    quote.paired = Some(Box::new(Order::new(quoter, 7, Direction::Sell, Card::Spade, 1)));
+    assert_eq!(exchange.process_order(quote), None);
+    let book = exchange.books.get(&Card::Spade).unwrap();
+    assert!(book.bid().is_none() && book.ask().is_none());
+
+    let mut reasons = Vec::new();
+    while let Ok(Event::OrderRejected { reason, .. }) = events.try_recv() {
+        reasons.push(reason);
+    }
+    assert_eq!(reasons, vec![RejectReason::LegRejected, RejectReason::NoInventory]);
+}
+
+// on a 5..=30 ladder in steps of 5, a bid at 12 rests at the nearest rung (10) and one at 31 never gets on the book
+#[test]
+fn prices_snap_to_the_ladder_and_off_it_are_rejected() {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    let bidder = PlayerName::new("Bidder");
+    let mut exchange = exchange(std::slice::from_ref(&bidder));
+    exchange.ladders.spades = PriceLadder { min: 5, max: 30, tick: 5 };
+    let mut events = exchange.event_sender.subscribe();
+
+    assert!(exchange.process_order(Order::new(bidder.clone(), 12, Direction::Buy, Card::Spade, 1)).is_some_and(|trades| trades.is_empty()));
+    assert_eq!(exchange.books.get(&Card::Spade).unwrap().bid().map(|bid| bid.price), Some(10));
+    assert!(exchange.process_order(Order::new(bidder.clone(), 13, Direction::Buy, Card::Spade, 1)).is_some_and(|trades| trades.is_empty()));
+    assert_eq!(exchange.books.get(&Card::Spade).unwrap().bid().map(|bid| bid.price), Some(15));
+
+    assert_eq!(exchange.process_order(Order::new(bidder, 31, Direction::Buy, Card::Spade, 1)), None);
+    assert!(matches!(events.try_recv(), Ok(Event::OrderRejected { reason: RejectReason::OffLadder, .. })));
+    assert_eq!(exchange.books.get(&Card::Spade).unwrap().bid().map(|bid| bid.price), Some(15));
+}
+
+// layered bids at 3 and 4 from one player both rest, best first, and its own layered ask above them trades with neither
+#[test]
+fn layered_bids_from_one_player_both_rest() {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    let layerer = PlayerName::new("Layerer");
+    let mut exchange = exchange(std::slice::from_ref(&layerer));
+    exchange.player_inventories.get_mut(&layerer).unwrap().spades = 1;
+    let layered = |price: usize, direction: Direction| Order { layer: true, ..Order::new(layerer.clone(), price, direction, Card::Spade, 1) };
+
+    assert!(exchange.process_order(layered(3, Direction::Buy)).is_some_and(|trades| trades.is_empty()));
+    assert!(exchange.process_order(layered(4, Direction::Buy)).is_some_and(|trades| trades.is_empty()));
+    assert!(exchange.process_order(layered(6, Direction::Sell)).is_some_and(|trades| trades.is_empty()));
+
+    let book = exchange.books.get(&Card::Spade).unwrap();
+    let bids: Vec<(usize, PlayerName)> = book.bids.iter().map(|level| (level.price, level.player_name.clone())).collect();
+    assert_eq!(bids, vec![(4, layerer.clone()), (3, layerer.clone())]);
+    assert_eq!(book.ask().map(|ask| ask.price), Some(6));
+    assert_eq!(exchange.player_inventories.get(&layerer).unwrap().spades, 1);
+}
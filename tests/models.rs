@@ -0,0 +1,281 @@
+use figgie_auto::{deal, hand_size, infer_goal_from_distribution, settle_round, validate_deal, Book, BookDiff, Card, Color, Deal, DealContext, DealError, Direction, FairValue, Inventory, InventoryDrift, Level, Order, PlayerName, Pot, RoundResult, Trade, TradeHistory};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+
+
+const SUITS: [Card; 4] = [Card::Spade, Card::Club, Card::Diamond, Card::Heart];
+
+// one hand per row, spades, clubs, diamonds, hearts
+fn table(hands: &[[usize; 4]]) -> HashMap<PlayerName, Inventory> {
+    hands.iter().enumerate().map(|(seat, [spades, clubs, diamonds, hearts])| {
+        (PlayerName::new(&format!("Seat#{}", seat)), Inventory { spades: *spades, clubs: *clubs, diamonds: *diamonds, hearts: *hearts })
+    }).collect()
+}
+
+// what goes over the wire has to come back as it left, a WsGateway client sees nothing else
+#[test]
+fn a_populated_book_survives_a_json_round_trip() {
+    let mut book = Book::new();
+    book.insert(&Direction::Buy, Level { price: 4, player_name: PlayerName::new("Bidder"), size: 2 });
+    book.insert(&Direction::Buy, Level { price: 3, player_name: PlayerName::new("Other"), size: 1 });
+    book.insert(&Direction::Sell, Level { price: 7, player_name: PlayerName::new("Seller"), size: 1 });
+    book.record_trade(5, Direction::Buy, 1);
+
+    let json = serde_json::to_string(&book).unwrap();
+    assert_eq!(serde_json::from_str::<Book>(&json).unwrap(), book);
+    assert!(json.contains(r#""aggressor":"Buy""#), "{}", json);
+}
+
+// suits and sides are sent by name, that's the contract with anything not written in Rust
+#[test]
+fn cards_and_directions_encode_as_their_names() {
+    let order = Order::new(PlayerName::new("Buyer"), 6, Direction::Buy, Card::Spade, 1);
+    let json = serde_json::to_string(&order).unwrap();
+    assert!(json.contains(r#""card":"Spade""#) && json.contains(r#""direction":"Buy""#), "{}", json);
+    assert_eq!(serde_json::from_str::<Order>(&json).unwrap(), order);
+
+    let trade = Trade { card: Card::Heart, price: 6, buyer: PlayerName::new("Buyer"), seller: PlayerName::new("Seller"), aggressor: Direction::Sell, timestamp: 1200 };
+    let json = serde_json::to_string(&trade).unwrap();
+    assert!(json.contains(r#""card":"Heart""#) && json.contains(r#""aggressor":"Sell""#), "{}", json);
+    assert_eq!(serde_json::from_str::<Trade>(&json).unwrap(), trade);
+}
+
+// from a hand alone, cards_outstanding never undercounts what the other seats really hold, with the split it's exact,
+// and it follows our live holding rather than the hand as dealt
+#[test]
+fn cards_outstanding_is_an_upper_bound_on_the_other_hands() {
+    let players: Vec<PlayerName> = (0..4).map(|seat| PlayerName::new(&format!("Seat#{}", seat))).collect();
+    for seed in 0..50 {
+        let deal = Deal::draw(&players, &mut StdRng::seed_from_u64(seed));
+        let count = |card: Card| deal.suit_counts[&card];
+        let totals = Inventory { spades: count(Card::Spade), clubs: count(Card::Club), diamonds: count(Card::Diamond), hearts: count(Card::Heart) };
+        for hand in deal.inventories.values() {
+            for card in SUITS.iter() {
+                let elsewhere = deal.suit_counts[card] - hand.get(card);
+                assert!(DealContext::from_hand(*hand).cards_outstanding(card, hand) >= elsewhere, "seed {} {:?}", seed, card);
+                assert_eq!(DealContext::from_split(*hand, totals).cards_outstanding(card, hand), elsewhere);
+            }
+        }
+    }
+
+    let hand = Inventory { spades: 3, clubs: 3, diamonds: 2, hearts: 2 };
+    let deal = DealContext::from_hand(hand);
+    assert_eq!(deal.cards_outstanding(&Card::Spade, &hand), 9);
+    let bought = Inventory { spades: 5, ..hand };
+    assert_eq!(deal.cards_outstanding(&Card::Spade, &bought), 7);
+    let cornered = Inventory { clubs: 11, ..hand }; // only the common suit runs past 10, so spades can't be it
+    assert_eq!(deal.cards_outstanding(&Card::Spade, &cornered), 7);
+}
+
+// a known four-player deal, hearts the goal (diamonds are the 12): every heart pays 10, the most hearts take the other 100,
+// ties split it with the odd point left as dust, and either way the payouts come to the 200 in the pot
+#[test]
+fn settle_round_pays_out_the_pot() {
+    let hands = [
+        ("A", Inventory { spades: 2, clubs: 1, diamonds: 3, hearts: 4 }),
+        ("B", Inventory { spades: 3, clubs: 2, diamonds: 2, hearts: 3 }),
+        ("C", Inventory { spades: 3, clubs: 2, diamonds: 3, hearts: 2 }),
+        ("D", Inventory { spades: 2, clubs: 3, diamonds: 4, hearts: 1 }),
+    ];
+    let inventories: HashMap<PlayerName, Inventory> = hands.iter().map(|(name, hand)| (PlayerName::new(name), *hand)).collect();
+    assert_eq!(infer_goal_from_distribution(&inventories), Ok(Card::Heart));
+    let result = RoundResult { goal_suit: Card::Heart, pot: 200, inventories };
+
+    let payouts: Vec<usize> = hands.iter().map(|(_, hand)| result.get_payout(hand)).collect();
+    assert_eq!(payouts, vec![140, 30, 20, 10]);
+    assert_eq!(payouts.iter().sum::<usize>(), 200);
+    let settled: Vec<i64> = hands.iter().map(|(_, hand)| settle_round(hand, &result)).collect();
+    assert_eq!(settled, vec![90, -20, -30, -40]);
+
+    let tied = |hearts: [usize; 4]| RoundResult { inventories: hands.iter().zip(hearts).map(|((name, hand), hearts)| (PlayerName::new(name), Inventory { hearts, ..*hand })).collect(), ..result.clone() };
+    let two_way = tied([4, 4, 1, 1]);
+    let payouts: Vec<usize> = two_way.inventories.values().map(|hand| two_way.get_payout(hand)).collect();
+    assert_eq!(payouts.iter().filter(|payout| **payout == 90).count(), 2);
+    assert_eq!(payouts.iter().sum::<usize>(), 200);
+
+    let three_way = tied([3, 3, 3, 1]);
+    let payouts: usize = three_way.inventories.values().map(|hand| three_way.get_payout(hand)).sum();
+    assert_eq!((payouts, three_way.get_dust()), (199, 1));
+}
+
+// 11 spades can't be any suit of a Figgie deck, with clubs the 12 it's the spade count that's named, and the other ways a deal goes wrong
+#[test]
+fn validate_deal_names_what_is_wrong() {
+    let eleven_spades = table(&[[3, 3, 3, 2], [3, 3, 2, 2], [3, 3, 2, 2], [2, 3, 2, 2]]);
+    assert_eq!(validate_deal(&eleven_spades), Err(DealError::BadSuitCount { card: Card::Spade, count: 11 }));
+    assert_eq!(validate_deal(&table(&[[3, 3, 2, 2], [3, 3, 2, 2], [3, 3, 2, 2]])), Err(DealError::WrongTotal { expected: 40, found: 30 }));
+    assert_eq!(validate_deal(&table(&[[3, 3, 2, 2], [3, 3, 2, 2], [2, 2, 3, 3], [2, 2, 3, 3]])), Err(DealError::NoCommonSuit));
+    assert_eq!(validate_deal(&table(&[[3, 3, 2, 2], [3, 3, 2, 2], [3, 2, 2, 3], [3, 2, 2, 3]])), Ok(()));
+}
+
+// diamonds the 12 makes hearts the goal, whichever of the other suits is short, and a deal that isn't legal has no goal to give
+#[test]
+fn the_goal_is_read_off_the_deal() {
+    let hearts_goal = table(&[[3, 3, 2, 2], [3, 2, 3, 2], [2, 2, 4, 2], [2, 1, 3, 4]]);
+    assert_eq!(infer_goal_from_distribution(&hearts_goal), Ok(Card::Heart));
+    let clubs_goal = table(&[[3, 3, 2, 2], [3, 3, 2, 2], [3, 2, 2, 3], [3, 2, 2, 3]]);
+    assert_eq!(infer_goal_from_distribution(&clubs_goal), Ok(Card::Club));
+
+    let malformed = table(&[[3, 3, 3, 2], [3, 3, 2, 2], [3, 3, 2, 2], [2, 3, 2, 2]]);
+    assert_eq!(infer_goal_from_distribution(&malformed), Err(DealError::BadSuitCount { card: Card::Spade, count: 11 }));
+    assert!(infer_goal_from_distribution(&table(&[[10, 10, 10, 10]])).is_err());
+}
+
+// every deal is the whole 40-card deck split 12/10/10/8, and the goal is the common suit's same-colour partner, 8 or 10 of them
+#[test]
+fn a_dealt_deck_follows_the_colour_rule() {
+    let players: Vec<PlayerName> = (0..4).map(|seat| PlayerName::new(&format!("Seat#{}", seat))).collect();
+    for seed in 0..50 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let drawn = Deal::draw(&players, &mut rng);
+        let mut counts: Vec<usize> = drawn.suit_counts.values().copied().collect();
+        counts.sort();
+        assert_eq!(counts, vec![8, 10, 10, 12], "seed {}", seed);
+        assert_eq!(drawn.inventories.values().map(Inventory::total).sum::<usize>(), 40);
+        assert_eq!(drawn.goal_suit, drawn.common_suit.get_goal_suit());
+        assert_eq!(drawn.goal_suit.color(), drawn.common_suit.color());
+        assert_eq!(drawn.suit_counts[&drawn.common_suit], 12);
+
+        let (inventories, goal_suit, pot) = deal::deal(&players, &mut rng);
+        assert_eq!(inventories.values().map(Inventory::total).sum::<usize>(), 40);
+        assert_eq!(infer_goal_from_distribution(&inventories), Ok(goal_suit));
+        assert_eq!(pot, 200);
+    }
+}
+
+// four players get 10 cards each and ante 50, five get 8 and ante 40, the whole deck goes out and the pot is 200 either way
+#[test]
+fn four_and_five_player_tables_deal_the_whole_deck() {
+    for (seats, cards, ante) in [(4, 10, 50), (5, 8, 40)] {
+        let players: Vec<PlayerName> = (0..seats).map(|seat| PlayerName::new(&format!("Seat#{}", seat))).collect();
+        assert_eq!(hand_size(seats), cards);
+        let drawn = Deal::draw(&players, &mut StdRng::seed_from_u64(seats as u64));
+        assert_eq!(drawn.inventories.len(), seats);
+        assert!(drawn.inventories.values().all(|hand| hand.total() == cards), "{} seats", seats);
+        assert_eq!(Pot::new(seats).ante, ante);
+        assert_eq!(Pot::new(seats).ante * seats, 200);
+        assert_eq!(Pot::break_even_price(seats), 5.0);
+    }
+}
+
+// a diff carrying only clubs swaps that one book in and leaves the other three exactly as they were
+#[test]
+fn a_clubs_diff_leaves_the_other_books_alone() {
+    let mut books = [Book::new(), Book::new(), Book::new(), Book::new()];
+    for (index, book) in books.iter_mut().enumerate() {
+        book.insert(&Direction::Buy, Level { price: index + 2, player_name: PlayerName::new("Bidder"), size: 1 });
+    }
+    let mut clubs = Book::new();
+    clubs.insert(&Direction::Sell, Level { price: 9, player_name: PlayerName::new("Seller"), size: 2 });
+
+    let update = BookDiff { changed: vec![(Card::Club, clubs.clone())], trades: Vec::new() }.apply(&books);
+    assert_eq!(update.clubs, clubs);
+    assert_eq!((update.spades, update.diamonds, update.hearts), (books[0].clone(), books[2].clone(), books[3].clone()));
+    assert!(update.trades.is_empty());
+}
+
+// mid and spread want both sides quoted, an empty or one-sided book has neither
+#[test]
+fn mid_and_spread_need_both_sides() {
+    let mut book = Book::new();
+    assert_eq!((book.mid(), book.spread(), book.is_crossed()), (None, None, false));
+    book.insert(&Direction::Buy, Level { price: 4, player_name: PlayerName::new("Bidder"), size: 1 });
+    assert_eq!((book.mid(), book.spread()), (None, None));
+    book.insert(&Direction::Sell, Level { price: 7, player_name: PlayerName::new("Seller"), size: 1 });
+    assert_eq!((book.mid(), book.spread(), book.is_crossed()), (Some(5.5), Some(3), false));
+}
+
+// imbalance is resting bid size less ask size over the two together: positive when bids outweigh asks, -1 to 1, 0 when empty
+#[test]
+fn imbalance_has_the_heavier_side_s_sign() {
+    let mut book = Book::new();
+    assert_eq!(book.imbalance(), 0.0);
+    book.insert(&Direction::Buy, Level { price: 4, player_name: PlayerName::new("Bidder"), size: 3 });
+    assert_eq!(book.imbalance(), 1.0);
+    book.insert(&Direction::Sell, Level { price: 7, player_name: PlayerName::new("Seller"), size: 1 });
+    assert_eq!(book.imbalance(), 0.5);
+    book.insert(&Direction::Sell, Level { price: 8, player_name: PlayerName::new("Other"), size: 5 });
+    assert_eq!(book.imbalance(), -1.0 / 3.0);
+    book.insert(&Direction::Buy, Level { price: 3, player_name: PlayerName::new("Another"), size: 3 });
+    assert_eq!(book.imbalance(), 0.0);
+}
+
+fn print(card: Card, price: usize) -> Trade {
+    Trade { card, price, buyer: PlayerName::new("Buyer"), seller: PlayerName::new("Seller"), aggressor: Direction::Buy, timestamp: 0 }
+}
+
+// with a 12-card hint the prior is nothing, so three spade prints averaging 8 against a prior worth 3 of them land on 4,
+// and the club print never counts towards spades
+#[test]
+fn fair_value_blends_the_prior_with_the_prints() {
+    let goal_card = FairValue::goal_card_value();
+    assert!((goal_card - 16.11).abs() < 0.01, "{}", goal_card);
+    assert!((FairValue::estimate(&Card::Spade, &[], 10) - goal_card / 3.0).abs() < 1e-4);
+    assert_eq!(FairValue::estimate(&Card::Spade, &[], 12), 0.0);
+
+    let prints = [print(Card::Spade, 6), print(Card::Club, 14), print(Card::Spade, 8), print(Card::Spade, 10)];
+    assert!((FairValue::estimate(&Card::Spade, &prints, 12) - 4.0).abs() < 1e-4);
+    assert_eq!(FairValue::suit_count_hint(&Inventory { spades: 3, clubs: 3, diamonds: 2, hearts: 2 }, &Card::Spade), 12);
+}
+
+// a near-certain goal suit is valued close to a whole goal card however cheaply it has printed, uniform odds change nothing
+#[test]
+fn a_decided_posterior_pulls_fair_value_to_the_goal_payout() {
+    let prints = [print(Card::Heart, 3), print(Card::Heart, 4), print(Card::Heart, 2)];
+    let estimate = FairValue::estimate(&Card::Heart, &prints, 8);
+    assert_eq!(FairValue::with_goal(&Card::Heart, &prints, 8, &[0.25; 4]), estimate);
+
+    let sure = FairValue::with_goal(&Card::Heart, &prints, 8, &[0.01, 0.01, 0.01, 0.97]);
+    assert!(prints.iter().all(|trade| sure > trade.price as f32), "{}", sure);
+    assert!(sure > 0.9 * FairValue::goal_card_value(), "{}", sure);
+    assert!(FairValue::with_goal(&Card::Spade, &[], 8, &[0.01, 0.01, 0.01, 0.97]) < 1.0);
+}
+
+// spades pair with clubs and diamonds with hearts, each colour's suits() are exactly the suits of that colour
+#[test]
+fn suits_pair_up_by_colour() {
+    let colours: Vec<Color> = SUITS.iter().map(Card::color).collect();
+    assert_eq!(colours, vec![Color::Black, Color::Black, Color::Red, Color::Red]);
+    for card in SUITS.iter() {
+        assert!(card.color().suits().contains(card));
+        let partner = card.get_goal_suit();
+        assert_ne!(&partner, card);
+        assert_eq!(partner.color(), card.color());
+        assert_eq!(&partner.get_goal_suit(), card);
+    }
+    assert_eq!(Color::Black.suits(), [Card::Spade, Card::Club]);
+    assert_eq!(Color::Red.suits(), [Card::Diamond, Card::Heart]);
+}
+
+// the exchange saw three fills, our count missed the diamond sale: reconcile names that suit alone and takes the exchange's count
+#[test]
+fn reconcile_finds_a_missed_trade() {
+    let dealt = Inventory { spades: 3, clubs: 3, diamonds: 2, hearts: 2 };
+    let (mut local, mut exchange) = (dealt, dealt);
+    for (card, bought) in [(Card::Spade, true), (Card::Heart, false), (Card::Diamond, false)] {
+        exchange.change(card.clone(), bought);
+        if card != Card::Diamond {
+            local.change(card, bought);
+        }
+    }
+    assert_eq!(local.reconcile(&exchange), vec![InventoryDrift { card: Card::Diamond, local: 2, expected: 1 }]);
+    assert_eq!(local, exchange);
+    assert_eq!(local.reconcile(&exchange), Vec::new());
+}
+
+// however long a round runs only the last `capacity` prints are kept, oldest first, and each round starts from nothing
+#[test]
+fn trade_history_stays_bounded_across_rounds() {
+    let mut history = TradeHistory::new(8);
+    for round in 0..20 {
+        assert!(history.is_empty(), "round {}", round);
+        for price in 0..(round * 7) {
+            history.push(print(Card::Spade, price));
+            assert!(history.len() <= 8);
+        }
+        let kept: Vec<usize> = history.as_slice().iter().map(|trade| trade.price).collect();
+        assert_eq!(kept, ((round * 7).saturating_sub(8)..round * 7).collect::<Vec<usize>>(), "round {}", round);
+        history.clear();
+    }
+}
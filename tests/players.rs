@@ -0,0 +1,279 @@
+use figgie_auto::{Book, Card, Clock, Direction, Event, EventDrivenPlayer, GenericPlayer, Inventory, Level, LogLevel, ManualClock, Order, PlayerName, Pot, Strategy, StrategyConfig, Trade, TradeSink, Update};
+use figgie_auto::player::config::{NoisyConfig, NoisyMode, RiskConfig};
+use kanal::AsyncReceiver;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+
+const HAND: Inventory = Inventory { spades: 3, clubs: 3, diamonds: 2, hearts: 2 };
+
+
+// a player on its own channels, nothing at the other end of them but the test
+fn generic(strategy: Strategy, config: StrategyConfig) -> (GenericPlayer, tokio::sync::broadcast::Sender<Event>, AsyncReceiver<Order>) {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    let (event_sender, _) = tokio::sync::broadcast::channel::<Event>(100);
+    let (order_sender, order_receiver) = kanal::unbounded_async::<Order>();
+    let player = GenericPlayer::new(PlayerName::new(&format!("{:?}", strategy)), strategy, 10, 10, Some(0), config, event_sender.clone(), Arc::new(order_sender));
+    (player, event_sender, order_receiver)
+}
+
+fn event_driven(strategy: Strategy, config: StrategyConfig) -> (EventDrivenPlayer, tokio::sync::broadcast::Sender<Event>, AsyncReceiver<Order>) {
+    figgie_auto::utils::logging::set_level(LogLevel::Error);
+    let (event_sender, _) = tokio::sync::broadcast::channel::<Event>(100);
+    let (order_sender, order_receiver) = kanal::unbounded_async::<Order>();
+    let player = EventDrivenPlayer::new(PlayerName::new(&format!("{:?}", strategy)), strategy, config, event_sender.clone(), Arc::new(order_sender));
+    (player, event_sender, order_receiver)
+}
+
+// Noisy draws its suit and then a coin for the side, whichever way the coin lands is the side that goes out
+#[tokio::test]
+async fn noisy_sends_the_side_its_coin_picked() {
+    let hand = Inventory { spades: 2, clubs: 2, diamonds: 2, hearts: 2 };
+    let mut buys = 0;
+    for seed in 0..20 {
+        let (player, _events, orders) = generic(Strategy::Noisy, StrategyConfig::default());
+        player.market.write().await.inventory = hand;
+
+        let mut probe = StdRng::seed_from_u64(seed);
+        probe.gen_range(0..4usize);
+        let expected = if probe.gen_bool(0.5) { Direction::Buy } else { Direction::Sell };
+
+        player.noisy_trader(hand, Book::new(), Book::new(), Book::new(), Book::new(), &mut StdRng::seed_from_u64(seed)).await.unwrap();
+        let order = orders.try_recv().unwrap().expect("noisy sends an order every tick it can");
+        assert_eq!(order.direction, expected, "seed {}", seed);
+        buys += usize::from(order.direction == Direction::Buy);
+    }
+    assert!(buys > 0, "no seed took the buy arm");
+}
+
+// nobody offering means nothing to lift, however much PickOff wants the suit (a 90% goal lifts its cap to 14)
+#[tokio::test]
+async fn pick_off_sends_no_buy_into_an_empty_book() {
+    let (mut player, _events, orders) = event_driven(Strategy::PickOff, StrategyConfig::default());
+    let mut bids_only = Book::new();
+    bids_only.insert(&Direction::Buy, Level { price: 2, player_name: PlayerName::new("Bidder"), size: 1 });
+    for book in [Book::new(), bids_only] {
+        player.pick_off(240, 0, book, Card::Spade, 0.9).await.unwrap();
+    }
+    assert!(orders.try_recv().unwrap().is_none());
+}
+
+// the exchange going away closes the event channel, and both kinds of player wind down on their own rather than waiting forever
+#[tokio::test]
+async fn players_exit_once_the_event_sender_is_dropped() {
+    let (mut generic_player, events, _orders) = generic(Strategy::Noisy, StrategyConfig::default());
+    let generic_task = tokio::spawn(async move { generic_player.start().await });
+    let (mut event_player, event_driven_events, _event_driven_orders) = event_driven(Strategy::PickOff, StrategyConfig::default());
+    let event_task = tokio::spawn(async move { event_player.start().await });
+    drop(events);
+    drop(event_driven_events);
+
+    tokio::time::timeout(Duration::from_secs(5), generic_task).await.expect("the generic player is still running").unwrap();
+    tokio::time::timeout(Duration::from_secs(5), event_task).await.expect("the event-driven player is still running").unwrap();
+}
+
+// wait until the player has subscribed, anything sent before then is never seen
+async fn subscribed(events: &tokio::sync::broadcast::Sender<Event>) {
+    while events.receiver_count() == 0 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+fn deal_to(names: &[&str], hand: Inventory) -> Event {
+    Event::DealCards { inventories: names.iter().map(|name| (PlayerName::new(name), hand)).collect(), pot: Pot::new(4) }
+}
+
+// with the exchange's order receiver gone the first order Noisy tries to send fails, and it stops instead of ticking on into the void
+#[tokio::test]
+async fn a_closed_order_channel_stops_the_player() {
+    let (mut player, events, orders) = generic(Strategy::Noisy, StrategyConfig::default());
+    let shutdown = player.shutdown.clone();
+    drop(orders);
+    let task = tokio::spawn(async move { player.start().await });
+    subscribed(&events).await;
+    events.send(deal_to(&["Noisy", "Other"], HAND)).unwrap();
+
+    tokio::time::timeout(Duration::from_secs(5), task).await.expect("the player kept running without an order channel").unwrap();
+    assert!(shutdown.load(Ordering::Acquire));
+}
+
+struct Exploding;
+
+impl TradeSink for Exploding {
+    fn log(&self, _trade: &Trade) {
+        panic!("sink blew up");
+    }
+}
+
+// a listener that dies mid-update leaves the books half-written, so the player stops trading and shuts down rather than carry on from them
+#[tokio::test]
+async fn a_panicking_listener_stops_the_player() {
+    let (mut player, events, _orders) = generic(Strategy::Noisy, StrategyConfig::default());
+    player.trade_logger = Some(Arc::new(Exploding));
+    let (trading, shutdown) = (player.trading.clone(), player.shutdown.clone());
+    let task = tokio::spawn(async move { player.start().await });
+    subscribed(&events).await;
+    events.send(deal_to(&["Noisy", "Other"], HAND)).unwrap();
+    let trade = Trade { card: Card::Spade, price: 5, buyer: PlayerName::new("Other"), seller: PlayerName::new("Someone"), aggressor: Direction::Buy, timestamp: 0 };
+    events.send(Event::Update(Update { spades: Book::new(), clubs: Book::new(), diamonds: Book::new(), hearts: Book::new(), trades: vec![trade] })).unwrap();
+
+    tokio::time::timeout(Duration::from_secs(5), task).await.expect("the player outlived its listener").unwrap();
+    assert!(!trading.load(Ordering::Acquire));
+    assert!(shutdown.load(Ordering::Acquire));
+}
+
+// dealt out of the round, the player sits it out: no trading, no orders, but still listening for the next deal
+#[tokio::test]
+async fn a_player_missing_from_the_deal_sits_the_round_out() {
+    let (mut player, events, orders) = generic(Strategy::Noisy, StrategyConfig::default());
+    let (trading, shutdown) = (player.trading.clone(), player.shutdown.clone());
+    let task = tokio::spawn(async move { player.start().await });
+    subscribed(&events).await;
+    events.send(deal_to(&["Someone", "Other"], HAND)).unwrap();
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+
+    assert!(!task.is_finished());
+    assert!(!trading.load(Ordering::Acquire));
+    assert!(!shutdown.load(Ordering::Acquire));
+    assert!(orders.try_recv().unwrap().is_none());
+
+    events.send(deal_to(&["Noisy", "Other"], HAND)).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), orders.recv()).await.expect("dealt back in, the player trades again").unwrap();
+    task.abort();
+}
+
+// poll until `flag` reads `value`, panicking if it never gets there
+async fn settles(flag: &AtomicBool, value: bool, what: &str) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while flag.load(Ordering::Acquire) != value {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }).await.unwrap_or_else(|_| panic!("{}", what));
+}
+
+// heartbeats held back past stale_after mark the feed stale, the next one to arrive clears it again
+#[tokio::test]
+async fn withheld_heartbeats_set_the_stale_flag() {
+    let (mut player, events, _orders) = generic(Strategy::Noisy, StrategyConfig::default());
+    let clock = Arc::new(ManualClock::new());
+    player.clock = clock.clone();
+    let (stale_after, stale, trading) = (player.stale_after.expect("on by default"), player.stale.clone(), player.trading.clone());
+    let task = tokio::spawn(async move { player.start().await });
+    subscribed(&events).await;
+    events.send(deal_to(&["Noisy", "Other"], HAND)).unwrap();
+    settles(&trading, true, "never started trading").await;
+    assert!(!stale.load(Ordering::Acquire));
+
+    clock.advance(stale_after + Duration::from_secs(1));
+    settles(&stale, true, "silence past stale_after never set the flag").await;
+    events.send(Event::Heartbeat { tick: 1 }).unwrap();
+    settles(&stale, false, "a heartbeat never cleared the flag").await;
+    task.abort();
+}
+
+// PickOff dealt no spades, lifting one cheap ask and then seeing another in the update that carries its own fill
+async fn orders_after_a_fill(cooldown_ms: u64) -> usize {
+    let mut config = StrategyConfig::default();
+    config.pick_off.cooldown_ms = cooldown_ms;
+    let (mut player, events, orders) = event_driven(Strategy::PickOff, config);
+    let task = tokio::spawn(async move { player.start().await });
+    subscribed(&events).await;
+    events.send(deal_to(&["PickOff", "Seller"], Inventory { spades: 0, clubs: 4, diamonds: 3, hearts: 3 })).unwrap();
+
+    let offered = || {
+        let mut spades = Book::new();
+        spades.insert(&Direction::Sell, Level { price: 1, player_name: PlayerName::new("Seller"), size: 1 });
+        spades
+    };
+    events.send(Event::Update(Update { spades: offered(), clubs: Book::new(), diamonds: Book::new(), hearts: Book::new(), trades: Vec::new() })).unwrap();
+    let first = tokio::time::timeout(Duration::from_secs(5), orders.recv()).await.expect("the cheap ask was never lifted").unwrap();
+    assert_eq!((first.direction, first.card, first.price), (Direction::Buy, Card::Spade, 1));
+
+    let fill = Trade { card: Card::Spade, price: 1, buyer: PlayerName::new("PickOff"), seller: PlayerName::new("Seller"), aggressor: Direction::Buy, timestamp: 0 };
+    events.send(Event::Update(Update { spades: offered(), clubs: Book::new(), diamonds: Book::new(), hearts: Book::new(), trades: vec![fill] })).unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    task.abort();
+    1 + orders.len()
+}
+
+// two updates well inside the cooldown, the second carrying our fill: only the first gets an order, with no cooldown both do
+#[tokio::test]
+async fn a_fill_holds_the_suit_back_for_the_cooldown() {
+    assert_eq!(orders_after_a_fill(1000).await, 1);
+    assert_eq!(orders_after_a_fill(0).await, 2);
+}
+
+// with edge_threshold 2, a buy 1 under fair value is held back, one 3 under goes out, and sells are measured the other way round
+#[tokio::test]
+async fn orders_short_of_the_edge_threshold_are_held_back() {
+    let (player, _events, orders) = generic(Strategy::Seller, StrategyConfig { risk: RiskConfig { edge_threshold: 2.0, ..Default::default() }, ..Default::default() });
+    player.market.write().await.inventory = HAND;
+    player.send_order_with_edge(5, Direction::Buy, &Card::Spade, &Book::new(), 1, 6.0).await.unwrap();
+    assert!(orders.try_recv().unwrap().is_none());
+    player.send_order_with_edge(3, Direction::Buy, &Card::Spade, &Book::new(), 1, 6.0).await.unwrap();
+    assert_eq!(orders.try_recv().unwrap().map(|order| (order.direction, order.price)), Some((Direction::Buy, 3)));
+
+    player.send_order_with_edge(7, Direction::Sell, &Card::Club, &Book::new(), 1, 6.0).await.unwrap();
+    assert!(orders.try_recv().unwrap().is_none());
+    player.send_order_with_edge(9, Direction::Sell, &Card::Club, &Book::new(), 1, 6.0).await.unwrap();
+    assert_eq!(orders.try_recv().unwrap().map(|order| (order.direction, order.price)), Some((Direction::Sell, 9)));
+}
+
+// equal bounds are a fixed interval rather than an empty range, backwards ones are swapped, and a player on a fixed interval trades
+#[tokio::test]
+async fn equal_tick_bounds_are_a_fixed_interval() {
+    let (mut player, events, orders) = generic(Strategy::Noisy, StrategyConfig::default());
+    let mut rng = StdRng::seed_from_u64(0);
+    assert!((0..100).all(|_| player.tick_interval(&mut rng) == 10));
+    let (event_sender, _) = tokio::sync::broadcast::channel::<Event>(1);
+    let (order_sender, _) = kanal::unbounded_async::<Order>();
+    let backwards = GenericPlayer::new(PlayerName::new("Backwards"), Strategy::Noisy, 20, 10, Some(0), StrategyConfig::default(), event_sender, Arc::new(order_sender));
+    assert!((0..100).all(|_| (10..=20).contains(&backwards.tick_interval(&mut rng))));
+
+    let task = tokio::spawn(async move { player.start().await });
+    subscribed(&events).await;
+    events.send(deal_to(&["Noisy", "Other"], HAND)).unwrap();
+    for _ in 0..3 {
+        tokio::time::timeout(Duration::from_secs(5), orders.recv()).await.expect("a fixed interval never ticked").unwrap();
+    }
+    task.abort();
+}
+
+// spades held to one tick in 400ms while the rest keep the 10ms pace: round robin offers every suit a turn, only the due ones take it
+#[tokio::test]
+async fn a_suit_override_sets_that_suit_s_pace() {
+    let (mut player, events, orders) = generic(Strategy::Noisy, StrategyConfig { noisy: NoisyConfig { mode: NoisyMode::RoundRobin }, ..Default::default() });
+    player.suit_frequency.insert(Card::Spade, (400, 400));
+    let task = tokio::spawn(async move { player.start().await });
+    subscribed(&events).await;
+    events.send(deal_to(&["Noisy", "Other"], HAND)).unwrap();
+    tokio::time::sleep(Duration::from_millis(2000)).await;
+    task.abort();
+
+    let mut sent = [0; 4];
+    while let Some(order) = orders.try_recv().unwrap() {
+        sent[order.card.get_index()] += 1;
+    }
+    assert!(sent[0] <= 3, "{:?}", sent); // the first second goes on waiting for the deal, which leaves spades three due ticks at most
+    assert!(sent[1..].iter().all(|count| *count >= 5), "{:?}", sent);
+}
+
+// a round that runs past round_seconds reads as 0 seconds left on both kinds of player, it doesn't wrap or panic
+#[tokio::test]
+async fn an_overrun_round_has_no_seconds_left() {
+    let clock = Arc::new(ManualClock::new());
+    let (mut generic_player, _events, _orders) = generic(Strategy::Noisy, StrategyConfig::default());
+    generic_player.clock = clock.clone();
+    *generic_player.timer.lock().await = clock.now();
+    let (mut event_player, _event_driven_events, _event_driven_orders) = event_driven(Strategy::PickOff, StrategyConfig::default());
+    event_player.clock = clock.clone();
+    event_player.timer = clock.now();
+
+    clock.advance(Duration::from_secs(generic_player.round_seconds - 5));
+    assert_eq!((generic_player.seconds_left().await, event_player.seconds_left()), (5, 5));
+    clock.advance(Duration::from_secs(3600));
+    assert_eq!((generic_player.seconds_left().await, event_player.seconds_left()), (0, 0));
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "sqlite")]
+
+use figgie_auto::{Card, Direction, PlayerName, SqliteLogger, Trade, TradeSink};
+use rusqlite::Connection;
+use std::time::Duration;
+
+
+// the writer runs on its own thread, so rows show up some time after they're logged
+fn wait_for_rows(connection: &Connection, expected: i64) {
+    for _ in 0..500 {
+        let count: i64 = connection.query_row("SELECT COUNT(*) FROM trades", [], |row| row.get(0)).unwrap_or(0);
+        if count >= expected {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    panic!("the trades never made it to the database");
+}
+
+// every trade handed to the sink comes back out of the trades table as it went in, in order
+#[test]
+fn logged_trades_read_back() {
+    let path = std::env::temp_dir().join(format!("figgie_trades_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let logger = SqliteLogger::new(path.to_str().unwrap()).unwrap();
+    let trades: Vec<Trade> = [(Card::Spade, 4), (Card::Heart, 9), (Card::Club, 2), (Card::Heart, 11)].into_iter().enumerate().map(|(index, (card, price))| {
+        Trade { card, price, buyer: PlayerName::new("Buyer"), seller: PlayerName::new("Seller"), aggressor: Direction::Buy, timestamp: index as u64 * 100 }
+    }).collect();
+    for trade in &trades {
+        logger.log(trade);
+    }
+
+    let connection = Connection::open(&path).unwrap();
+    wait_for_rows(&connection, trades.len() as i64);
+    let mut statement = connection.prepare("SELECT buyer, seller, card, price, aggressor, round_ms FROM trades ORDER BY id").unwrap();
+    let rows: Vec<(String, String, String, i64, String, i64)> = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?))).unwrap().map(Result::unwrap).collect();
+    let expected: Vec<(String, String, String, i64, String, i64)> = trades.iter().map(|trade| {
+        (format!("{:?}", trade.buyer), format!("{:?}", trade.seller), format!("{:?}", trade.card), trade.price as i64, format!("{:?}", trade.aggressor), trade.timestamp as i64)
+    }).collect();
+    assert_eq!(rows, expected);
+
+    drop(statement);
+    drop(connection);
+    let _ = std::fs::remove_file(&path);
+}